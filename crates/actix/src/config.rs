@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::env::VarError;
 use clap::Parser;
 use config::{Config, Environment, File};
@@ -23,8 +24,77 @@ pub struct ServerConfig {
     pub port: u16,
     pub excludes: Option<Vec<String>>,
     pub exclude_starts: Option<Vec<String>>,
+    /// 会话凭证来源
+    #[serde(default)]
+    pub session: SessionConfig,
 }
 
+/// 会话凭证: bearer 仅读取 Authorization 头; cookie 同时读取会话 cookie,
+/// 经 cookie 认证的写请求需携带与 CSRF cookie 一致的请求头
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// bearer / cookie
+    pub mode: String,
+    /// 会话 cookie, 保存 access token
+    pub cookie_name: String,
+    /// refresh token cookie, 仅发送到刷新接口
+    pub refresh_cookie_name: String,
+    pub refresh_path: String,
+    /// CSRF cookie, 前端读取后放入 csrf_header
+    pub csrf_cookie_name: String,
+    pub csrf_header: String,
+    pub domain: Option<String>,
+    pub path: String,
+    pub secure: bool,
+    /// Strict / Lax / None
+    pub same_site: String,
+}
+
+impl SessionConfig {
+    pub fn cookie_mode(&self) -> bool {
+        self.mode == "cookie"
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            mode: "bearer".to_string(),
+            cookie_name: "laurel_session".to_string(),
+            refresh_cookie_name: "laurel_refresh".to_string(),
+            refresh_path: "/api/system/account/token/refresh".to_string(),
+            csrf_cookie_name: "laurel_csrf".to_string(),
+            csrf_header: "X-CSRF-Token".to_string(),
+            domain: None,
+            path: "/".to_string(),
+            secure: true,
+            same_site: "Lax".to_string(),
+        }
+    }
+}
+
+/// 通过 uc 服务解析令牌
+#[derive(Debug, Deserialize, Clone)]
+pub struct RemoteTokenConfig {
+    pub uc_service: String,
+    /// 有效令牌缓存时间(秒), 默认30秒
+    pub positive_ttl: Option<u64>,
+    /// 无效令牌缓存时间(秒), 默认5秒
+    pub negative_ttl: Option<u64>,
+}
+/// 服务间调用签名
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignatureConfig {
+    /// 本服务作为调用方的名称与密钥
+    pub caller: String,
+    pub key: String,
+    /// 允许调用本服务 /interface 的调用方及其密钥
+    #[serde(default)]
+    pub callers: HashMap<String, String>,
+    /// 时间戳允许的偏差(秒), 默认300秒
+    pub window: Option<u64>,
+}
 
 pub fn load(env: Option<String>, path: Option<String>) -> anyhow::Result<config::Config>{
     let env_path = match env {
@@ -15,6 +15,10 @@ pub enum AppError {
     #[error("Authorization Error: {0}")]
     AuthError(String),
 
+    // 已认证但无权访问
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     // 其他自定义业务错误
     #[error("Resource Not Found")]
     NotFound,
@@ -29,20 +33,45 @@ pub enum AppError {
 }
 
 
+/// 带业务状态码的错误, 通过 anyhow 传递到路由层后按 code 返回
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct BizError {
+    pub code: u16,
+    pub message: String,
+    /// 附带的结构化明细, 如逐条的校验失败原因
+    pub data: Option<serde_json::Value>,
+}
+
+impl BizError {
+    pub fn new(code: u16, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
 // 为 AppError 实现 ResponseError，这是与 Actix-web 集成的关键
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
         let result = match self {
             AppError::ActixWeb(err) => {
                 error!("error: {}", err);
-                common::ApiResult::<()>::error_message(&err.to_string())
+                common::ApiResult::<serde_json::Value>::error_message(&err.to_string())
             },
-            AppError::AuthError(err) => common::ApiResult::<()>::without_data(401, &err.to_string()),
-            AppError::NotFound => common::ApiResult::<()>::without_data(404, "resource not found"),
-            AppError::InternalServerError => common::ApiResult::<()>::without_data(500, "server error"),
-            AppError::AnyhowError(err) => {
-                error!("error: {}", err);
-                common::ApiResult::<()>::error_message(&err.to_string())
+            AppError::AuthError(err) => common::ApiResult::<serde_json::Value>::without_data(401, &err.to_string()),
+            AppError::Forbidden(err) => common::ApiResult::<serde_json::Value>::without_data(403, err),
+            AppError::NotFound => common::ApiResult::<serde_json::Value>::without_data(404, "resource not found"),
+            AppError::InternalServerError => common::ApiResult::<serde_json::Value>::without_data(500, "server error"),
+            AppError::AnyhowError(err) => match err.downcast_ref::<BizError>() {
+                Some(biz) => common::ApiResult::new(biz.code, &biz.message, biz.data.clone()),
+                None => {
+                    error!("error: {}", err);
+                    common::ApiResult::<serde_json::Value>::error_message(&err.to_string())
+                },
             },
         };
         HttpResponse::build(StatusCode::OK)
@@ -6,12 +6,40 @@ use actix_web::dev::ServiceRequest;
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 use serde::{Deserialize, Serialize};
 use tracing::error;
-use crate::error::AppError;
+use crate::config::SessionConfig;
+use crate::error::{AppError, BizError};
+use crate::session;
 
 #[derive(Debug, Deserialize, Serialize, Eq, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Token{
     pub account_id: String,
+    #[serde(default)]
+    pub ticket_id: String,
+    /// 代登录时为实际操作的管理员, account_id 为被代登录的账户
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<String>,
+    /// 以 API key 认证时为 key id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// API key 限定的权限码, 为空时沿用账户全部权限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// OAuth2 客户端签发的令牌为客户端id, 只能访问 OIDC 接口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+}
+
+impl Token {
+    /// 是否为 OAuth2 客户端签发的令牌
+    pub fn is_oauth(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    /// 本人登录的会话: 非 API key、非代登录、非 OAuth2 客户端令牌, 账户安全设置只允许此类会话修改
+    pub fn is_session(&self) -> bool {
+        self.api_key.is_none() && self.impersonator.is_none() && !self.is_oauth()
+    }
 }
 
 pub type TokenResult<T> = Pin<Box<dyn Future<Output = anyhow::Result<T, Box<dyn std::error::Error>>> + Send>>;
@@ -20,6 +48,11 @@ pub trait TokenHandler{
     fn parse(&self, token: &str) ->  TokenResult<Option<Token>>;
 
     fn exclude(&self, url: &str) -> TokenResult<bool>;
+
+    /// 令牌能否访问该路径, 默认拒绝 OAuth2 客户端令牌
+    fn permit(&self, _url: &str, token: &Token) -> bool {
+        !token.is_oauth()
+    }
 }
 
 pub async fn validator(req: ServiceRequest, credentials: Option<BearerAuth>) ->Result<ServiceRequest, (Error, ServiceRequest)> {
@@ -37,16 +70,26 @@ pub async fn validator(req: ServiceRequest, credentials: Option<BearerAuth>) ->R
         }
     }
 
-    let token = match &credentials {
-        Some(x) => x.token(),
+    // 未配置时仅接受 Authorization 头
+    let session = req.app_data::<web::Data<SessionConfig>>().cloned();
+    let (token, from_cookie) = match session::credential(
+        req.request(),
+        credentials.as_ref().map(|c| c.token()),
+        session.as_ref().map(|c| c.get_ref()),
+    ) {
+        Some(x) => x,
         _ => return Err((AppError::AuthError("invalid token".to_string()).into(), req))
     };
-    if token.is_empty(){
-        return Err((AppError::AuthError("invalid token".to_string()).into(), req))
+    if from_cookie && let Some(config) = &session && !session::csrf_valid(req.request(), config) {
+        return Err((AppError::Forbidden("csrf token mismatch".to_string()).into(), req))
     }
+    let token = token.as_str();
     match token_service.parse(token).await{
         Ok(account) => {
             match account {
+                Some(account) if !token_service.permit(path, &account) => {
+                    Err((AppError::Forbidden(format!("token not allowed for {}", path)).into(), req))
+                },
                 Some(account) => {
                     req.extensions_mut().insert(account);
                     Ok(req)
@@ -55,6 +98,11 @@ pub async fn validator(req: ServiceRequest, credentials: Option<BearerAuth>) ->R
             }
         },
         Err(err) => {
+            // 业务拒绝(如账户停用)按原状态码返回
+            if let Some(biz) = err.downcast_ref::<BizError>() {
+                let biz = BizError { code: biz.code, message: biz.message.clone(), data: biz.data.clone() };
+                return Err((AppError::AnyhowError(biz.into()).into(), req))
+            }
             error!("parse token: {} error: {}", token, err);
             Err((AppError::AuthError(format!("invalid token: {}", token).to_string()).into(), req))
         }
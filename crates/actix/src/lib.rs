@@ -3,6 +3,10 @@ pub mod error;
 pub mod types;
 pub mod config;
 pub mod utils;
+pub mod permission;
+pub mod remote;
+pub mod signature;
+pub mod session;
 
 
 
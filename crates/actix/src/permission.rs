@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error, HttpMessage};
+use tracing::error;
+use crate::error::AppError;
+use crate::handler::{Token, TokenResult};
+
+/// 拥有该权限码的账户可访问全部受保护路由
+pub static SUPER_AUTHORITY: &str = "*";
+
+/// 解析账户的权限码
+pub trait PermissionHandler{
+    fn permissions(&self, account_id: &str) -> TokenResult<Arc<HashSet<String>>>;
+}
+
+/// 缓存条目上限, 超出时先清理过期条目
+static MAX_CACHE_ENTRIES: usize = 10000;
+
+type PermissionCache = HashMap<String, (Instant, Arc<HashSet<String>>)>;
+
+/// 带过期时间的权限缓存, 包装实际的 PermissionHandler
+pub struct CachedPermissionHandler {
+    inner: Arc<dyn PermissionHandler + Send + Sync>,
+    ttl: Duration,
+    cache: Arc<Mutex<PermissionCache>>,
+}
+
+impl CachedPermissionHandler {
+    pub fn new(inner: Arc<dyn PermissionHandler + Send + Sync>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 账户角色变更后移除其缓存
+    pub fn evict(&self, account_id: &str) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.remove(account_id);
+        }
+    }
+
+    /// 角色授权变更影响多个账户, 清空全部缓存
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+}
+
+impl std::fmt::Debug for CachedPermissionHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedPermissionHandler")
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl PermissionHandler for CachedPermissionHandler {
+    fn permissions(&self, account_id: &str) -> TokenResult<Arc<HashSet<String>>> {
+        if let Ok(cache) = self.cache.lock()
+            && let Some((at, permissions)) = cache.get(account_id)
+            && at.elapsed() < self.ttl {
+            let permissions = Arc::clone(permissions);
+            return Box::pin(async move { Ok(permissions) });
+        }
+        let inner = Arc::clone(&self.inner);
+        let cache = Arc::clone(&self.cache);
+        let ttl = self.ttl;
+        let account_id = account_id.to_string();
+        Box::pin(async move {
+            let permissions = inner.permissions(account_id.as_str()).await?;
+            if let Ok(mut cache) = cache.lock() {
+                if cache.len() >= MAX_CACHE_ENTRIES {
+                    cache.retain(|_, (at, _)| at.elapsed() < ttl);
+                    if cache.len() >= MAX_CACHE_ENTRIES {
+                        cache.clear();
+                    }
+                }
+                cache.insert(account_id, (Instant::now(), Arc::clone(&permissions)));
+            }
+            Ok(permissions)
+        })
+    }
+}
+
+/// 路由级权限校验, 需在认证之后执行:
+/// `#[post("/create", wrap = "Permission::new(\"role:edit\")")]`
+pub struct Permission {
+    code: &'static str,
+}
+
+impl Permission {
+    pub fn new(code: &'static str) -> Self {
+        Self { code }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Permission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = PermissionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(PermissionMiddleware {
+            service: Rc::new(service),
+            code: self.code,
+        }))
+    }
+}
+
+pub struct PermissionMiddleware<S> {
+    service: Rc<S>,
+    code: &'static str,
+}
+
+impl<S, B> Service<ServiceRequest> for PermissionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let code = self.code;
+        Box::pin(async move {
+            let (account_id, scopes) = match req.extensions().get::<Token>() {
+                Some(token) if token.is_oauth() => {
+                    return Err(AppError::Forbidden(format!("permission denied: {} not granted to oauth client", code)).into());
+                },
+                Some(token) => (token.account_id.clone(), token.scopes.clone()),
+                None => return Err(AppError::AuthError("invalid token".to_string()).into()),
+            };
+            // API key 只能使用限定范围内、且账户本身拥有的权限
+            if let Some(scopes) = scopes && !scopes.iter().any(|s| s == code) {
+                return Err(AppError::Forbidden(format!("permission denied: {} out of api key scope", code)).into());
+            }
+            let handler = match req.app_data::<web::Data<Arc<dyn PermissionHandler>>>().cloned() {
+                Some(handler) => handler,
+                None => {
+                    error!("permission handler component not found");
+                    return Err(AppError::InternalServerError.into());
+                },
+            };
+            match handler.permissions(account_id.as_str()).await {
+                Ok(permissions) if permissions.contains(code) || permissions.contains(SUPER_AUTHORITY) => {
+                    service.call(req).await
+                },
+                Ok(_) => Err(AppError::Forbidden(format!("permission denied: {}", code)).into()),
+                Err(err) => {
+                    error!("resolve permissions of account: {} error: {}", account_id, err);
+                    Err(AppError::Forbidden(format!("permission denied: {}", code)).into())
+                },
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpResponse};
+
+    struct SuperHandler;
+
+    impl PermissionHandler for SuperHandler {
+        fn permissions(&self, _account_id: &str) -> TokenResult<Arc<HashSet<String>>> {
+            Box::pin(async { Ok(Arc::new(HashSet::from([SUPER_AUTHORITY.to_string()]))) })
+        }
+    }
+
+    /// 以给定令牌访问受 system:role:view 保护的路由, 返回业务状态码
+    async fn guarded_code(token: Token) -> u64 {
+        let handler: Arc<dyn PermissionHandler> = Arc::new(SuperHandler);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(handler))
+                .wrap_fn(move |req, srv| {
+                    req.extensions_mut().insert(token.clone());
+                    srv.call(req)
+                })
+                .service(
+                    web::resource("/guarded")
+                        .wrap(Permission::new("system:role:view"))
+                        .to(|| async { HttpResponse::Ok().json(serde_json::json!({"code": 200})) }),
+                ),
+        )
+        .await;
+        let resp = match test::try_call_service(&app, test::TestRequest::get().uri("/guarded").to_request()).await {
+            Ok(res) => res.into_parts().1.map_into_boxed_body(),
+            Err(err) => err.error_response(),
+        };
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["code"].as_u64().unwrap()
+    }
+
+    fn token(client_id: Option<&str>, scopes: Option<Vec<String>>) -> Token {
+        Token {
+            account_id: "1".to_string(),
+            ticket_id: "t".to_string(),
+            impersonator: None,
+            api_key: None,
+            scopes,
+            client_id: client_id.map(|c| c.to_string()),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_oauth_token_forbidden() {
+        assert_eq!(guarded_code(token(None, None)).await, 200);
+        // 即使账户拥有全部权限, OAuth2 令牌也不能访问受保护路由
+        let oidc = Some(vec!["openid".to_string(), "profile".to_string()]);
+        assert_eq!(guarded_code(token(Some("client"), oidc)).await, 403);
+        assert_eq!(guarded_code(token(Some("client"), Some(vec!["system:role:view".to_string()]))).await, 403);
+    }
+}
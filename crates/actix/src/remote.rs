@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use laurel_uc_api::account::AccountApi;
+use tracing::{error, warn};
+use crate::config::RemoteTokenConfig;
+use crate::error::BizError;
+use crate::handler::{Token, TokenHandler, TokenResult};
+
+/// 缓存条目上限, 超出时先清理过期条目
+static MAX_CACHE_ENTRIES: usize = 10000;
+/// uc 服务不可用时返回的状态码
+static UNAVAILABLE: u16 = 503;
+
+type TokenCache = HashMap<String, (Instant, Option<Token>)>;
+
+/// 调用 uc 服务解析令牌, 有效与无效结果均短暂缓存在进程内
+pub struct RemoteTokenHandler {
+    account_api: AccountApi,
+    exclude_paths: Vec<String>,
+    exclude_start_path: Vec<String>,
+    positive_ttl: Duration,
+    negative_ttl: Duration,
+    cache: Arc<Mutex<TokenCache>>,
+}
+
+impl RemoteTokenHandler {
+    pub fn new(
+        account_api: AccountApi,
+        exclude_paths: Vec<String>,
+        exclude_start_path: Vec<String>,
+        config: &RemoteTokenConfig,
+    ) -> Self {
+        Self {
+            account_api,
+            exclude_paths,
+            exclude_start_path,
+            positive_ttl: Duration::from_secs(config.positive_ttl.unwrap_or(30)),
+            negative_ttl: Duration::from_secs(config.negative_ttl.unwrap_or(5)),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl TokenHandler for RemoteTokenHandler {
+    fn parse(&'_ self, token: &str) -> TokenResult<Option<Token>> {
+        if let Ok(cache) = self.cache.lock()
+            && let Some((expire_at, t)) = cache.get(token)
+            && *expire_at > Instant::now() {
+            let t = t.clone();
+            return Box::pin(async move { Ok(t) });
+        }
+        let account_api = self.account_api.clone();
+        let cache = Arc::clone(&self.cache);
+        let (positive_ttl, negative_ttl) = (self.positive_ttl, self.negative_ttl);
+        let token = token.to_string();
+        Box::pin(async move {
+            // uc 服务不可用时返回错误而非无效令牌, 避免客户端据此退出登录
+            let resp = match account_api.parse_token(token.as_str()).await {
+                Ok(resp) => resp,
+                Err(err) => {
+                    error!("remote parse token error: {}", err);
+                    return Err(BizError::new(UNAVAILABLE, "uc service unavailable").into());
+                }
+            };
+            if !resp.is_successful() {
+                if resp.code >= 500 {
+                    error!("remote parse token error: {} {}", resp.code, resp.message);
+                    return Err(BizError::new(UNAVAILABLE, "uc service unavailable").into());
+                }
+                // 令牌无效, 不缓存
+                if resp.code == 401 {
+                    warn!("remote parse token rejected: {}", resp.message);
+                    return Ok(None);
+                }
+                // 业务拒绝(如账户停用、会话被挤下线)按原状态码返回, 不缓存
+                return Err(BizError::new(resp.code, resp.message).into());
+            }
+            let t = resp.data.map(|p| Token {
+                account_id: p.account_id,
+                ticket_id: p.ticket_id,
+                impersonator: p.impersonator,
+                api_key: p.api_key,
+                scopes: p.scopes,
+                client_id: p.client_id,
+            });
+            let ttl = if t.is_some() { positive_ttl } else { negative_ttl };
+            if let Ok(mut cache) = cache.lock() {
+                if cache.len() >= MAX_CACHE_ENTRIES {
+                    let now = Instant::now();
+                    cache.retain(|_, (expire_at, _)| *expire_at > now);
+                    if cache.len() >= MAX_CACHE_ENTRIES {
+                        cache.clear();
+                    }
+                }
+                cache.insert(token, (Instant::now() + ttl, t.clone()));
+            }
+            Ok(t)
+        })
+    }
+
+    fn exclude(&'_ self, url: &str) -> TokenResult<bool> {
+        let exclude = self.exclude_paths.iter().any(|path| path == url)
+            || self
+            .exclude_start_path
+            .iter()
+            .any(|path| url.starts_with(path));
+        Box::pin(async move { Ok(exclude) })
+    }
+}
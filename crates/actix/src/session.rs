@@ -0,0 +1,102 @@
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::cookie::time::Duration;
+use actix_web::http::Method;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use crate::config::SessionConfig;
+use crate::types::common::ApiResult;
+
+/// 请求携带的会话令牌, 及是否取自 cookie
+pub fn credential(req: &HttpRequest, bearer: Option<&str>, config: Option<&SessionConfig>) -> Option<(String, bool)> {
+    if let Some(token) = bearer.filter(|t| !t.is_empty()) {
+        return Some((token.to_string(), false));
+    }
+    let config = config.filter(|c| c.cookie_mode())?;
+    req.cookie(config.cookie_name.as_str())
+        .map(|c| c.value().to_string())
+        .filter(|t| !t.is_empty())
+        .map(|t| (t, true))
+}
+
+/// double-submit 校验: 写请求的 CSRF 请求头须与 CSRF cookie 一致
+pub fn csrf_valid(req: &HttpRequest, config: &SessionConfig) -> bool {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE) {
+        return true;
+    }
+    let header = req
+        .headers()
+        .get(config.csrf_header.as_str())
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+    match req.cookie(config.csrf_cookie_name.as_str()) {
+        Some(cookie) => !header.is_empty() && constant_eq(header.as_bytes(), cookie.value().as_bytes()),
+        None => false,
+    }
+}
+
+fn constant_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn cookie(config: &SessionConfig, name: &str, value: String, path: &str, http_only: bool, max_age: i64) -> Cookie<'static> {
+    let same_site = match config.same_site.to_lowercase().as_str() {
+        "strict" => SameSite::Strict,
+        "none" => SameSite::None,
+        _ => SameSite::Lax,
+    };
+    let mut cookie = Cookie::build(name.to_string(), value)
+        .path(path.to_string())
+        .http_only(http_only)
+        .secure(config.secure)
+        .same_site(same_site)
+        .max_age(Duration::seconds(max_age))
+        .finish();
+    if let Some(domain) = &config.domain {
+        cookie.set_domain(domain.clone());
+    }
+    cookie
+}
+
+/// 登录或刷新后下发的 cookie, 有效期与票据一致
+pub fn issue_cookies(config: &SessionConfig, token: &str, refresh_token: &str, csrf_token: &str, max_age: i64) -> Vec<Cookie<'static>> {
+    vec![
+        cookie(config, config.cookie_name.as_str(), token.to_string(), config.path.as_str(), true, max_age),
+        cookie(config, config.refresh_cookie_name.as_str(), refresh_token.to_string(), config.refresh_path.as_str(), true, max_age),
+        cookie(config, config.csrf_cookie_name.as_str(), csrf_token.to_string(), config.path.as_str(), false, max_age),
+    ]
+}
+
+/// 退出登录时清除全部会话 cookie
+pub fn clear_cookies(config: &SessionConfig) -> Vec<Cookie<'static>> {
+    vec![
+        cookie(config, config.cookie_name.as_str(), String::new(), config.path.as_str(), true, 0),
+        cookie(config, config.refresh_cookie_name.as_str(), String::new(), config.refresh_path.as_str(), true, 0),
+        cookie(config, config.csrf_cookie_name.as_str(), String::new(), config.path.as_str(), false, 0),
+    ]
+}
+
+/// 带 cookie 的统一响应
+pub fn respond<T: Serialize>(result: ApiResult<T>, cookies: Vec<Cookie<'static>>) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    for cookie in cookies {
+        response.cookie(cookie);
+    }
+    response.json(result)
+}
+
+#[test]
+fn test_csrf_valid() {
+    use actix_web::test::TestRequest;
+    let config = SessionConfig { mode: "cookie".to_string(), ..Default::default() };
+    let csrf = Cookie::new("laurel_csrf", "abc");
+    assert!(csrf_valid(&TestRequest::get().to_http_request(), &config));
+    assert!(!csrf_valid(&TestRequest::post().cookie(csrf.clone()).to_http_request(), &config));
+    assert!(!csrf_valid(
+        &TestRequest::post().cookie(csrf.clone()).insert_header(("X-CSRF-Token", "abd")).to_http_request(),
+        &config,
+    ));
+    assert!(csrf_valid(
+        &TestRequest::post().cookie(csrf).insert_header(("X-CSRF-Token", "abc")).to_http_request(),
+        &config,
+    ));
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error};
+use laurel_middleware::signature;
+use laurel_redis::Redis;
+use tracing::{error, warn};
+use crate::config::SignatureConfig;
+use crate::error::AppError;
+
+static NONCE_PREFIX: &str = "laurel:signature:nonce:";
+
+/// 校验服务间调用签名, 并通过 redis 拒绝重放的 nonce
+#[derive(Debug, Clone)]
+pub struct SignatureVerifier {
+    callers: HashMap<String, String>,
+    window: Duration,
+    /// 未配置时拒绝全部 /interface 调用
+    redis: Option<Redis>,
+}
+
+impl SignatureVerifier {
+    pub fn new(config: &SignatureConfig, redis: Redis) -> Self {
+        Self {
+            callers: config.callers.clone(),
+            window: Duration::from_secs(config.window.unwrap_or(300)),
+            redis: Some(redis),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        warn!("signature config not found, all /interface calls are rejected");
+        Self {
+            callers: HashMap::new(),
+            window: Duration::ZERO,
+            redis: None,
+        }
+    }
+
+    fn header<'a>(req: &'a ServiceRequest, name: &str) -> Result<&'a str, String> {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("missing header: {}", name))
+    }
+
+    /// 校验签名, body 为完整的请求体
+    pub async fn verify(&self, req: &ServiceRequest, body: &[u8]) -> Result<(), String> {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Err("signature not configured".to_string()),
+        };
+        let caller = Self::header(req, signature::HEADER_CALLER)?;
+        let timestamp = Self::header(req, signature::HEADER_TIMESTAMP)?;
+        let nonce = Self::header(req, signature::HEADER_NONCE)?;
+        let sign = Self::header(req, signature::HEADER_SIGNATURE)?;
+        let key = self
+            .callers
+            .get(caller)
+            .ok_or_else(|| format!("unknown caller: {}", caller))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let ts = timestamp.parse::<u64>().map_err(|_| "invalid timestamp".to_string())?;
+        if now.abs_diff(ts) > self.window.as_secs() {
+            return Err("signature expired".to_string());
+        }
+        let path = req
+            .uri()
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or(req.path());
+        if !signature::verify(key, req.method().as_str(), path, timestamp, nonce, body, sign) {
+            return Err("invalid signature".to_string());
+        }
+
+        // nonce 保留两倍窗口, 覆盖时间戳前后的偏差
+        let nonce_key = format!("{}{}:{}", NONCE_PREFIX, caller, nonce);
+        match redis.set_nx_with_expire(nonce_key.as_str(), "1", self.window * 2).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("nonce replayed".to_string()),
+            Err(err) => {
+                error!("save signature nonce: {} error: {}", nonce_key, err);
+                Err("nonce check failed".to_string())
+            }
+        }
+    }
+}
+
+/// /interface 路由的签名校验, 需注册 `web::Data<SignatureVerifier>`:
+/// `web::scope("/interface/...").wrap(Signed)`
+pub struct Signed;
+
+impl<S, B> Transform<S, ServiceRequest> for Signed
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SignedMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SignedMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct SignedMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SignedMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            let verifier = req
+                .app_data::<web::Data<SignatureVerifier>>()
+                .cloned()
+                .expect("signature verifier component not found");
+            // 读取请求体参与验签, 再放回供后续提取
+            let body = req.extract::<web::Bytes>().await?;
+            req.set_payload(Payload::from(body.clone()));
+            match verifier.verify(&req, &body).await {
+                Ok(()) => service.call(req).await,
+                Err(err) => {
+                    warn!("verify signature of {} error: {}", req.path(), err);
+                    Err(AppError::AuthError(err).into())
+                }
+            }
+        })
+    }
+}
@@ -5,6 +5,8 @@ use actix_web::{web};
 pub mod route{
     pub use crate::error::AppError;
     pub type Result<T> = core::result::Result<crate::types::common::ApiResult<T>, AppError>;
+    /// 需要自行构造响应(如写 cookie)的路由
+    pub type Response = core::result::Result<actix_web::HttpResponse, AppError>;
 
     #[allow(non_snake_case)]
     #[macro_export]
@@ -8,6 +8,8 @@ pub mod logs{
     #[derive(Clone, Debug)]
     pub struct LogApi(laurel_middleware::request::Client);
     static LOGIN_LOG_URI: &'static str = "/interface/logs/login/create";
+    static LOGIN_LOG_TICKETS_URI: &'static str = "/interface/logs/login/tickets";
+    static AUDIT_LOG_URI: &'static str = "/interface/logs/audit/create";
 
     impl LogApi{
         pub fn build(client: Arc<ClientWithMiddleware>, host: String, path: Option<String>) -> Self{
@@ -30,6 +32,31 @@ pub mod logs{
                 .await?;
             Ok(resp)
         }
+
+        /// 按票据查询登录成功时记录的登录日志
+        pub async fn list_login_logs_by_tickets(&self, req: &LoginLogTicketsReqBo) -> api::Result<Vec<LoginLogBo>>{
+            let url = self.0.url(LOGIN_LOG_TICKETS_URI);
+            let resp = self.0.client()
+                .post(url)
+                .json(req)
+                .send()
+                .await?
+                .json::<api::ApiResult<Vec<LoginLogBo>>>()
+                .await?;
+            Ok(resp)
+        }
+
+        pub async fn save_audit_log(&self, req: &AuditLogCreateReqBo) -> api::Result<i64>{
+            let url = self.0.url(AUDIT_LOG_URI);
+            let resp = self.0.client()
+                .post(url)
+                .json(req)
+                .send()
+                .await?
+                .json::<api::ApiResult<i64>>()
+                .await?;
+            Ok(resp)
+        }
     }
 
 
@@ -52,9 +79,50 @@ pub mod logs{
         pub browser: Option<String>,
         pub os: Option<String>,
         pub device: Option<String>,
+        /// 是否为首次出现的设备
+        #[serde(default)]
+        pub new_device: bool,
+        // yyyy-MM-dd HH:mm:ss
+        pub login_cts: String,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LoginLogTicketsReqBo{
+        pub ticket_ids: Vec<String>,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LoginLogBo{
+        pub ticket_id: String,
+        pub account: String,
+        pub login_type: String,
+        pub login_state: String,
+        pub ip: Option<String>,
+        pub location: Option<String>,
+        pub browser: Option<String>,
+        pub os: Option<String>,
+        pub device: Option<String>,
+        #[serde(default)]
+        pub new_device: bool,
         // yyyy-MM-dd HH:mm:ss
         pub login_cts: String,
     }
 
+    #[derive(Deserialize, Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AuditLogCreateReqBo{
+        // 操作人
+        pub operator: String,
+        pub action: String,
+        // 操作对象, 如 account_id / ticket_id
+        pub target: String,
+        pub detail: Option<String>,
+        pub ip: Option<String>,
+        // yyyy-MM-dd HH:mm:ss
+        pub audit_cts: String,
+    }
+
 
 }
\ No newline at end of file
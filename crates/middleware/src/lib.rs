@@ -1,4 +1,5 @@
 pub mod reqwest_middle;
+pub mod signature;
 
 pub mod request{
     use std::sync::Arc;
@@ -140,10 +140,65 @@ impl Redis {
         Ok(())
     }
 
+    /// key 不存在时写入并设置过期时间, 返回是否写入成功
+    pub async fn set_nx_with_expire<V>(&self, key: &str, value: V, duration: Duration) -> Result<bool, Error>
+    where V: TryInto<Value> + Send,
+          V::Error: Into<Error> + Send,
+    {
+        let expire = Some(
+            Expiration::EX( duration.as_secs() as i64 )
+        );
+        let result: Option<String> = self.0.set::<Option<String>, &str, V>(key, value, expire, Some(SetOptions::NX), false).await?;
+        Ok(result.is_some())
+    }
+
+    /// 自增并返回新值, key 不存在时从 0 开始
+    pub async fn incr(&self, key: &str) -> Result<i64, Error>{
+        self.0.incr::<i64, &str>(key).await
+    }
+
+    pub async fn del(&self, key: &str) -> Result<(), Error>{
+        let _: () = self.0.del::<(), &str>(key).await?;
+        Ok(())
+    }
+
     pub async fn expire<V>(&self, key: &str, duration: Duration) -> Result<(), Error>{
         let _: () = self.0.expire::<(), &str>(key, duration.as_secs() as i64, None).await?;
         Ok(())
     }
+
+    pub async fn exists(&self, key: &str) -> Result<bool, Error>{
+        Ok(self.0.exists::<i64, &str>(key).await? > 0)
+    }
+
+    /// 剩余过期秒数, key 不存在为 -2, 无过期时间为 -1
+    pub async fn ttl(&self, key: &str) -> Result<i64, Error>{
+        self.0.ttl::<i64, &str>(key).await
+    }
+
+    pub async fn zadd(&self, key: &str, score: f64, member: &str) -> Result<(), Error>{
+        let _: () = self.0.zadd::<(), &str, (f64, &str)>(key, None, None, false, false, (score, member)).await?;
+        Ok(())
+    }
+
+    pub async fn zremrangebyscore(&self, key: &str, min: f64, max: f64) -> Result<i64, Error>{
+        self.0.zremrangebyscore::<i64, &str, f64, f64>(key, min, max).await
+    }
+
+    pub async fn zcard(&self, key: &str) -> Result<i64, Error>{
+        self.0.zcard::<i64, &str>(key).await
+    }
+
+    pub async fn zrem(&self, key: &str, member: &str) -> Result<i64, Error>{
+        self.0.zrem::<i64, &str, &str>(key, member).await
+    }
+
+    /// 执行 lua 脚本, 集群模式下 keys 须位于同一 slot
+    pub async fn eval<R>(&self, script: &str, keys: Vec<String>, args: Vec<String>) -> Result<R, Error>
+    where R: FromValue
+    {
+        self.0.eval::<R, &str, Vec<String>, Vec<String>>(script, keys, args).await
+    }
 }
 
 
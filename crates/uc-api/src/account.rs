@@ -25,6 +25,20 @@ pub struct AccountBo{
 #[serde(rename_all = "camelCase")]
 pub struct TokenPayloadBo{
     pub account_id: String,
+    #[serde(default)]
+    pub ticket_id: String,
+    /// 代登录的管理员账户
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<String>,
+    /// API key 的 key id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// API key 限定的权限码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// OAuth2 客户端id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -49,7 +63,7 @@ impl AccountApi{
     }
 
     pub async fn find_with_id(&self, account_id: &str) -> api::Result<AccountBo>{
-        let url = self.0.url("/interface/uc/account");
+        let url = self.0.url("/interface/system/uc/account");
         let resp = self.0.client()
             .get(url)
             .query(&[("accountId", account_id)])
@@ -61,7 +75,7 @@ impl AccountApi{
     }
 
     pub async fn find_with_account(&self, account_name: &str, account_type: &str)-> api::Result<AccountBo>{
-        let url = self.0.url("/interface/uc/account");
+        let url = self.0.url("/interface/system/uc/account");
         let resp = self.0.client()
             .get(url)
             .query(&[("accountName", account_name), ("accountType", account_type)])
@@ -73,7 +87,7 @@ impl AccountApi{
     }
 
     pub async fn parse_token(&self, token: &str) -> api::Result<TokenPayloadBo>{
-        let url = self.0.url("/interface/uc/account/token");
+        let url = self.0.url("/interface/system/uc/account/token");
         let resp = self.0.client()
             .get(url)
             .query(&[("token", token)])
@@ -38,7 +38,7 @@ impl ProfileApi{
     }
 
     pub async fn list_profiles(&self, query: &ProfileQuery) -> api::Result<Vec<ProfileBo>>{
-        let url = self.0.url("/interface/uc/profile/profiles");
+        let url = self.0.url("/interface/system/uc/profile/profiles");
         let resp = self.0.client()
             .post(url)
             .json(&query)
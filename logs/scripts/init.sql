@@ -19,6 +19,7 @@ CREATE TABLE login_log(
                           browser VARCHAR(128) DEFAULT NULL,
                           os VARCHAR(128) DEFAULT NULL,
                           device VARCHAR(128) DEFAULT NULL,
+                          new_device BOOLEAN NOT NULL DEFAULT FALSE,
                           cts TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                           login_cts TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                           CONSTRAINT uniq_ti UNIQUE (ticket_id)
@@ -26,4 +27,19 @@ CREATE TABLE login_log(
 
 CREATE INDEX login_log_idx_account ON login_log (account);
 CREATE INDEX login_log_idx_login_status ON login_log (login_state);
-CREATE INDEX login_log_idx_ip ON login_log (ip);
\ No newline at end of file
+CREATE INDEX login_log_idx_ip ON login_log (ip);
+
+CREATE TABLE audit_log(
+                          id BIGSERIAL NOT NULL PRIMARY KEY,
+                          operator VARCHAR(40) NOT NULL,
+                          action VARCHAR(40) NOT NULL,
+                          target VARCHAR(64) NOT NULL,
+                          detail VARCHAR(400) DEFAULT NULL,
+                          ip VARCHAR(64) DEFAULT NULL,
+                          cts TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                          audit_cts TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX audit_log_idx_operator ON audit_log (operator);
+CREATE INDEX audit_log_idx_target ON audit_log (target);
+CREATE INDEX audit_log_idx_action ON audit_log (action);
\ No newline at end of file
@@ -8,7 +8,8 @@ mod setup;
 
 use actix_web::{App, HttpResponse, HttpServer, web};
 use clap::Parser;
-use laurel_actix::config::{AppArgs, ServerConfig, load_config};
+use laurel_actix::config::{AppArgs, RemoteTokenConfig, ServerConfig, SignatureConfig, load_config};
+use laurel_redis::{Redis, RedisConfig};
 use laurel_actix::{ActixApp};
 use laurel_logging::types::LogConfig;
 use laurel_pg::DbPool;
@@ -30,6 +31,11 @@ pub struct LogsAppConfig {
     pub server_config: ServerConfig,
     pub db_config: DbConfig,
     pub log_config: LogConfig,
+    pub redis_config: RedisConfig,
+    /// 配置后通过 uc 服务校验令牌
+    pub token_config: Option<RemoteTokenConfig>,
+    /// 服务间调用签名, 未配置时拒绝全部 /interface 请求
+    pub signature_config: Option<SignatureConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -41,14 +47,14 @@ pub struct SystemApiConfig {
 #[actix_web::main]
 #[allow(deprecated, unused_mut)]
 async fn main() -> std::io::Result<()> {
-    let (app_config, pool) = setup().await;
+    let (app_config, pool, redis) = setup().await;
     let (host, port) = (
         (&app_config).server_config.host.clone(),
         (&app_config).server_config.port,
     );
     HttpServer::new(move || {
         let mut app = ActixApp!().configure(route::config).configure(|cfg| {
-            setup::components::load_components(cfg, (&app_config).clone(), pool.clone())
+            setup::components::load_components(cfg, (&app_config).clone(), pool.clone(), redis.clone())
         });
         app
     })
@@ -59,7 +65,7 @@ async fn main() -> std::io::Result<()> {
 }
 
 #[allow(deprecated)]
-async fn setup() -> (LogsAppConfig, DbPool) {
+async fn setup() -> (LogsAppConfig, DbPool, Redis) {
     let args = AppArgs::parse();
     let app_config: LogsAppConfig =
         load_config::<LogsAppConfig>(None, args.config).expect("Failed to load config");
@@ -67,5 +73,8 @@ async fn setup() -> (LogsAppConfig, DbPool) {
     let pool = laurel_pg::setup(&app_config.db_config)
         .await
         .expect("Failed to setup db");
-    (app_config, pool)
+    let redis = Redis::new(&app_config.redis_config)
+        .await
+        .expect("Failed to create redis pool");
+    (app_config, pool, redis)
 }
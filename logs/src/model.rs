@@ -1,9 +1,22 @@
+use chrono::NaiveDateTime;
+use laurel_actix::error::BizError;
+use laurel_common::date_time;
+
+/// 解析查询条件中的时间, 格式错误时返回 400
+fn parse_query_time(field: &str, value: &Option<String>) -> anyhow::Result<Option<NaiveDateTime>> {
+    value
+        .as_ref()
+        .map(|v| NaiveDateTime::parse_from_str(v.as_str(), date_time::DTF))
+        .transpose()
+        .map_err(|_| BizError::new(400, format!("invalid {}, expected %Y-%m-%d %H:%M:%S", field)).into())
+}
+
 pub mod login_log {
     use chrono::{Local, NaiveDateTime};
     use diesel::{Identifiable, Insertable, Queryable, Selectable};
     use laurel_common::date_time;
     use laurel_common::types::{IndexAble, PageQuery};
-    use laurel_logs_api::logs::LoginLogCreateReqBo;
+    use laurel_logs_api::logs::{LoginLogBo, LoginLogCreateReqBo};
     use serde::{Deserialize, Serialize};
     use tracing::error;
 
@@ -22,6 +35,7 @@ pub mod login_log {
         pub browser: Option<String>,
         pub os: Option<String>,
         pub device: Option<String>,
+        pub new_device: bool,
         pub cts: NaiveDateTime,
         pub login_cts: NaiveDateTime,
     }
@@ -39,6 +53,7 @@ pub mod login_log {
         pub browser: Option<String>,
         pub os: Option<String>,
         pub device: Option<String>,
+        pub new_device: bool,
         pub cts: NaiveDateTime,
         pub login_cts: NaiveDateTime,
     }
@@ -56,6 +71,7 @@ pub mod login_log {
                 browser: bo.browser.clone(),
                 os: bo.os.clone(),
                 device: bo.device.clone(),
+                new_device: bo.new_device,
                 cts: Local::now().naive_local(),
                 login_cts: match NaiveDateTime::parse_from_str(
                     bo.login_cts.as_str(),
@@ -79,29 +95,23 @@ pub mod login_log {
         pub account: &'a Option<String>,
         pub ip: &'a Option<String>,
         pub login_state: &'a Option<String>,
+        pub new_device: Option<bool>,
         pub login_cts_start: Option<NaiveDateTime>,
         pub login_cts_end: Option<NaiveDateTime>,
     }
 
-    impl<'a> From<&'a LoginLogQueryReq> for QueryableLoginLog<'a> {
-        fn from(req: &'a LoginLogQueryReq) -> Self {
-            QueryableLoginLog {
+    impl<'a> TryFrom<&'a LoginLogQueryReq> for QueryableLoginLog<'a> {
+        type Error = anyhow::Error;
+
+        fn try_from(req: &'a LoginLogQueryReq) -> Result<Self, Self::Error> {
+            Ok(QueryableLoginLog {
                 account: &req.account,
                 ip: &req.ip,
                 login_state: &req.login_state,
-                login_cts_start: match &req.login_cts_start {
-                    Some(cs) => Some(
-                        NaiveDateTime::parse_from_str(cs.as_str(), date_time::DTF).expect("Invalid date time for LoginLogQueryReq(login_cts_start, %Y-%m-%d %H:%M:%S)")
-                    ),
-                    None => None,
-                },
-                login_cts_end: match &req.login_cts_end {
-                    Some(cs) => Some(
-                        NaiveDateTime::parse_from_str(cs.as_str(), date_time::DTF).expect("Invalid date time for LoginLogQueryReq(login_cts_end, %Y-%m-%d %H:%M:%S)")
-                    ),
-                    None => None,
-                },
-            }
+                new_device: req.new_device,
+                login_cts_start: super::parse_query_time("loginCtsStart", &req.login_cts_start)?,
+                login_cts_end: super::parse_query_time("loginCtsEnd", &req.login_cts_end)?,
+            })
         }
     }
 
@@ -119,6 +129,8 @@ pub mod login_log {
         pub browser: Option<String>,
         pub os: Option<String>,
         pub device: Option<String>,
+        /// 是否为首次出现的设备
+        pub new_device: bool,
         pub cts: String,
         pub login_cts: String,
     }
@@ -144,21 +156,174 @@ pub mod login_log {
                 browser: value.browser,
                 os: value.os,
                 device: value.device,
+                new_device: value.new_device,
                 cts: value.cts.format(date_time::DTF).to_string(),
                 login_cts: value.login_cts.format(date_time::DTF).to_string(),
             }
         }
     }
 
+    impl From<LoginLog> for LoginLogBo {
+        fn from(value: LoginLog) -> Self {
+            LoginLogBo {
+                ticket_id: value.ticket_id,
+                account: value.account,
+                login_type: value.login_type,
+                login_state: value.login_state,
+                ip: value.ip,
+                location: value.location,
+                browser: value.browser,
+                os: value.os,
+                device: value.device,
+                new_device: value.new_device,
+                login_cts: value.login_cts.format(date_time::DTF).to_string(),
+            }
+        }
+    }
+
     #[derive(Deserialize, Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct LoginLogQueryReq {
         pub account: Option<String>,
         pub ip: Option<String>,
         pub login_state: Option<String>,
+        /// 仅查询新设备登录
+        pub new_device: Option<bool>,
         pub login_cts_start: Option<String>,
         pub login_cts_end: Option<String>,
         #[serde(flatten)]
         pub page: Option<PageQuery>,
     }
 }
+
+pub mod audit_log {
+    use chrono::{Local, NaiveDateTime};
+    use diesel::{Identifiable, Insertable, Queryable, Selectable};
+    use laurel_common::date_time;
+    use laurel_common::types::{IndexAble, PageQuery};
+    use laurel_logs_api::logs::AuditLogCreateReqBo;
+    use serde::{Deserialize, Serialize};
+    use tracing::error;
+
+    #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+    #[diesel(table_name = crate::schema::audit_log)]
+    #[diesel(check_for_backend(diesel::pg::Pg))]
+    pub struct AuditLog {
+        pub id: i64,
+        pub operator: String,
+        pub action: String,
+        pub target: String,
+        pub detail: Option<String>,
+        pub ip: Option<String>,
+        pub cts: NaiveDateTime,
+        pub audit_cts: NaiveDateTime,
+    }
+
+    #[derive(Debug, Insertable)]
+    #[diesel(table_name = crate::schema::audit_log)]
+    pub struct InsertableAuditLog<'a> {
+        pub operator: &'a str,
+        pub action: &'a str,
+        pub target: &'a str,
+        pub detail: Option<String>,
+        pub ip: Option<String>,
+        pub cts: NaiveDateTime,
+        pub audit_cts: NaiveDateTime,
+    }
+
+    impl<'a> From<&'a AuditLogCreateReqBo> for InsertableAuditLog<'a> {
+        fn from(bo: &'a AuditLogCreateReqBo) -> Self {
+            InsertableAuditLog {
+                operator: bo.operator.as_str(),
+                action: bo.action.as_str(),
+                target: bo.target.as_str(),
+                detail: bo.detail.clone(),
+                ip: bo.ip.clone(),
+                cts: Local::now().naive_local(),
+                audit_cts: match NaiveDateTime::parse_from_str(
+                    bo.audit_cts.as_str(),
+                    date_time::DTF,
+                ) {
+                    Ok(cts) => cts,
+                    Err(err) => {
+                        error!(
+                            "Invalid date time for AuditLogCreateReqBo({:?}.audit_cts, %Y-%m-%d %H:%M:%S): {}",
+                            bo, err
+                        );
+                        Local::now().naive_local()
+                    }
+                },
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct QueryableAuditLog<'a> {
+        pub operator: &'a Option<String>,
+        pub action: &'a Option<String>,
+        pub target: &'a Option<String>,
+        pub audit_cts_start: Option<NaiveDateTime>,
+        pub audit_cts_end: Option<NaiveDateTime>,
+    }
+
+    impl<'a> TryFrom<&'a AuditLogQueryReq> for QueryableAuditLog<'a> {
+        type Error = anyhow::Error;
+
+        fn try_from(req: &'a AuditLogQueryReq) -> Result<Self, Self::Error> {
+            Ok(QueryableAuditLog {
+                operator: &req.operator,
+                action: &req.action,
+                target: &req.target,
+                audit_cts_start: super::parse_query_time("auditCtsStart", &req.audit_cts_start)?,
+                audit_cts_end: super::parse_query_time("auditCtsEnd", &req.audit_cts_end)?,
+            })
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AuditLogVo {
+        pub index: u32,
+        pub operator: String,
+        pub action: String,
+        pub target: String,
+        pub detail: Option<String>,
+        pub ip: Option<String>,
+        pub cts: String,
+        pub audit_cts: String,
+    }
+
+    impl IndexAble for AuditLogVo {
+        fn set_index(&mut self, index: u32) -> &mut Self {
+            self.index = index;
+            self
+        }
+    }
+
+    impl From<AuditLog> for AuditLogVo {
+        fn from(value: AuditLog) -> Self {
+            AuditLogVo {
+                index: 0u32,
+                operator: value.operator,
+                action: value.action,
+                target: value.target,
+                detail: value.detail,
+                ip: value.ip,
+                cts: value.cts.format(date_time::DTF).to_string(),
+                audit_cts: value.audit_cts.format(date_time::DTF).to_string(),
+            }
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AuditLogQueryReq {
+        pub operator: Option<String>,
+        pub action: Option<String>,
+        pub target: Option<String>,
+        pub audit_cts_start: Option<String>,
+        pub audit_cts_end: Option<String>,
+        #[serde(flatten)]
+        pub page: Option<PageQuery>,
+    }
+}
@@ -33,6 +33,24 @@ pub mod login_log {
             Ok(id)
         }
 
+        /// 票据对应的登录成功日志
+        pub async fn list_by_tickets(&self, ticket_ids: &[String]) -> repository::Result<Vec<LoginLog>> {
+            if ticket_ids.is_empty() {
+                return Ok(vec![]);
+            }
+            let mut conn = self.pool.get().await?;
+            let list = AsyncDsl::load(
+                LoginLogDsl::login_log
+                    .filter(LoginLogDsl::ticket_id.eq_any(ticket_ids))
+                    .filter(LoginLogDsl::login_state.eq("normal"))
+                    .order_by(LoginLogDsl::id.desc())
+                    .select(LoginLog::as_select()),
+                &mut conn,
+            )
+            .await?;
+            Ok(list)
+        }
+
         pub async fn page<'a>(
             &self,
             queryable: &'a QueryableLoginLog<'a>,
@@ -94,6 +112,9 @@ pub mod login_log {
             {
                 query = query.filter(LoginLogDsl::login_state.eq(param.as_str()))
             }
+            if let Some(param) = queryable.new_device {
+                query = query.filter(LoginLogDsl::new_device.eq(param))
+            }
 
             if let (Some(cs), Some(ce)) = (queryable.login_cts_start, queryable.login_cts_end) {
                 query = query.filter(LoginLogDsl::login_cts.between(cs, ce))
@@ -109,3 +130,115 @@ pub mod login_log {
         }
     }
 }
+
+pub mod audit_log {
+    use crate::model::audit_log::{AuditLog, InsertableAuditLog, QueryableAuditLog};
+    use crate::schema::audit_log as AuditLogSchema;
+    use crate::schema::audit_log::dsl as AuditLogDsl;
+    use diesel::QueryDsl;
+    use diesel::pg::Pg;
+    use diesel::{ExpressionMethods, SelectableHelper};
+    use laurel_actix::types::repository;
+    use laurel_common::types::Pagination;
+    use laurel_pg::{AsyncDsl, DbPool};
+
+    #[derive(Clone, Debug)]
+    pub struct Repository {
+        pool: DbPool,
+    }
+    impl Repository {
+        pub fn new(pool: DbPool) -> Self {
+            Self { pool }
+        }
+
+        pub async fn save<'a>(
+            &self,
+            insertable: &InsertableAuditLog<'a>,
+        ) -> repository::Result<i64> {
+            let mut conn = self.pool.get().await?;
+            let id = AsyncDsl::get_result(
+                diesel::insert_into(AuditLogDsl::audit_log)
+                    .values(insertable)
+                    .returning(AuditLogDsl::id),
+                &mut conn,
+            )
+            .await?;
+            Ok(id)
+        }
+
+        pub async fn page<'a>(
+            &self,
+            queryable: &'a QueryableAuditLog<'a>,
+            (page, size): (u32, u32),
+        ) -> repository::Result<Pagination<AuditLog>> {
+            let mut conn = self.pool.get().await?;
+            let total = AsyncDsl::get_result::<i64>(
+                self.apply_filters(queryable, AuditLogDsl::audit_log.into_boxed())
+                    .select(diesel::dsl::count_star()),
+                &mut conn,
+            )
+            .await?;
+            let offset = (page - 1) * size;
+            if total <= 0 {
+                return Ok(Pagination {
+                    page,
+                    size,
+                    pages: 0,
+                    total: 0,
+                    data: Some(vec![]),
+                });
+            }
+            let pages = (total as f64 / size as f64).ceil() as u64;
+            let list = AsyncDsl::load(
+                self.apply_filters(queryable, AuditLogDsl::audit_log.into_boxed())
+                    .order_by(AuditLogDsl::id.desc())
+                    .offset(offset as i64)
+                    .limit(size as i64)
+                    .select(AuditLog::as_returning()),
+                &mut conn,
+            )
+            .await?;
+            Ok(Pagination {
+                page,
+                size,
+                pages,
+                total: total as u64,
+                data: Some(list),
+            })
+        }
+
+        fn apply_filters<'a>(
+            &self,
+            queryable: &QueryableAuditLog<'a>,
+            mut query: AuditLogSchema::BoxedQuery<'a, Pg>,
+        ) -> AuditLogSchema::BoxedQuery<'a, Pg> {
+            if let Some(param) = queryable.operator
+                && !param.is_empty()
+            {
+                query = query.filter(AuditLogDsl::operator.eq(param.as_str()))
+            }
+            if let Some(param) = queryable.action
+                && !param.is_empty()
+            {
+                query = query.filter(AuditLogDsl::action.eq(param.as_str()))
+            }
+            if let Some(param) = queryable.target
+                && !param.is_empty()
+            {
+                query = query.filter(AuditLogDsl::target.eq(param.as_str()))
+            }
+
+            if let (Some(cs), Some(ce)) = (queryable.audit_cts_start, queryable.audit_cts_end) {
+                query = query.filter(AuditLogDsl::audit_cts.between(cs, ce))
+            } else {
+                if let Some(cs) = queryable.audit_cts_start {
+                    query = query.filter(AuditLogDsl::audit_cts.ge(cs))
+                }
+                if let Some(ce) = queryable.audit_cts_end {
+                    query = query.filter(AuditLogDsl::audit_cts.le(ce))
+                }
+            }
+            query
+        }
+    }
+}
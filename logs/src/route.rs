@@ -2,7 +2,9 @@ use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.configure(login_log_api::config)
-        .configure(login_log::config);
+        .configure(login_log::config)
+        .configure(audit_log_api::config)
+        .configure(audit_log::config);
 }
 
 pub mod login_log_api {
@@ -10,10 +12,16 @@ pub mod login_log_api {
     use actix_web::{post, web};
     use laurel_actix::Data;
     use laurel_actix::types::{Autowired, RequestBody, route};
-    use laurel_logs_api::logs::LoginLogCreateReqBo;
+    use laurel_actix::signature::Signed;
+    use laurel_logs_api::logs::{LoginLogBo, LoginLogCreateReqBo, LoginLogTicketsReqBo};
 
     pub fn config(cfg: &mut web::ServiceConfig) {
-        cfg.service(web::scope("/interface/logs/login").service(save_log));
+        cfg.service(
+            web::scope("/interface/logs/login")
+                .wrap(Signed)
+                .service(save_log)
+                .service(list_by_tickets),
+        );
     }
 
     #[post("/create")]
@@ -23,6 +31,20 @@ pub mod login_log_api {
     ) -> route::Result<i64> {
         Data!(service.create(&body).await?)
     }
+
+    #[post("/tickets")]
+    pub async fn list_by_tickets(
+        service: Autowired<login_log::Service>,
+        body: RequestBody<LoginLogTicketsReqBo>,
+    ) -> route::Result<Vec<LoginLogBo>> {
+        let logs: Vec<LoginLogBo> = service
+            .list_by_tickets(&body)
+            .await?
+            .into_iter()
+            .map(|l| l.into())
+            .collect();
+        Data!(logs)
+    }
 }
 
 pub mod login_log {
@@ -49,3 +71,54 @@ pub mod login_log {
         Data!(service.page(&body, page).await?.to_with_index())
     }
 }
+
+
+pub mod audit_log_api {
+    use crate::service::audit_log;
+    use actix_web::{post, web};
+    use laurel_actix::Data;
+    use laurel_actix::types::{Autowired, RequestBody, route};
+    use laurel_actix::signature::Signed;
+    use laurel_logs_api::logs::AuditLogCreateReqBo;
+
+    pub fn config(cfg: &mut web::ServiceConfig) {
+        cfg.service(
+            web::scope("/interface/logs/audit")
+                .wrap(Signed)
+                .service(save_log),
+        );
+    }
+
+    #[post("/create")]
+    pub async fn save_log(
+        service: Autowired<audit_log::Service>,
+        body: RequestBody<AuditLogCreateReqBo>,
+    ) -> route::Result<i64> {
+        Data!(service.create(&body).await?)
+    }
+}
+
+pub mod audit_log {
+    use crate::model::audit_log::{AuditLogQueryReq, AuditLogVo};
+    use crate::service::audit_log;
+    use actix_web::{post, web};
+    use laurel_actix::Data;
+    use laurel_actix::types::{Autowired, RequestBody, route};
+    use laurel_common::types::Pagination;
+
+    pub fn config(cfg: &mut web::ServiceConfig) {
+        cfg.service(web::scope("/api/logs/audit").service(page_logs));
+    }
+
+    #[post("/pages")]
+    pub async fn page_logs(
+        service: Autowired<audit_log::Service>,
+        body: RequestBody<AuditLogQueryReq>,
+    ) -> route::Result<Pagination<AuditLogVo>> {
+        let page = match &body.page {
+            Some(p) => (p.page, p.size),
+            _ => (1, 15),
+        };
+        Data!(service.page(&body, page).await?.to_with_index())
+    }
+}
@@ -23,9 +23,30 @@ diesel::table! {
         os -> Nullable<Varchar>,
         #[max_length = 256]
         device -> Nullable<Varchar>,
+        new_device -> Bool,
         cts -> Timestamp,
         login_cts -> Timestamp,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(login_log,);
+diesel::table! {
+    use diesel::sql_types::*;
+
+    audit_log (id) {
+        id -> Int8,
+        #[max_length = 40]
+        operator -> Varchar,
+        #[max_length = 40]
+        action -> Varchar,
+        #[max_length = 64]
+        target -> Varchar,
+        #[max_length = 400]
+        detail -> Nullable<Varchar>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        cts -> Timestamp,
+        audit_cts -> Timestamp,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(login_log, audit_log,);
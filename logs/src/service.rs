@@ -5,7 +5,7 @@ pub mod login_log {
     use crate::repository::login_log;
     use laurel_actix::types::service;
     use laurel_common::types::Pagination;
-    use laurel_logs_api::logs::LoginLogCreateReqBo;
+    use laurel_logs_api::logs::{LoginLogCreateReqBo, LoginLogTicketsReqBo};
     use std::sync::Arc;
 
     #[derive(Debug, Clone)]
@@ -20,7 +20,11 @@ pub mod login_log {
 
         pub async fn create(&self, req: &LoginLogCreateReqBo) -> service::Result<i64> {
             let insertable = InsertableLoginLog::from(req);
-            Ok(self.repository.save(&insertable).await?)
+            self.repository.save(&insertable).await
+        }
+
+        pub async fn list_by_tickets(&self, req: &LoginLogTicketsReqBo) -> service::Result<Vec<LoginLog>> {
+            self.repository.list_by_tickets(&req.ticket_ids).await
         }
 
         pub async fn page(
@@ -28,12 +32,47 @@ pub mod login_log {
             req: &LoginLogQueryReq,
             (page, size): (u32, u32),
         ) -> service::Result<Pagination<LoginLog>> {
-            let queryable = QueryableLoginLog::from(req);
-            Ok(self.repository.page(&queryable, (page, size)).await?)
+            let queryable = QueryableLoginLog::try_from(req)?;
+            self.repository.page(&queryable, (page, size)).await
         }
     }
 }
 
+pub mod audit_log {
+    use crate::model::audit_log::{
+        AuditLog, AuditLogQueryReq, InsertableAuditLog, QueryableAuditLog,
+    };
+    use crate::repository::audit_log;
+    use laurel_actix::types::service;
+    use laurel_common::types::Pagination;
+    use laurel_logs_api::logs::AuditLogCreateReqBo;
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone)]
+    pub struct Service {
+        repository: Arc<audit_log::Repository>,
+    }
+
+    impl Service {
+        pub fn new(repository: Arc<audit_log::Repository>) -> Self {
+            Self { repository }
+        }
+
+        pub async fn create(&self, req: &AuditLogCreateReqBo) -> service::Result<i64> {
+            let insertable = InsertableAuditLog::from(req);
+            self.repository.save(&insertable).await
+        }
+
+        pub async fn page(
+            &self,
+            req: &AuditLogQueryReq,
+            (page, size): (u32, u32),
+        ) -> service::Result<Pagination<AuditLog>> {
+            let queryable = QueryableAuditLog::try_from(req)?;
+            self.repository.page(&queryable, (page, size)).await
+        }
+    }
+}
 
 pub mod token{
     use laurel_actix::handler::{Token, TokenHandler, TokenResult};
@@ -64,7 +103,12 @@ pub mod token{
                 Ok(
                     Some(
                         Token{
-                            account_id: "123".to_string()
+                            account_id: "123".to_string(),
+                            ticket_id: String::new(),
+                            impersonator: None,
+                            api_key: None,
+                            scopes: None,
+                            client_id: None,
                         }
                     )
                 )
@@ -2,33 +2,64 @@ pub mod components {
     use crate::{LogsAppConfig, repository, service};
     use actix_web::web;
     use laurel_pg::DbPool;
+    use laurel_redis::Redis;
+    use laurel_actix::signature::SignatureVerifier;
+    use laurel_middleware::signature::SigningMiddleware;
     use std::sync::Arc;
     use laurel_actix::handler::TokenHandler;
+    use laurel_actix::remote::RemoteTokenHandler;
+    use laurel_middleware::reqwest_middle::RequestLoggingMiddleware;
+    use laurel_uc_api::account::AccountApi;
 
     #[allow(unused)]
     pub fn load_components(
         cfg: &mut web::ServiceConfig,
         service_config: LogsAppConfig,
         pool: DbPool,
+        redis: Redis,
     ) {
         let login_log_repository = Arc::new(repository::login_log::Repository::new(pool.clone()));
 
         let login_log_service = service::login_log::Service::new(login_log_repository);
         cfg.app_data(web::Data::new(login_log_service));
 
-        #[allow(deprecated)]
-        let token_service: Arc<dyn TokenHandler> = Arc::new(
-            service::token::TokenService::new(
-                match &service_config.server_config.excludes {
-                    Some(p) => p.clone(),
-                    None => vec![]
-                },
-                match &service_config.server_config.exclude_starts {
-                    Some(p) => p.clone(),
-                    None => vec![]
+        let audit_log_repository = Arc::new(repository::audit_log::Repository::new(pool.clone()));
+        cfg.app_data(web::Data::new(service::audit_log::Service::new(audit_log_repository)));
+
+        let excludes = match &service_config.server_config.excludes {
+            Some(p) => p.clone(),
+            None => vec![]
+        };
+        let exclude_starts = match &service_config.server_config.exclude_starts {
+            Some(p) => p.clone(),
+            None => vec![]
+        };
+        let token_service: Arc<dyn TokenHandler> = match &service_config.token_config {
+            Some(token_config) => {
+                let mut client_builder = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+                    .with(RequestLoggingMiddleware);
+                if let Some(signature_config) = &service_config.signature_config {
+                    client_builder = client_builder.with(
+                        SigningMiddleware::new(signature_config.caller.clone(), signature_config.key.clone())
+                    );
                 }
-            )
-        );
+                let client = Arc::new(client_builder.build());
+                Arc::new(RemoteTokenHandler::new(
+                    AccountApi::build(client, token_config.uc_service.clone(), None),
+                    excludes,
+                    exclude_starts,
+                    token_config,
+                ))
+            },
+            #[allow(deprecated)]
+            None => Arc::new(service::token::TokenService::new(excludes, exclude_starts)),
+        };
         cfg.app_data(web::Data::new(token_service));
+        cfg.app_data(web::Data::new(service_config.server_config.session.clone()));
+
+        cfg.app_data(web::Data::new(match &service_config.signature_config {
+            Some(signature_config) => SignatureVerifier::new(signature_config, redis.clone()),
+            None => SignatureVerifier::disabled(),
+        }));
     }
 }
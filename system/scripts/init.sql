@@ -42,8 +42,10 @@ CREATE TABLE passport
 (
     id         BIGSERIAL   NOT NULL PRIMARY KEY,
     account_id VARCHAR(40) NOT NULL,
-    salt       VARCHAR(64) NOT NULL,
-    password   VARCHAR(64) NOT NULL,
+    salt         VARCHAR(64)  NOT NULL,
+    password     VARCHAR(255) NOT NULL,
+    hash_version VARCHAR(20)  NOT NULL DEFAULT 'sha1',
+    pts        TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     cts        TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     uts        TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
 
@@ -60,6 +62,10 @@ COMMENT
     ON COLUMN passport.salt IS '密码盐';
 COMMENT
     ON COLUMN passport.password IS '账户密码';
+COMMENT
+    ON COLUMN passport.hash_version IS '密码算法版本: sha1 argon2id';
+COMMENT
+    ON COLUMN passport.pts IS '密码设置时间, 用于密码最长有效期';
 COMMENT
     ON COLUMN passport.cts IS '创建时间';
 COMMENT
@@ -177,11 +183,27 @@ CREATE TABLE permission
     menu_id VARCHAR(40) NOT NULL,
     cts     TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     uts     TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    CONSTRAINT uniq_ri UNIQUE (role_id)
+    CONSTRAINT uniq_p_ri_mi UNIQUE (role_id, menu_id)
 );
+CREATE INDEX idx_p_mi ON permission (menu_id);
 COMMENT
     ON TABLE permission IS '权限表';
 
+-- 初始管理员角色: 授权超级权限码 *, 可访问全部受保护接口
+INSERT INTO menu (app_id, menu_id, menu_name, menu_type, menu_action_type, weight, parent_id, authority, menu_status)
+VALUES ('data-map', 'super-authority', '超级权限', 'btn', 'route', 0, 'super-authority', '*', 'open');
+INSERT INTO role (role_id, role_name, role_type, weight, role_status)
+VALUES ('admin', '系统管理员', 'system', 0, 'open');
+INSERT INTO permission (role_id, menu_id)
+VALUES ('admin', 'super-authority');
+-- 账户注册后授予管理员角色:
+-- INSERT INTO role_account (role_id, account_id) VALUES ('admin', '<account_id>');
+
+-- 已有库: 一个角色可授权多个菜单
+-- ALTER TABLE permission DROP CONSTRAINT uniq_ri;
+-- ALTER TABLE permission ADD CONSTRAINT uniq_p_ri_mi UNIQUE (role_id, menu_id);
+-- CREATE INDEX idx_p_mi ON permission (menu_id);
+
 
 CREATE TABLE dict
 (
@@ -221,9 +243,14 @@ CREATE TABLE ticket
     id           BIGSERIAL   NOT NULL PRIMARY KEY,
     ticket_id    VARCHAR(40) NOT NULL,
     token        TEXT        NOT NULL,
+    refresh_id   VARCHAR(40) NOT NULL DEFAULT '',
     account_id   VARCHAR(40) NOT NULL,
     login_type   VARCHAR(20) NOT NULL,
     ticket_state VARCHAR(20) NOT NULL,
+    impersonator VARCHAR(40) NOT NULL DEFAULT '',
+    device_class VARCHAR(20) NOT NULL DEFAULT '',
+    client_id    VARCHAR(40) NOT NULL DEFAULT '',
+    scope        VARCHAR(200) NOT NULL DEFAULT '',
     cts          TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     uts          TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
     ets          TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
@@ -231,3 +258,156 @@ CREATE TABLE ticket
 );
 
 
+
+
+CREATE TABLE account_mfa
+(
+    id             BIGSERIAL   NOT NULL PRIMARY KEY,
+    account_id     VARCHAR(40) NOT NULL,
+    mfa_type       VARCHAR(20) NOT NULL,
+    secret         VARCHAR(64) NOT NULL,
+    mfa_state      VARCHAR(20) NOT NULL,
+    recovery_codes TEXT        NOT NULL DEFAULT '',
+    last_step      BIGINT      NOT NULL DEFAULT 0,
+    cts            TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    uts            TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uniq_am_ai UNIQUE (account_id)
+);
+COMMENT
+    ON TABLE account_mfa IS '账户二次验证表';
+COMMENT
+    ON COLUMN account_mfa.mfa_type IS '验证方式: totp';
+COMMENT
+    ON COLUMN account_mfa.secret IS 'base32 秘钥';
+COMMENT
+    ON COLUMN account_mfa.mfa_state IS '状态: pending enabled';
+COMMENT
+    ON COLUMN account_mfa.recovery_codes IS '恢复码摘要, 逗号分隔';
+COMMENT
+    ON COLUMN account_mfa.last_step IS '最近一次使用的时间步, 防重放';
+
+
+CREATE TABLE passport_history
+(
+    id           BIGSERIAL    NOT NULL PRIMARY KEY,
+    account_id   VARCHAR(40)  NOT NULL,
+    salt         VARCHAR(64)  NOT NULL,
+    password     VARCHAR(255) NOT NULL,
+    hash_version VARCHAR(20)  NOT NULL,
+    cts          TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_ph_ai ON passport_history (account_id);
+COMMENT
+    ON TABLE passport_history IS '历史密码表, 用于禁止重复使用最近的密码';
+COMMENT
+    ON COLUMN passport_history.cts IS '密码设置时间';
+
+
+CREATE TABLE trusted_device
+(
+    id             BIGSERIAL    NOT NULL PRIMARY KEY,
+    account_id     VARCHAR(40)  NOT NULL,
+    fingerprint    VARCHAR(64)  NOT NULL,
+    device_id      VARCHAR(128) NOT NULL DEFAULT '',
+    browser        VARCHAR(128) DEFAULT NULL,
+    os             VARCHAR(128) DEFAULT NULL,
+    device         VARCHAR(128) DEFAULT NULL,
+    host           VARCHAR(128) DEFAULT NULL,
+    endpoint       VARCHAR(128) DEFAULT NULL,
+    ip             VARCHAR(64)  DEFAULT NULL,
+    last_ticket_id VARCHAR(40)  NOT NULL DEFAULT '',
+    cts            TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    uts            TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uniq_td_ai_fp UNIQUE (account_id, fingerprint)
+);
+COMMENT
+    ON TABLE trusted_device IS '账户信任设备表';
+COMMENT
+    ON COLUMN trusted_device.fingerprint IS '设备指纹: 客户端设备id + UA 解析结果的摘要';
+COMMENT
+    ON COLUMN trusted_device.device_id IS '客户端上报的设备id';
+COMMENT
+    ON COLUMN trusted_device.ip IS '最近一次登录ip';
+COMMENT
+    ON COLUMN trusted_device.last_ticket_id IS '最近一次登录的票据, 移除设备时一并吊销';
+COMMENT
+    ON COLUMN trusted_device.uts IS '最近一次登录时间';
+
+
+CREATE TABLE oauth_client
+(
+    id            BIGSERIAL    NOT NULL PRIMARY KEY,
+    client_id     VARCHAR(40)  NOT NULL,
+    client_secret VARCHAR(64)  NOT NULL DEFAULT '',
+    client_name   VARCHAR(64)  NOT NULL,
+    redirect_uris TEXT         NOT NULL,
+    scopes        VARCHAR(200) NOT NULL,
+    client_state  VARCHAR(20)  NOT NULL,
+    creator       VARCHAR(40)  NOT NULL,
+    cts           TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    uts           TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uniq_oc_ci UNIQUE (client_id)
+);
+COMMENT
+    ON TABLE oauth_client IS 'OAuth2 客户端表';
+COMMENT
+    ON COLUMN oauth_client.client_secret IS '客户端密钥摘要, 公开客户端为空';
+COMMENT
+    ON COLUMN oauth_client.redirect_uris IS '允许的回调地址, 空格分隔';
+COMMENT
+    ON COLUMN oauth_client.scopes IS '允许申请的 scope, 空格分隔';
+COMMENT
+    ON COLUMN oauth_client.client_state IS '状态: normal disabled';
+
+
+CREATE TABLE federated_identity
+(
+    id         BIGSERIAL    NOT NULL PRIMARY KEY,
+    provider   VARCHAR(20)  NOT NULL,
+    subject    VARCHAR(255) NOT NULL,
+    account_id VARCHAR(40)  NOT NULL,
+    email      VARCHAR(128) DEFAULT NULL,
+    cts        TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    uts        TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uniq_fi_ps UNIQUE (provider, subject)
+);
+COMMENT
+    ON TABLE federated_identity IS '外部身份源账户关联表';
+COMMENT
+    ON COLUMN federated_identity.provider IS '身份源标识, 对应配置中的 provider id';
+COMMENT
+    ON COLUMN federated_identity.subject IS '身份源中的用户标识(id_token 的 sub)';
+COMMENT
+    ON COLUMN federated_identity.email IS '最近一次登录时身份源返回的邮箱';
+COMMENT
+    ON COLUMN federated_identity.uts IS '最近一次登录时间';
+
+
+CREATE TABLE api_key
+(
+    id         BIGSERIAL    NOT NULL PRIMARY KEY,
+    key_id     VARCHAR(40)  NOT NULL,
+    account_id VARCHAR(40)  NOT NULL,
+    key_name   VARCHAR(64)  NOT NULL,
+    key_hash   VARCHAR(64)  NOT NULL,
+    scopes     TEXT         DEFAULT NULL,
+    key_state  VARCHAR(20)  NOT NULL,
+    ets        TIMESTAMP    DEFAULT NULL,
+    lts        TIMESTAMP    DEFAULT NULL,
+    cts        TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    uts        TIMESTAMP    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uniq_ak_ki UNIQUE (key_id)
+);
+CREATE INDEX idx_ak_ai ON api_key (account_id);
+COMMENT
+    ON TABLE api_key IS '账户 API key 表, 供自动化脚本调用接口';
+COMMENT
+    ON COLUMN api_key.key_hash IS 'key 密钥部分的摘要, 明文仅创建时返回一次';
+COMMENT
+    ON COLUMN api_key.scopes IS '限定的权限码, 空格分隔, 为空时沿用账户全部权限';
+COMMENT
+    ON COLUMN api_key.key_state IS '状态: normal revoked';
+COMMENT
+    ON COLUMN api_key.ets IS '过期时间, 为空不过期';
+COMMENT
+    ON COLUMN api_key.lts IS '最近使用时间';
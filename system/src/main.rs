@@ -11,12 +11,13 @@ mod utils;
 use actix_web::{App, HttpResponse, HttpServer, web};
 use clap::Parser;
 use laurel_actix::ActixApp;
-use laurel_actix::config::{AppArgs, ServerConfig, load_config};
+use laurel_actix::config::{AppArgs, ServerConfig, SignatureConfig, load_config};
 use laurel_logging::types::LogConfig;
 use laurel_pg::DbPool;
 use laurel_pg::types::DbConfig;
 use laurel_redis::{Redis, RedisConfig};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 // use mimalloc::MiMalloc;
 //
@@ -31,6 +32,305 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize, Clone)]
 pub struct UcConfig{
     pub secret: String,
+    /// 登录票据有效期(秒), 默认7天
+    pub ticket_expire: Option<u64>,
+    /// access token 有效期(秒), 默认30分钟
+    pub access_expire: Option<u64>,
+    /// 密码哈希参数
+    #[serde(default)]
+    pub argon2: Argon2Config,
+    /// 登录失败锁定
+    #[serde(default)]
+    pub login_guard: LoginGuardConfig,
+    /// 动态码发行方名称, 默认 Laurel
+    pub mfa_issuer: Option<String>,
+    /// 权限码缓存时间(秒), 默认60秒
+    pub permission_cache_ttl: Option<u64>,
+    /// 令牌签发方、受众与签名密钥
+    #[serde(default)]
+    pub jwt: JwtConfig,
+    /// 邮箱/手机验证码登录
+    #[serde(default)]
+    pub login_code: LoginCodeConfig,
+    /// 密码策略
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
+    /// 管理员代登录
+    #[serde(default)]
+    pub impersonation: ImpersonationConfig,
+    /// 扫码登录
+    #[serde(default)]
+    pub qr_login: QrLoginConfig,
+    /// 同时在线会话数限制
+    #[serde(default)]
+    pub session_limit: SessionLimitConfig,
+    /// OAuth2/OIDC 授权服务
+    #[serde(default)]
+    pub oauth: OAuthConfig,
+    /// 外部 OIDC 身份源登录
+    #[serde(default)]
+    pub federation: FederationConfig,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct FederationConfig {
+    /// 身份源登录后回调的前端地址, 前端取得 code 与 state 后调用登录接口
+    pub redirect_uri: String,
+    /// 登录发起到回调的有效期(秒)
+    pub state_expire: u64,
+    /// 身份源发现文档与公钥缓存时间(秒)
+    pub metadata_ttl: u64,
+    pub providers: Vec<FederatedProviderConfig>,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            redirect_uri: "http://localhost:8080/login/federated".to_string(),
+            state_expire: 300,
+            metadata_ttl: 3600,
+            providers: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FederatedProviderConfig {
+    /// 身份源标识, 最长20位, 记录在账户关联中, 配置后不宜修改
+    pub id: String,
+    /// 登录页展示名称
+    pub name: String,
+    /// 据此获取 /.well-known/openid-configuration, 并校验 id_token 的 iss
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// 为空时申请 openid email profile
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// 外部身份首次登录时的处理: provision 自动创建账户, email 按已验证的邮箱关联已有邮箱账户
+    pub link: String,
+    /// 身份源已执行二次验证时可跳过本地 TOTP, 默认已启用 TOTP 的账户仍需验证
+    #[serde(default)]
+    pub skip_mfa: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OAuthConfig {
+    /// 对外访问地址, 用于发现文档与 id_token 的 iss
+    pub issuer: String,
+    /// 未登录时跳转的登录页, 附带 redirect 参数
+    pub login_page: String,
+    /// 授权码有效期(秒)
+    pub code_expire: u64,
+    /// 支持的 scope
+    pub scopes: Vec<String>,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "http://localhost:8080".to_string(),
+            login_page: "/login".to_string(),
+            code_expire: 60,
+            scopes: vec!["openid".to_string(), "profile".to_string(), "email".to_string(), "phone".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionLimitConfig {
+    /// 单账户同时在线的会话数上限, 0 不限制
+    pub max_sessions: usize,
+    /// 超出上限时的处理: kick_oldest 下线最早的会话, reject 拒绝本次登录
+    pub policy: String,
+    /// 按登录方式单独限制, 如 email = 1
+    pub login_types: HashMap<String, usize>,
+    /// 按终端类型(登录时上报的 endpoint)单独限制, 如 app = 1
+    pub device_classes: HashMap<String, usize>,
+}
+
+impl Default for SessionLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: 0,
+            policy: "kick_oldest".to_string(),
+            login_types: HashMap::new(),
+            device_classes: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct QrLoginConfig {
+    /// 二维码有效期(秒), 确认后需在剩余时间内完成登录
+    pub expire: u64,
+}
+
+impl Default for QrLoginConfig {
+    fn default() -> Self {
+        Self { expire: 120 }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImpersonationConfig {
+    /// 代登录票据有效期(秒)
+    pub expire: u64,
+    /// 发起代登录所需的权限码
+    pub permission: String,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            expire: 1800,
+            permission: "system:account:impersonate".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// 禁止使用的密码, 追加到内置的常见弱密码
+    #[serde(default)]
+    pub banned_passwords: Vec<String>,
+    /// 不允许与最近 N 次密码相同, 0 不限制
+    pub history_size: i64,
+    /// 密码最长有效天数, 过期后登录需先修改密码, 0 不限制
+    pub max_age_days: i64,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: false,
+            banned_passwords: vec![],
+            history_size: 5,
+            max_age_days: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LoginCodeConfig {
+    /// 投递方式: console / file
+    pub sender: String,
+    /// sender 为 file 时的输出文件
+    pub file: Option<String>,
+    /// 验证码有效期(秒)
+    pub expire: u64,
+    /// 同一标识两次发送的最小间隔(秒)
+    pub cooldown: u64,
+    /// 同一标识每小时最多发送次数
+    pub hourly_limit: i64,
+    /// 同一ip每小时最多发送次数
+    pub ip_hourly_limit: i64,
+    /// 单个验证码最多校验次数
+    pub max_attempts: i64,
+}
+
+impl Default for LoginCodeConfig {
+    fn default() -> Self {
+        Self {
+            sender: "console".to_string(),
+            file: None,
+            expire: 300,
+            cooldown: 60,
+            hourly_limit: 10,
+            ip_hourly_limit: 50,
+            max_attempts: 5,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JwtConfig {
+    pub issuer: String,
+    pub audience: Vec<String>,
+    /// 当前用于签名的 kid, 未配置 keys 时使用 secret 以 HS256 签名
+    pub signing_kid: Option<String>,
+    /// 轮换期间旧密钥只保留公钥继续验签
+    #[serde(default)]
+    pub keys: Vec<JwtKeyConfig>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "laurel-system".to_string(),
+            audience: vec!["laurel".to_string()],
+            signing_kid: None,
+            keys: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JwtKeyConfig {
+    pub kid: String,
+    /// RS256 / EdDSA
+    pub algorithm: String,
+    /// 私钥 PEM 文件路径, 仅签名密钥需要
+    pub private_key: Option<String>,
+    /// 公钥 PEM 文件路径
+    pub public_key: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LoginGuardConfig {
+    /// 统计窗口(秒)
+    pub window: u64,
+    /// 窗口内单账户最大失败次数
+    pub max_account_failures: i64,
+    /// 窗口内单ip最大失败次数
+    pub max_ip_failures: i64,
+    /// 锁定时长(秒)
+    pub lock_duration: u64,
+}
+
+impl Default for LoginGuardConfig {
+    fn default() -> Self {
+        Self {
+            window: 900,
+            max_account_failures: 5,
+            max_ip_failures: 20,
+            lock_duration: 900,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Argon2Config {
+    /// 内存(KiB)
+    pub memory_cost: u32,
+    /// 迭代次数
+    pub time_cost: u32,
+    /// 并行度
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Self {
+            memory_cost: 19456,
+            time_cost: 2,
+            parallelism: 1,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -41,6 +341,8 @@ pub struct SystemAppConfig {
     pub redis_config: RedisConfig,
     pub api_config: SystemApiConfig,
     pub uc_config: UcConfig,
+    /// 服务间调用签名, 未配置时拒绝全部 /interface 请求
+    pub signature_config: Option<SignatureConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -1,8 +1,116 @@
 use chrono::NaiveDateTime;
-use diesel::{Identifiable, Queryable, Selectable};
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use laurel_common::date_time::DTF;
+use laurel_common::enum_options;
+use laurel_common::types::{HappyEnum, IndexAble, PageQuery, SelectOption};
 use laurel_uc_api::account::AccountBo;
 use serde::{Deserialize, Serialize};
-use crate::model::ticket::Ticket;
+use crate::model::device::LoginClient;
+use crate::model::ticket::IssuedTicket;
+use crate::utils::codes;
+
+#[derive(Debug)]
+pub enum AccountState {
+    ACTIVE(&'static str, &'static str),
+    DISABLED(&'static str, &'static str),
+    LOCKED(&'static str, &'static str),
+    PENDING(&'static str, &'static str),
+}
+
+static ACCOUNT_STATES: [AccountState; 4] = [
+    AccountState::ACTIVE("active", "正常"),
+    AccountState::DISABLED("disabled", "已禁用"),
+    AccountState::LOCKED("locked", "已锁定"),
+    AccountState::PENDING("pending", "待激活"),
+];
+
+impl HappyEnum<&'static str> for AccountState {
+    fn take(&self) -> (&'static str, &'static str) {
+        match self {
+            AccountState::ACTIVE(x, y)
+            | AccountState::DISABLED(x, y)
+            | AccountState::LOCKED(x, y)
+            | AccountState::PENDING(x, y) => (x, y),
+        }
+    }
+
+    fn valid(key: &str) -> bool {
+        Self::find_self(key).is_some()
+    }
+
+    fn find(key: &str) -> Option<&'static str> {
+        Self::find_self(key).map(|t| t.take().1).or(None)
+    }
+
+    fn find_self(key: &str) -> Option<&'static Self> {
+        for item in &ACCOUNT_STATES {
+            if let Some(y) = match item {
+                &AccountState::ACTIVE(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &AccountState::DISABLED(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &AccountState::LOCKED(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &AccountState::PENDING(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+            } {
+                return Some(y);
+            }
+        }
+        None
+    }
+
+    fn options() -> Vec<SelectOption<&'static str, &'static str>> {
+        enum_options!(ACCOUNT_STATES)
+    }
+}
+
+impl AccountState {
+    /// 允许的状态迁移
+    pub fn can_transit(from: &str, to: &str) -> bool {
+        matches!(
+            (from, to),
+            ("pending", "active")
+                | ("pending", "disabled")
+                | ("active", "disabled")
+                | ("active", "locked")
+                | ("locked", "active")
+                | ("locked", "disabled")
+                | ("disabled", "active")
+        )
+    }
+
+    /// 非正常状态对应的业务状态码, 正常状态返回 None
+    pub fn reject_code(state: &str) -> Option<(u16, &'static str)> {
+        match state {
+            "active" => None,
+            "disabled" => Some((codes::ACCOUNT_DISABLED, "account disabled")),
+            "locked" => Some((codes::ACCOUNT_FROZEN, "account locked by administrator")),
+            "pending" => Some((codes::ACCOUNT_PENDING, "account pending activation")),
+            _ => Some((codes::ACCOUNT_DISABLED, "account state invalid")),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = crate::schema::schema::account)]
@@ -26,18 +134,141 @@ pub struct AccountEntity {
     pub uts: NaiveDateTime,
 }
 
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::account)]
+pub struct InsertableAccount<'a> {
+    pub account_id: &'a str,
+    pub account_name: &'a str,
+    pub account_state: &'a str,
+    pub account_type: &'a str,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountCreateReq {
+    pub account_name: String,
+
+    /// 默认 name
+    pub account_type: Option<String>,
+
+    pub password: String,
+
+    /// active / pending, 默认 active
+    pub account_state: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountQueryReq {
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub account_type: Option<String>,
+    pub account_state: Option<String>,
+    /// 分页参数
+    #[serde(flatten)]
+    pub page: Option<PageQuery>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStateReq {
+    pub account_id: String,
+    pub account_state: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordChangeReq {
+    pub old_password: String,
+
+    pub new_password: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordRenewReq {
+    /// 登录时返回的修改过期密码票据
+    pub ticket: String,
+
+    pub new_password: String,
+
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordResetReq {
+    pub account_id: String,
+
+    pub new_password: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountLoginVo {
     pub account: String,
 
+    /// 登录标识类型: name / email / phone, 默认 name
+    pub account_type: Option<String>,
+
     pub password: String,
 
-    pub device: Option<String>,
+    /// 设备id、host 与 endpoint
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginCodeSendReq {
+    /// email / phone
+    pub account_type: String,
+    pub account: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginCodeReq {
+    /// email / phone
+    pub account_type: String,
+    pub account: String,
+    pub code: String,
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
 
-    pub host: Option<String>,
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonateReq {
+    pub account_id: String,
+    /// 代登录原因, 记入审计日志
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationVo {
+    pub account: AccountVo,
+    pub token: String,
+    /// access token 有效期(秒)
+    pub expires_in: u64,
+    /// 代登录结束时间
+    pub ets: String,
+}
 
-    pub endpoint: Option<String>,
+impl From<(AccountEntity, IssuedTicket)> for ImpersonationVo {
+    fn from(value: (AccountEntity, IssuedTicket)) -> Self {
+        ImpersonationVo {
+            account: value.0.into(),
+            ets: value.1.ticket.ets.format(DTF).to_string(),
+            token: value.1.ticket.token,
+            expires_in: value.1.expires_in,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -72,6 +303,41 @@ impl From<AccountEntity> for AccountVo {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPageVo {
+    pub index: u32,
+    pub account_id: String,
+    pub account_name: String,
+    pub account_state: String,
+    pub account_state_name: Option<&'static str>,
+    pub account_type: String,
+    pub cts: String,
+    pub uts: String,
+}
+
+impl IndexAble for AccountPageVo {
+    fn set_index(&mut self, index: u32) -> &mut Self {
+        self.index = index;
+        self
+    }
+}
+
+impl From<AccountEntity> for AccountPageVo {
+    fn from(entity: AccountEntity) -> Self {
+        AccountPageVo {
+            index: 0,
+            account_id: entity.account_id,
+            account_name: entity.account_name,
+            account_state_name: AccountState::find(&entity.account_state),
+            account_state: entity.account_state,
+            account_type: entity.account_type,
+            cts: entity.cts.format(DTF).to_string(),
+            uts: entity.uts.format(DTF).to_string(),
+        }
+    }
+}
+
 impl From<AccountEntity> for AccountBo {
     fn from(entity: AccountEntity) -> Self {
         let ts_formatter = "%Y-%m-%d %H:%M:%S";
@@ -86,18 +352,68 @@ impl From<AccountEntity> for AccountBo {
     }
 }
 
+/// 登录结果: 直接签发票据, 或需要二次验证/修改过期密码
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum LoginStep {
+    Issued(IssuedTicket),
+    /// 二次验证挑战票据
+    Challenge(String),
+    /// 密码已过期, 凭此票据修改密码后完成登录
+    PasswordExpired(String),
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginVo {
     pub account: AccountVo,
-    pub token: String,
+    pub token: Option<String>,
+    pub refresh_token: Option<String>,
+    /// access token 有效期(秒)
+    pub expires_in: Option<u64>,
+    /// 是否需要二次验证
+    pub mfa_required: bool,
+    /// 二次验证挑战票据
+    pub challenge: Option<String>,
+    /// 密码是否已过期, 需修改后才能登录
+    pub password_expired: bool,
+    /// 修改过期密码的票据
+    pub renew_ticket: Option<String>,
 }
 
-impl From<(AccountEntity, Ticket)> for LoginVo {
-    fn from(value: (AccountEntity, Ticket)) -> Self {
-        LoginVo {
-            account: value.0.into(),
-            token: value.1.token,
+impl From<(AccountEntity, LoginStep)> for LoginVo {
+    fn from(value: (AccountEntity, LoginStep)) -> Self {
+        match value.1 {
+            LoginStep::Issued(issued) => LoginVo {
+                account: value.0.into(),
+                token: Some(issued.ticket.token),
+                refresh_token: Some(issued.refresh_token),
+                expires_in: Some(issued.expires_in),
+                mfa_required: false,
+                challenge: None,
+                password_expired: false,
+                renew_ticket: None,
+            },
+            LoginStep::Challenge(challenge) => LoginVo {
+                account: value.0.into(),
+                token: None,
+                refresh_token: None,
+                expires_in: None,
+                mfa_required: true,
+                challenge: Some(challenge),
+                password_expired: false,
+                renew_ticket: None,
+            },
+            LoginStep::PasswordExpired(ticket) => LoginVo {
+                account: value.0.into(),
+                token: None,
+                refresh_token: None,
+                expires_in: None,
+                mfa_required: false,
+                challenge: None,
+                password_expired: true,
+                renew_ticket: Some(ticket),
+            },
         }
     }
 }
@@ -111,3 +427,13 @@ pub struct AccountQuery {
 
     pub account_type: Option<String>,
 }
+
+#[test]
+fn test_state_transit() {
+    assert!(AccountState::can_transit("pending", "active"));
+    assert!(AccountState::can_transit("locked", "active"));
+    assert!(!AccountState::can_transit("disabled", "locked"));
+    assert!(!AccountState::can_transit("active", "pending"));
+    assert!(AccountState::reject_code("active").is_none());
+    assert_eq!(AccountState::reject_code("locked").map(|r| r.0), Some(codes::ACCOUNT_FROZEN));
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use laurel_common::date_time::DTF;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::api_key)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+    pub id: i64,
+    pub key_id: String,
+    pub account_id: String,
+    pub key_name: String,
+    pub key_hash: String,
+    /// 空格分隔, 为空时沿用账户全部权限
+    pub scopes: Option<String>,
+    /// normal / revoked
+    pub key_state: String,
+    pub ets: Option<NaiveDateTime>,
+    /// 最近使用时间
+    pub lts: Option<NaiveDateTime>,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+impl ApiKey {
+    /// 未吊销且未过期
+    pub fn active(&self, now: NaiveDateTime) -> bool {
+        self.key_state == "normal" && self.ets.map(|ets| ets > now).unwrap_or(true)
+    }
+
+    pub fn scope_list(&self) -> Option<Vec<String>> {
+        self.scopes
+            .as_ref()
+            .map(|s| s.split_whitespace().map(|s| s.to_string()).collect())
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::api_key)]
+pub struct InsertableApiKey<'a> {
+    pub key_id: &'a str,
+    pub account_id: &'a str,
+    pub key_name: &'a str,
+    pub key_hash: &'a str,
+    pub scopes: Option<&'a str>,
+    pub key_state: &'a str,
+    pub ets: Option<NaiveDateTime>,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyCreateReq {
+    pub key_name: String,
+    /// 限定的权限码, 须为本账户拥有的权限; 不传时沿用账户全部权限
+    pub scopes: Option<Vec<String>>,
+    /// 有效天数, 不传时不过期
+    pub expire_days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyRevokeReq {
+    pub key_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyVo {
+    pub key_id: String,
+    /// 完整的 key, 仅创建时返回一次
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub key_name: String,
+    pub scopes: Option<Vec<String>>,
+    pub key_state: String,
+    pub ets: Option<String>,
+    pub lts: Option<String>,
+    pub cts: String,
+}
+
+impl From<ApiKey> for ApiKeyVo {
+    fn from(value: ApiKey) -> Self {
+        ApiKeyVo {
+            key: None,
+            scopes: value.scope_list(),
+            key_id: value.key_id,
+            key_name: value.key_name,
+            key_state: value.key_state,
+            ets: value.ets.map(|t| t.format(DTF).to_string()),
+            lts: value.lts.map(|t| t.format(DTF).to_string()),
+            cts: value.cts.format(DTF).to_string(),
+        }
+    }
+}
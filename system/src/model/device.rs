@@ -0,0 +1,104 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use laurel_common::date_time::DTF;
+use serde::{Deserialize, Serialize};
+
+/// 登录时客户端上报的设备信息
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginClient {
+    /// 客户端生成并持久保存的设备id
+    pub device: Option<String>,
+
+    pub host: Option<String>,
+
+    pub endpoint: Option<String>,
+}
+
+impl LoginClient {
+    /// 终端类型, 取上报的 endpoint, 未上报时为 unknown
+    pub fn device_class(&self) -> String {
+        self.endpoint
+            .as_deref()
+            .map(|e| e.trim().to_lowercase().chars().take(20).collect::<String>())
+            .filter(|e| !e.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::trusted_device)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TrustedDevice {
+    pub id: i64,
+    pub account_id: String,
+    pub fingerprint: String,
+    pub device_id: String,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub host: Option<String>,
+    pub endpoint: Option<String>,
+    pub ip: Option<String>,
+    pub last_ticket_id: String,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::trusted_device)]
+pub struct InsertableTrustedDevice<'a> {
+    pub account_id: &'a str,
+    pub fingerprint: &'a str,
+    pub device_id: &'a str,
+    pub browser: Option<&'a str>,
+    pub os: Option<&'a str>,
+    pub device: Option<&'a str>,
+    pub host: Option<&'a str>,
+    pub endpoint: Option<&'a str>,
+    pub ip: Option<&'a str>,
+    pub last_ticket_id: &'a str,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceRevokeReq {
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedDeviceVo {
+    pub fingerprint: String,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub host: Option<String>,
+    pub endpoint: Option<String>,
+    pub ip: Option<String>,
+    /// 是否为当前会话所用设备
+    pub current: bool,
+    /// 首次登录时间
+    pub cts: String,
+    /// 最近一次登录时间
+    pub uts: String,
+}
+
+impl TrustedDeviceVo {
+    pub fn of(device: TrustedDevice, current_ticket_id: &str) -> Self {
+        TrustedDeviceVo {
+            current: device.last_ticket_id == current_ticket_id,
+            fingerprint: device.fingerprint,
+            browser: device.browser,
+            os: device.os,
+            device: device.device,
+            host: device.host,
+            endpoint: device.endpoint,
+            ip: device.ip,
+            cts: device.cts.format(DTF).to_string(),
+            uts: device.uts.format(DTF).to_string(),
+        }
+    }
+}
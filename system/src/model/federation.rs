@@ -0,0 +1,106 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use crate::model::device::LoginClient;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::federated_identity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FederatedIdentity {
+    pub id: i64,
+    pub provider: String,
+    pub subject: String,
+    pub account_id: String,
+    pub email: Option<String>,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::federated_identity)]
+pub struct InsertableFederatedIdentity<'a> {
+    pub provider: &'a str,
+    pub subject: &'a str,
+    pub account_id: &'a str,
+    pub email: Option<&'a str>,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedProviderVo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationAuthorizeReq {
+    pub provider: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationAuthorizeVo {
+    /// 前端跳转到该地址进行身份源登录
+    pub authorization_url: String,
+    pub state: String,
+}
+
+/// 身份源回调后, 前端提交回调地址上的 code 与 state
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationLoginReq {
+    pub code: String,
+    pub state: String,
+    /// 设备id、host 与 endpoint
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
+
+/// 发起登录时保存在 redis 中的状态, 回调时使用一次即作废
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FederationState {
+    pub provider: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+/// 身份源发现文档, 只取用到的字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProviderTokenVo {
+    pub id_token: Option<String>,
+}
+
+/// 身份源 id_token 中用到的声明
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FederatedClaims {
+    pub iss: String,
+    pub sub: String,
+    pub exp: i64,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    /// 部分身份源以字符串返回
+    pub email_verified: Option<serde_json::Value>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+}
+
+impl FederatedClaims {
+    pub fn email_verified(&self) -> bool {
+        match &self.email_verified {
+            Some(serde_json::Value::Bool(verified)) => *verified,
+            Some(serde_json::Value::String(verified)) => verified == "true",
+            _ => false,
+        }
+    }
+}
@@ -4,7 +4,7 @@ use diesel::{AsChangeset, Identifiable, Insertable, Queryable, QueryableByName,
 use laurel_common::types::{HappyEnum, IndexAble, PageQuery, SelectOption};
 use laurel_common::{enum_options};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::string::ToString;
 use laurel_common::date_time::DTF;
 
@@ -475,6 +475,14 @@ impl From<Menu> for MenuVo {
     }
 }
 
+/// 当前账户可见的菜单树与按钮权限码
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MineMenuVo {
+    pub menus: Vec<MenuVo>,
+    pub authorities: Vec<String>,
+}
+
 struct TempNode {
     id: String,
     pid: String,
@@ -482,6 +490,39 @@ struct TempNode {
 }
 
 impl Menu {
+    /// 按授权过滤: 保留已授权的菜单及其全部上级, 按钮仅输出权限码
+    pub fn retain_granted(menus: Vec<Menu>, granted: &HashSet<String>) -> (Vec<Menu>, Vec<String>) {
+        let parents: HashMap<&str, &str> = menus
+            .iter()
+            .map(|m| (m.menu_id.as_str(), m.parent_id.as_str()))
+            .collect();
+        let mut visible: HashSet<String> = HashSet::new();
+        let mut authorities: Vec<String> = Vec::new();
+        for menu in menus.iter().filter(|m| granted.contains(&m.menu_id)) {
+            if menu.menu_type == "btn" {
+                if let Some(authority) = &menu.authority
+                    && !authority.trim().is_empty()
+                    && !authorities.contains(authority)
+                {
+                    authorities.push(authority.clone());
+                }
+                continue;
+            }
+            let mut current = menu.menu_id.as_str();
+            while visible.insert(current.to_string()) {
+                match parents.get(current) {
+                    Some(&pid) if pid != current => current = pid,
+                    _ => break,
+                }
+            }
+        }
+        let menus = menus
+            .into_iter()
+            .filter(|m| visible.contains(&m.menu_id))
+            .collect();
+        (menus, authorities)
+    }
+
     /// 零拷贝、纯迭代构建树（O(n) 时间，O(n) 空间）
     pub fn build_tree(menus: Vec<Menu>) -> Vec<MenuVo> {
         if menus.is_empty() {
@@ -658,3 +699,37 @@ fn test() {
         MenuType::find_self(&"menu".to_string()).unwrap()
     );
 }
+
+#[test]
+fn test_retain_granted() {
+    let menu = |id: &str, pid: &str, menu_type: &str, authority: Option<&str>| Menu {
+        id: 0,
+        app_id: "app".to_string(),
+        menu_id: id.to_string(),
+        menu_name: id.to_string(),
+        menu_type: menu_type.to_string(),
+        menu_action_type: "route".to_string(),
+        menu_icon: None,
+        menu_route: None,
+        route_param: None,
+        weight: 0,
+        parent_id: pid.to_string(),
+        authority: authority.map(|a| a.to_string()),
+        menu_status: "open".to_string(),
+        cts: NaiveDateTime::default(),
+        uts: NaiveDateTime::default(),
+    };
+    let menus = vec![
+        menu("root", "root", "menu", None),
+        menu("system", "root", "menu", None),
+        menu("role", "system", "menu", None),
+        menu("role-add", "role", "btn", Some("role:add")),
+        menu("menu", "system", "menu", None),
+        menu("other", "other", "menu", None),
+    ];
+    let granted: HashSet<String> = ["role", "role-add"].iter().map(|s| s.to_string()).collect();
+    let (visible, authorities) = Menu::retain_granted(menus, &granted);
+    let ids: Vec<&str> = visible.iter().map(|m| m.menu_id.as_str()).collect();
+    assert_eq!(ids, vec!["root", "system", "role"]);
+    assert_eq!(authorities, vec!["role:add".to_string()]);
+}
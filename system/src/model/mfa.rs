@@ -0,0 +1,141 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use crate::model::device::LoginClient;
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::account_mfa)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AccountMfa {
+    pub id: i64,
+    pub account_id: String,
+    pub mfa_type: String,
+    pub secret: String,
+    /// pending: 已生成秘钥待确认, enabled: 已启用
+    pub mfa_state: String,
+    pub recovery_codes: String,
+    pub last_step: i64,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+impl AccountMfa {
+    pub fn enabled(&self) -> bool {
+        self.mfa_state == "enabled"
+    }
+
+    pub fn recovery_code_hashes(&self) -> Vec<&str> {
+        self.recovery_codes
+            .split(',')
+            .filter(|c| !c.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::account_mfa)]
+pub struct InsertableAccountMfa<'a> {
+    pub account_id: &'a str,
+    pub mfa_type: &'a str,
+    pub secret: &'a str,
+    pub mfa_state: &'a str,
+    pub recovery_codes: &'a str,
+    pub last_step: i64,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpEnrollVo {
+    pub secret: String,
+    /// otpauth 地址, 用于生成二维码
+    pub uri: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaCodeReq {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodesVo {
+    /// 明文仅返回一次
+    pub codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaStatusVo {
+    pub enabled: bool,
+    pub mfa_type: Option<String>,
+    pub recovery_codes: usize,
+}
+
+impl From<Option<AccountMfa>> for MfaStatusVo {
+    fn from(value: Option<AccountMfa>) -> Self {
+        match value {
+            Some(mfa) if mfa.enabled() => MfaStatusVo {
+                enabled: true,
+                recovery_codes: mfa.recovery_code_hashes().len(),
+                mfa_type: Some(mfa.mfa_type),
+            },
+            _ => MfaStatusVo {
+                enabled: false,
+                mfa_type: None,
+                recovery_codes: 0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginMfaReq {
+    /// 第一步登录返回的挑战票据
+    pub challenge: String,
+    /// 动态码或恢复码
+    pub code: String,
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
+
+/// 二次验证挑战, 记录第一因子的登录方式, 验证通过后按原方式签发票据
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaChallenge {
+    pub account_id: String,
+    pub login_type: String,
+    /// 外部身份源等可信登录, 不校验本地密码有效期
+    pub trusted: bool,
+}
+
+impl MfaChallenge {
+    pub fn new(account_id: &str, login_type: &str, trusted: bool) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            login_type: login_type.to_string(),
+            trusted,
+        }
+    }
+
+    /// 验证通过后是否仍需校验本地密码有效期
+    pub fn check_password_age(&self) -> bool {
+        !self.trusted
+    }
+}
+
+#[test]
+fn test_federated_challenge() {
+    // 身份源登录进入二次验证后, 保留 federated 登录方式且跳过密码有效期
+    let challenge = MfaChallenge::new("1", "federated", true);
+    let saved = serde_json::to_string(&challenge).unwrap();
+    let loaded = serde_json::from_str::<MfaChallenge>(saved.as_str()).unwrap();
+    assert_eq!(loaded, challenge);
+    assert_eq!(loaded.login_type, "federated");
+    assert!(!loaded.check_password_age());
+    // 验证码登录保留 email / phone, 仍校验密码有效期
+    assert!(MfaChallenge::new("1", "email", false).check_password_age());
+}
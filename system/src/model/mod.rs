@@ -5,4 +5,9 @@ pub mod role;
 pub mod account;
 pub mod passport;
 pub mod profile;
-pub mod ticket;
\ No newline at end of file
+pub mod ticket;pub mod mfa;
+pub mod device;
+pub mod oauth;
+pub mod federation;
+pub mod api_key;
+pub mod qr_login;
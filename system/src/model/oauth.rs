@@ -0,0 +1,211 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use laurel_common::date_time::DTF;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::oauth_client)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthClient {
+    pub id: i64,
+    pub client_id: String,
+    /// 密钥摘要, 公开客户端为空
+    pub client_secret: String,
+    pub client_name: String,
+    pub redirect_uris: String,
+    pub scopes: String,
+    /// normal / disabled
+    pub client_state: String,
+    pub creator: String,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+impl OAuthClient {
+    pub fn enabled(&self) -> bool {
+        self.client_state == "normal"
+    }
+
+    /// 公开客户端(如单页应用)不持有密钥, 仅凭 PKCE 换取令牌
+    pub fn public(&self) -> bool {
+        self.client_secret.is_empty()
+    }
+
+    pub fn redirect_uri_list(&self) -> Vec<&str> {
+        self.redirect_uris.split_whitespace().collect()
+    }
+
+    pub fn scope_list(&self) -> Vec<&str> {
+        self.scopes.split_whitespace().collect()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::oauth_client)]
+pub struct InsertableOAuthClient<'a> {
+    pub client_id: &'a str,
+    pub client_secret: &'a str,
+    pub client_name: &'a str,
+    pub redirect_uris: &'a str,
+    pub scopes: &'a str,
+    pub client_state: &'a str,
+    pub creator: &'a str,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthClientCreateReq {
+    pub client_name: String,
+    pub redirect_uris: Vec<String>,
+    /// 为空时允许申请全部支持的 scope
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// 公开客户端不生成密钥
+    #[serde(default)]
+    pub public: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthClientStateReq {
+    pub client_id: String,
+    pub client_state: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthClientVo {
+    pub client_id: String,
+    /// 仅创建时返回一次
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub client_name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub public: bool,
+    pub client_state: String,
+    pub creator: String,
+    pub cts: String,
+    pub uts: String,
+}
+
+impl From<OAuthClient> for OAuthClientVo {
+    fn from(value: OAuthClient) -> Self {
+        OAuthClientVo {
+            client_secret: None,
+            redirect_uris: value.redirect_uri_list().iter().map(|u| u.to_string()).collect(),
+            scopes: value.scope_list().iter().map(|s| s.to_string()).collect(),
+            public: value.public(),
+            client_id: value.client_id,
+            client_name: value.client_name,
+            client_state: value.client_state,
+            creator: value.creator,
+            cts: value.cts.format(DTF).to_string(),
+            uts: value.uts.format(DTF).to_string(),
+        }
+    }
+}
+
+/// 授权请求, 字段名遵循 OAuth2 规范
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuthorizeReq {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    #[serde(default)]
+    pub scope: String,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub code_challenge: String,
+    pub code_challenge_method: Option<String>,
+}
+
+/// 授权码在 redis 中保存的内容, 使用一次即作废
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuthorizationCode {
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub account_id: String,
+    pub scope: String,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    /// 授权时间戳(秒)
+    pub auth_time: i64,
+}
+
+/// 令牌请求, application/x-www-form-urlencoded
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OAuthTokenReq {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OAuthTokenVo {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    pub scope: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub exp: i64,
+    pub iat: i64,
+    pub auth_time: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// 对应的 access token 摘要
+    pub at_hash: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct UserInfoVo {
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number_verified: Option<bool>,
+}
+
+/// OIDC 发现文档
+#[derive(Debug, Serialize)]
+pub struct DiscoveryVo {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub response_types_supported: Vec<&'static str>,
+    pub grant_types_supported: Vec<&'static str>,
+    pub subject_types_supported: Vec<&'static str>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub scopes_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<&'static str>,
+    pub code_challenge_methods_supported: Vec<&'static str>,
+    pub claims_supported: Vec<&'static str>,
+}
@@ -1,5 +1,5 @@
 use chrono::NaiveDateTime;
-use diesel::{Identifiable, Queryable, Selectable};
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
@@ -15,9 +15,64 @@ pub struct PassportEntity {
 
     pub password: String,
 
+    /// 密码算法版本
+    pub hash_version: String,
+
+    /// 密码设置时间
+    pub pts: NaiveDateTime,
+
     /// 创建时间
     pub cts: NaiveDateTime,
 
     /// 更新时间
     pub uts: NaiveDateTime,
 }
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::passport)]
+pub struct InsertablePassport<'a> {
+    pub account_id: &'a str,
+    pub salt: &'a str,
+    pub password: &'a str,
+    pub hash_version: &'a str,
+    pub pts: NaiveDateTime,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::passport_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PassportHistory {
+    pub id: i64,
+    pub account_id: String,
+    pub salt: String,
+    pub password: String,
+    pub hash_version: String,
+    pub cts: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::passport_history)]
+pub struct InsertablePassportHistory<'a> {
+    pub account_id: &'a str,
+    pub salt: &'a str,
+    pub password: &'a str,
+    pub hash_version: &'a str,
+    pub cts: NaiveDateTime,
+}
+
+/// 单条密码规则校验失败
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyViolation {
+    /// 规则: min_length max_length lowercase uppercase digit symbol banned account reuse
+    pub rule: String,
+    pub message: String,
+}
+
+impl PolicyViolation {
+    pub fn new(rule: &str, message: impl Into<String>) -> Self {
+        Self { rule: rule.to_string(), message: message.into() }
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use crate::model::device::LoginClient;
+
+/// 扫码登录状态
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrStatus {
+    /// 等待扫码
+    Pending,
+    /// 已扫码, 等待手机端确认
+    Scanned,
+    Confirmed,
+    Denied,
+    /// 已过期或已完成登录
+    Expired,
+}
+
+/// 保存在 redis 中的扫码登录挑战
+#[derive(Debug, Deserialize, Serialize)]
+pub struct QrChallenge {
+    pub status: QrStatus,
+    /// 轮询凭证摘要, 只有发起登录的桌面端持有明文
+    pub poll_hash: String,
+    /// 扫码的账户, 只有该账户可以确认
+    pub account_id: Option<String>,
+    /// 桌面端信息, 供手机端确认时展示
+    pub client: LoginClient,
+    pub ua: Option<String>,
+    pub ip: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrLoginCreateReq {
+    /// 设备id、host 与 endpoint
+    #[serde(flatten)]
+    pub client: LoginClient,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrLoginVo {
+    /// 二维码内容
+    pub challenge: String,
+    /// 查询状态与完成登录时使用, 不要放入二维码
+    pub poll_token: String,
+    /// 有效期(秒)
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrLoginPollReq {
+    pub challenge: String,
+    pub poll_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrStatusVo {
+    pub status: QrStatus,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrLoginScanReq {
+    pub challenge: String,
+}
+
+/// 手机端确认前展示的桌面端信息
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrScanVo {
+    pub ua: Option<String>,
+    pub ip: String,
+    pub host: Option<String>,
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrLoginConfirmReq {
+    pub challenge: String,
+    /// false 时拒绝本次登录
+    pub approve: bool,
+}
@@ -2,7 +2,8 @@ use bon::Builder;
 use chrono::NaiveDateTime;
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable, QueryableByName, Selectable};
 use laurel_common::enum_options;
-use laurel_common::types::{HappyEnum, SelectOption};
+use laurel_common::date_time::DTF;
+use laurel_common::types::{HappyEnum, IndexAble, PageQuery, SelectOption};
 use serde::{Deserialize, Serialize};
 
 pub enum RoleStatus {
@@ -96,6 +97,7 @@ pub struct UpdatableRole {
 #[derive(Debug, Insertable)]
 #[diesel(table_name = crate::schema::schema::role)]
 pub struct InsertableRole<'a> {
+    pub role_id: &'a str,
     pub role_name: &'a str,
     pub role_type: &'a str,
     pub weight: i32,
@@ -104,4 +106,133 @@ pub struct InsertableRole<'a> {
     pub uts: NaiveDateTime,
 }
 
-pub struct QueryableRole {}
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::role_account)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoleAccount {
+    pub id: i64,
+    pub role_id: String,
+    pub account_id: String,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::role_account)]
+pub struct InsertableRoleAccount<'a> {
+    pub role_id: &'a str,
+    pub account_id: &'a str,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::schema::permission)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Permission {
+    pub id: i64,
+    pub role_id: String,
+    pub menu_id: String,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::schema::permission)]
+pub struct InsertablePermission<'a> {
+    pub role_id: &'a str,
+    pub menu_id: &'a str,
+    pub cts: NaiveDateTime,
+    pub uts: NaiveDateTime,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleCreateReq {
+    pub role_name: String,
+    pub role_type: String,
+    pub weight: i32,
+    pub role_status: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleUpdateReq {
+    pub role_id: String,
+    pub role_name: Option<String>,
+    pub role_type: Option<String>,
+    pub weight: Option<i32>,
+    pub role_status: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleQueryReq {
+    pub role_id: Option<String>,
+    pub role_name: Option<String>,
+    pub role_type: Option<String>,
+    pub role_status: Option<String>,
+    #[serde(flatten)]
+    pub pagination: Option<PageQuery>,
+}
+
+/// 角色下增减账户
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleAccountsReq {
+    pub role_id: String,
+    pub account_ids: Vec<String>,
+}
+
+/// 覆盖账户的角色
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRolesReq {
+    pub account_id: String,
+    pub role_ids: Vec<String>,
+}
+
+/// 覆盖角色授权的菜单
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleMenusReq {
+    pub role_id: String,
+    pub menu_ids: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleVo {
+    pub index: u32,
+    pub role_id: String,
+    pub role_name: String,
+    pub role_type: String,
+    pub weight: i32,
+    pub role_status: String,
+    pub role_status_name: Option<&'static str>,
+    pub cts: String,
+    pub uts: String,
+}
+
+impl IndexAble for RoleVo {
+    fn set_index(&mut self, index: u32) -> &mut Self {
+        self.index = index;
+        self
+    }
+}
+
+impl From<Role> for RoleVo {
+    fn from(value: Role) -> Self {
+        RoleVo {
+            index: 0,
+            role_id: value.role_id,
+            role_name: value.role_name,
+            role_type: value.role_type,
+            weight: value.weight,
+            role_status_name: RoleStatus::find(&value.role_status),
+            role_status: value.role_status,
+            cts: value.cts.format(DTF).to_string(),
+            uts: value.uts.format(DTF).to_string(),
+        }
+    }
+}
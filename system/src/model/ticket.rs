@@ -1,6 +1,98 @@
 use chrono::NaiveDateTime;
 use diesel::{Identifiable, Insertable, Queryable, QueryableByName, Selectable};
+use laurel_actix::handler::Token;
+use laurel_common::date_time::DTF;
+use laurel_common::enum_options;
+use laurel_common::types::{HappyEnum, SelectOption};
+use laurel_logs_api::logs::LoginLogBo;
 use serde::{Deserialize, Serialize};
+use crate::utils::oauth_utils;
+
+#[derive(Debug)]
+pub enum TicketState {
+    NORMAL(&'static str, &'static str),
+    LOGOUT(&'static str, &'static str),
+    REVOKED(&'static str, &'static str),
+    KICKED(&'static str, &'static str),
+    REPLACED(&'static str, &'static str),
+}
+
+static TICKET_STATES: [TicketState; 5] = [
+    TicketState::NORMAL("normal", "正常"),
+    TicketState::LOGOUT("logout", "已登出"),
+    TicketState::REVOKED("revoked", "已吊销"),
+    TicketState::KICKED("kicked", "已下线"),
+    TicketState::REPLACED("replaced", "已被挤下线"),
+];
+
+impl HappyEnum<&'static str> for TicketState {
+    fn take(&self) -> (&'static str, &'static str) {
+        match self {
+            TicketState::NORMAL(x, y)
+            | TicketState::LOGOUT(x, y)
+            | TicketState::REVOKED(x, y)
+            | TicketState::KICKED(x, y)
+            | TicketState::REPLACED(x, y) => (x, y),
+        }
+    }
+
+    fn valid(key: &str) -> bool {
+        Self::find_self(key).is_some()
+    }
+
+    fn find(key: &str) -> Option<&'static str> {
+        Self::find_self(key).map(|t| t.take().1).or(None)
+    }
+
+    fn find_self(key: &str) -> Option<&'static Self> {
+        for item in &TICKET_STATES {
+            if let Some(y) = match item {
+                &TicketState::NORMAL(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &TicketState::LOGOUT(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &TicketState::REVOKED(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &TicketState::KICKED(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+                &TicketState::REPLACED(x, _) => {
+                    if x == key {
+                        Some(item)
+                    } else {
+                        None
+                    }
+                }
+            } {
+                return Some(y);
+            }
+        }
+        None
+    }
+
+    fn options() -> Vec<SelectOption<&'static str, &'static str>> {
+        enum_options!(TICKET_STATES)
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Identifiable, QueryableByName)]
 #[diesel(table_name = crate::schema::schema::ticket)]
@@ -9,9 +101,18 @@ pub struct Ticket{
     pub id: i64,
     pub ticket_id: String,
     pub token: String,
+    pub refresh_id: String,
     pub account_id: String,
     pub login_type: String,
     pub ticket_state: String,
+    /// 代登录时为发起的管理员账户, 否则为空
+    pub impersonator: String,
+    /// 登录终端类型, 用于分类限制在线会话数
+    pub device_class: String,
+    /// OAuth2 签发时为客户端id, 否则为空
+    pub client_id: String,
+    /// OAuth2 授权范围, 空格分隔
+    pub scope: String,
     pub cts: NaiveDateTime,
     pub uts: NaiveDateTime,
     pub ets: NaiveDateTime,
@@ -22,9 +123,14 @@ pub struct Ticket{
 pub struct InsertableTicket<'a>{
     pub ticket_id: &'a str,
     pub token: &'a str,
+    pub refresh_id: &'a str,
     pub account_id: &'a str,
     pub login_type: &'a str,
     pub ticket_state: &'a str,
+    pub impersonator: &'a str,
+    pub device_class: &'a str,
+    pub client_id: &'a str,
+    pub scope: &'a str,
     pub cts: NaiveDateTime,
     pub uts: NaiveDateTime,
     pub ets: NaiveDateTime,
@@ -33,4 +139,175 @@ pub struct InsertableTicket<'a>{
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JwtPayload{
     pub ticket_id: String,
-}
\ No newline at end of file
+    /// access / refresh
+    pub typ: String,
+    /// refresh token 的轮换标识, 对应 ticket.refresh_id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    pub iss: String,
+    pub aud: Vec<String>,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+/// 签发结果: 票据 + 本次的 refresh token
+#[derive(Debug)]
+pub struct IssuedTicket {
+    pub ticket: Ticket,
+    pub refresh_token: String,
+    /// access token 有效期(秒)
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenRefreshReq {
+    /// cookie 会话模式下可为空, 从 cookie 读取
+    #[serde(default)]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenRefreshVo {
+    pub token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+}
+
+impl From<IssuedTicket> for TokenRefreshVo {
+    fn from(value: IssuedTicket) -> Self {
+        TokenRefreshVo {
+            token: value.ticket.token,
+            refresh_token: value.refresh_token,
+            expires_in: value.expires_in,
+        }
+    }
+}
+
+/// OAuth2 令牌只携带 OIDC scope, 不会命中任何权限码
+impl From<&Ticket> for Token {
+    fn from(ticket: &Ticket) -> Self {
+        let client_id = Some(ticket.client_id.clone()).filter(|c| !c.is_empty());
+        Token {
+            account_id: ticket.account_id.clone(),
+            ticket_id: ticket.ticket_id.clone(),
+            impersonator: Some(ticket.impersonator.clone()).filter(|i| !i.is_empty()),
+            api_key: None,
+            scopes: client_id.as_ref().map(|_| oauth_utils::oidc_scopes(ticket.scope.as_str())),
+            client_id,
+        }
+    }
+}
+
+/// 会话缓存, 携带当前 access token 摘要, 命中缓存时同样拒绝已被轮换的旧令牌
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedTicket {
+    pub token_hash: String,
+    pub token: Token,
+}
+
+impl CachedTicket {
+    pub fn matches(&self, token: &str) -> bool {
+        oauth_utils::secret_matches(token, self.token_hash.as_str())
+    }
+}
+
+impl From<&Ticket> for CachedTicket {
+    fn from(ticket: &Ticket) -> Self {
+        CachedTicket {
+            token_hash: oauth_utils::secret_hash(ticket.token.as_str()),
+            token: Token::from(ticket),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketRevokeReq {
+    pub ticket_id: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTicketRevokeReq {
+    pub account_id: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketVo {
+    pub ticket_id: String,
+    pub account_id: String,
+    pub login_type: String,
+    pub ticket_state: String,
+    pub ticket_state_name: Option<&'static str>,
+    pub device_class: String,
+    pub cts: String,
+    pub uts: String,
+    pub ets: String,
+}
+
+impl From<Ticket> for TicketVo {
+    fn from(value: Ticket) -> Self {
+        TicketVo {
+            ticket_id: value.ticket_id,
+            account_id: value.account_id,
+            login_type: value.login_type,
+            ticket_state_name: TicketState::find(&value.ticket_state),
+            ticket_state: value.ticket_state,
+            device_class: value.device_class,
+            cts: value.cts.format(DTF).to_string(),
+            uts: value.uts.format(DTF).to_string(),
+            ets: value.ets.format(DTF).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionKickReq {
+    pub ticket_id: String,
+}
+
+/// 在线会话: 票据 + 登录时记录的终端信息
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionVo {
+    pub ticket_id: String,
+    pub account_id: String,
+    pub login_type: String,
+    /// 是否为发起请求的会话
+    pub current: bool,
+    pub ip: Option<String>,
+    pub location: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub login_cts: Option<String>,
+    pub cts: String,
+    pub ets: String,
+}
+
+impl SessionVo {
+    pub fn of(ticket: Ticket, log: Option<LoginLogBo>, current_ticket_id: &str) -> Self {
+        let log = log.unwrap_or_default();
+        SessionVo {
+            current: ticket.ticket_id == current_ticket_id,
+            ticket_id: ticket.ticket_id,
+            account_id: ticket.account_id,
+            login_type: ticket.login_type,
+            ip: log.ip,
+            location: log.location,
+            browser: log.browser,
+            os: log.os,
+            device: log.device,
+            login_cts: if log.login_cts.is_empty() { None } else { Some(log.login_cts) },
+            cts: ticket.cts.format(DTF).to_string(),
+            ets: ticket.ets.format(DTF).to_string(),
+        }
+    }
+}
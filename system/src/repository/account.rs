@@ -1,8 +1,14 @@
-use crate::model::account::AccountEntity;
+use crate::model::account::{AccountEntity, AccountQueryReq, InsertableAccount};
+use crate::model::passport::{InsertablePassport, InsertablePassportHistory};
+use crate::schema::schema::account as AccountSchema;
 use crate::schema::schema::account::dsl as AccountDsl;
+use crate::schema::schema::passport::dsl as PassportDsl;
+use crate::schema::schema::passport_history::dsl as PassportHistoryDsl;
+use chrono::Local;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use laurel_actix::types::{repository};
+use laurel_common::types::Pagination;
 use laurel_pg::DbPool; //::*;
 
 #[derive(Clone, Debug)]
@@ -46,4 +52,176 @@ impl AccountRepository {
             .optional()?;
         Ok(account)
     }
+
+    /// 同一事务内写入账户与密码
+    pub async fn save<'a>(
+        &self,
+        account: &InsertableAccount<'a>,
+        passport: &InsertablePassport<'a>,
+    ) -> repository::Result<AccountEntity> {
+        let mut conn = self.pool.get().await?;
+        let account = conn
+            .transaction::<AccountEntity, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    let account = diesel::insert_into(AccountDsl::account)
+                        .values(account)
+                        .returning(AccountEntity::as_returning())
+                        .get_result(&mut tx)
+                        .await?;
+                    diesel::insert_into(PassportDsl::passport)
+                        .values(passport)
+                        .execute(&mut tx)
+                        .await?;
+                    diesel::insert_into(PassportHistoryDsl::passport_history)
+                        .values(&InsertablePassportHistory {
+                            account_id: passport.account_id,
+                            salt: passport.salt,
+                            password: passport.password,
+                            hash_version: passport.hash_version,
+                            cts: passport.pts,
+                        })
+                        .execute(&mut tx)
+                        .await?;
+                    Ok(account)
+                })
+            })
+            .await?;
+        Ok(account)
+    }
+
+    /// 同一事务内更新密码、记录历史密码并刷新账户更新时间, 账户不存在时返回 None
+    pub async fn update_passport(
+        &self,
+        account_id: &str,
+        salt: &str,
+        password: &str,
+        hash_version: &str,
+    ) -> repository::Result<Option<AccountEntity>> {
+        let mut conn = self.pool.get().await?;
+        let account = conn
+            .transaction::<Option<AccountEntity>, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    let now = Local::now().naive_local();
+                    let account = diesel::update(AccountDsl::account)
+                        .filter(AccountDsl::account_id.eq(account_id))
+                        .set(AccountDsl::uts.eq(now))
+                        .returning(AccountEntity::as_returning())
+                        .get_result(&mut tx)
+                        .await
+                        .optional()?;
+                    if account.is_none() {
+                        return Ok(None);
+                    }
+                    diesel::update(PassportDsl::passport)
+                        .filter(PassportDsl::account_id.eq(account_id))
+                        .set((
+                            PassportDsl::salt.eq(salt),
+                            PassportDsl::password.eq(password),
+                            PassportDsl::hash_version.eq(hash_version),
+                            PassportDsl::pts.eq(now),
+                            PassportDsl::uts.eq(now),
+                        ))
+                        .execute(&mut tx)
+                        .await?;
+                    diesel::insert_into(PassportHistoryDsl::passport_history)
+                        .values(&InsertablePassportHistory {
+                            account_id,
+                            salt,
+                            password,
+                            hash_version,
+                            cts: now,
+                        })
+                        .execute(&mut tx)
+                        .await?;
+                    Ok(account)
+                })
+            })
+            .await?;
+        Ok(account)
+    }
+
+    pub async fn page(&self, query: &AccountQueryReq, page: u32, size: u32) -> repository::Result<Pagination<AccountEntity>> {
+        let mut conn = self.pool.get().await?;
+        let total = self
+            .apply_filters(query, AccountDsl::account.into_boxed())
+            .select(diesel::dsl::count_star())
+            .get_result::<i64>(&mut conn)
+            .await?;
+        let offset = (page - 1) * size;
+        if total <= 0 {
+            return Ok(Pagination {
+                page,
+                size,
+                pages: 0,
+                total: 0,
+                data: Some(vec![]),
+            });
+        }
+        let pages = (total as f64 / size as f64).ceil() as u64;
+        let accounts = self
+            .apply_filters(query, AccountDsl::account.into_boxed())
+            .order_by(AccountDsl::id.desc())
+            .offset(offset as i64)
+            .limit(size as i64)
+            .select(AccountEntity::as_select())
+            .load(&mut conn)
+            .await?;
+        Ok(Pagination {
+            page,
+            size,
+            pages,
+            total: total as u64,
+            data: Some(accounts),
+        })
+    }
+
+    /// 仅当当前状态为 from_state 时更新, 返回更新后的账户
+    pub async fn update_state(
+        &self,
+        account_id: &str,
+        from_state: &str,
+        to_state: &str,
+    ) -> repository::Result<Option<AccountEntity>> {
+        let mut conn = self.pool.get().await?;
+        let account = diesel::update(AccountDsl::account)
+            .filter(AccountDsl::account_id.eq(account_id))
+            .filter(AccountDsl::account_state.eq(from_state))
+            .set((
+                AccountDsl::account_state.eq(to_state),
+                AccountDsl::uts.eq(Local::now().naive_local()),
+            ))
+            .returning(AccountEntity::as_returning())
+            .get_result(&mut conn)
+            .await
+            .optional()?;
+        Ok(account)
+    }
+
+    fn apply_filters<'a>(
+        &self,
+        params: &'a AccountQueryReq,
+        mut query: AccountSchema::BoxedQuery<'a, diesel::pg::Pg>,
+    ) -> AccountSchema::BoxedQuery<'a, diesel::pg::Pg> {
+        if let Some(param) = &params.account_id
+            && !param.is_empty()
+        {
+            query = query.filter(AccountDsl::account_id.eq(param.as_str()));
+        }
+        if let Some(param) = &params.account_name
+            && !param.is_empty()
+        {
+            query = query.filter(AccountDsl::account_name.ilike(format!("%{}%", param)));
+        }
+        if let Some(param) = &params.account_type
+            && !param.is_empty()
+        {
+            query = query.filter(AccountDsl::account_type.eq(param.as_str()));
+        }
+        if let Some(param) = &params.account_state
+            && !param.is_empty()
+        {
+            query = query.filter(AccountDsl::account_state.eq(param.as_str()));
+        }
+        query
+    }
 }
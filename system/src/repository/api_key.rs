@@ -0,0 +1,118 @@
+use chrono::{Local, NaiveDateTime};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use laurel_actix::types::repository;
+use laurel_pg::{AsyncDsl, DbPool};
+use crate::model::api_key::{ApiKey, InsertableApiKey};
+use crate::schema::schema::api_key::dsl as ApiKeyDsl;
+
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, key_id: &str) -> repository::Result<Option<ApiKey>>{
+        let mut conn = self.pool.get().await?;
+        let key = AsyncDsl::first(
+            ApiKeyDsl::api_key
+                .filter(ApiKeyDsl::key_id.eq(key_id))
+                .select(ApiKey::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(key)
+    }
+
+    /// 账户下全部 key, 按创建时间倒序
+    pub async fn list(&self, account_id: &str) -> repository::Result<Vec<ApiKey>>{
+        let mut conn = self.pool.get().await?;
+        let keys = AsyncDsl::load(
+            ApiKeyDsl::api_key
+                .filter(ApiKeyDsl::account_id.eq(account_id))
+                .order_by(ApiKeyDsl::cts.desc())
+                .select(ApiKey::as_select()),
+            &mut conn,
+        )
+            .await?;
+        Ok(keys)
+    }
+
+    pub async fn count_active(&self, account_id: &str) -> repository::Result<i64>{
+        let mut conn = self.pool.get().await?;
+        let count = AsyncDsl::get_result(
+            ApiKeyDsl::api_key
+                .filter(ApiKeyDsl::account_id.eq(account_id))
+                .filter(ApiKeyDsl::key_state.eq("normal"))
+                .count(),
+            &mut conn,
+        )
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn save<'a>(&self, insertable: &InsertableApiKey<'a>) -> repository::Result<ApiKey>{
+        let mut conn = self.pool.get().await?;
+        let key = AsyncDsl::get_result(
+            diesel::insert_into(ApiKeyDsl::api_key)
+                .values(insertable)
+                .returning(ApiKey::as_returning()),
+            &mut conn,
+        )
+            .await?;
+        Ok(key)
+    }
+
+    /// 吊销本账户的 key, 不存在时返回 None
+    pub async fn revoke(&self, account_id: &str, key_id: &str) -> repository::Result<Option<ApiKey>>{
+        let mut conn = self.pool.get().await?;
+        let key = AsyncDsl::get_result(
+            diesel::update(ApiKeyDsl::api_key)
+                .filter(ApiKeyDsl::account_id.eq(account_id))
+                .filter(ApiKeyDsl::key_id.eq(key_id))
+                .set((
+                    ApiKeyDsl::key_state.eq("revoked"),
+                    ApiKeyDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(ApiKey::as_returning()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(key)
+    }
+
+    /// 吊销账户下全部有效的 key
+    pub async fn revoke_account(&self, account_id: &str) -> repository::Result<usize>{
+        let mut conn = self.pool.get().await?;
+        let size = AsyncDsl::execute(
+            diesel::update(ApiKeyDsl::api_key)
+                .filter(ApiKeyDsl::account_id.eq(account_id))
+                .filter(ApiKeyDsl::key_state.eq("normal"))
+                .set((
+                    ApiKeyDsl::key_state.eq("revoked"),
+                    ApiKeyDsl::uts.eq(Local::now().naive_local()),
+                )),
+            &mut conn,
+        )
+            .await?;
+        Ok(size)
+    }
+
+    /// 记录最近使用时间
+    pub async fn touch(&self, key_id: &str, lts: NaiveDateTime) -> repository::Result<usize>{
+        let mut conn = self.pool.get().await?;
+        let size = AsyncDsl::execute(
+            diesel::update(ApiKeyDsl::api_key)
+                .filter(ApiKeyDsl::key_id.eq(key_id))
+                .set(ApiKeyDsl::lts.eq(lts)),
+            &mut conn,
+        )
+            .await?;
+        Ok(size)
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel::upsert::excluded;
+use laurel_actix::types::repository;
+use laurel_pg::{AsyncDsl, DbPool};
+use crate::model::device::{InsertableTrustedDevice, TrustedDevice};
+use crate::schema::schema::trusted_device::dsl as DeviceDsl;
+
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, account_id: &str, fingerprint: &str) -> repository::Result<Option<TrustedDevice>>{
+        let mut conn = self.pool.get().await?;
+        let device = AsyncDsl::first(
+            DeviceDsl::trusted_device
+                .filter(DeviceDsl::account_id.eq(account_id))
+                .filter(DeviceDsl::fingerprint.eq(fingerprint))
+                .select(TrustedDevice::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(device)
+    }
+
+    pub async fn list(&self, account_id: &str) -> repository::Result<Vec<TrustedDevice>>{
+        let mut conn = self.pool.get().await?;
+        let devices = AsyncDsl::load(
+            DeviceDsl::trusted_device
+                .filter(DeviceDsl::account_id.eq(account_id))
+                .order_by(DeviceDsl::uts.desc())
+                .select(TrustedDevice::as_select()),
+            &mut conn,
+        )
+            .await?;
+        Ok(devices)
+    }
+
+    /// 新设备写入, 已有设备刷新最近登录信息
+    pub async fn save<'a>(&self, insertable: &InsertableTrustedDevice<'a>) -> repository::Result<TrustedDevice>{
+        let mut conn = self.pool.get().await?;
+        let device = AsyncDsl::get_result(
+            diesel::insert_into(DeviceDsl::trusted_device)
+                .values(insertable)
+                .on_conflict((DeviceDsl::account_id, DeviceDsl::fingerprint))
+                .do_update()
+                .set((
+                    DeviceDsl::host.eq(excluded(DeviceDsl::host)),
+                    DeviceDsl::endpoint.eq(excluded(DeviceDsl::endpoint)),
+                    DeviceDsl::ip.eq(excluded(DeviceDsl::ip)),
+                    DeviceDsl::last_ticket_id.eq(excluded(DeviceDsl::last_ticket_id)),
+                    DeviceDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(TrustedDevice::as_returning()),
+            &mut conn,
+        )
+            .await?;
+        Ok(device)
+    }
+
+    pub async fn delete(&self, account_id: &str, fingerprint: &str) -> repository::Result<Option<TrustedDevice>>{
+        let mut conn = self.pool.get().await?;
+        let device = AsyncDsl::get_result(
+            diesel::delete(DeviceDsl::trusted_device)
+                .filter(DeviceDsl::account_id.eq(account_id))
+                .filter(DeviceDsl::fingerprint.eq(fingerprint))
+                .returning(TrustedDevice::as_returning()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(device)
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel::upsert::excluded;
+use laurel_actix::types::repository;
+use laurel_pg::{AsyncDsl, DbPool};
+use crate::model::federation::{FederatedIdentity, InsertableFederatedIdentity};
+use crate::schema::schema::federated_identity::dsl as IdentityDsl;
+
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, provider: &str, subject: &str) -> repository::Result<Option<FederatedIdentity>>{
+        let mut conn = self.pool.get().await?;
+        let identity = AsyncDsl::first(
+            IdentityDsl::federated_identity
+                .filter(IdentityDsl::provider.eq(provider))
+                .filter(IdentityDsl::subject.eq(subject))
+                .select(FederatedIdentity::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(identity)
+    }
+
+    /// 新关联写入, 已有关联只刷新邮箱与最近登录时间
+    pub async fn save<'a>(&self, insertable: &InsertableFederatedIdentity<'a>) -> repository::Result<FederatedIdentity>{
+        let mut conn = self.pool.get().await?;
+        let identity = AsyncDsl::get_result(
+            diesel::insert_into(IdentityDsl::federated_identity)
+                .values(insertable)
+                .on_conflict((IdentityDsl::provider, IdentityDsl::subject))
+                .do_update()
+                .set((
+                    IdentityDsl::email.eq(excluded(IdentityDsl::email)),
+                    IdentityDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(FederatedIdentity::as_returning()),
+            &mut conn,
+        )
+            .await?;
+        Ok(identity)
+    }
+}
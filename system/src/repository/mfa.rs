@@ -0,0 +1,123 @@
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel_async::AsyncConnection;
+use laurel_actix::types::repository;
+use laurel_pg::{AsyncDsl, DbPool};
+use crate::model::mfa::{AccountMfa, InsertableAccountMfa};
+use crate::schema::schema::account_mfa::dsl as MfaDsl;
+
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, account_id: &str) -> repository::Result<Option<AccountMfa>>{
+        let mut conn = self.pool.get().await?;
+        let mfa = AsyncDsl::first(
+            MfaDsl::account_mfa
+                .filter(MfaDsl::account_id.eq(account_id))
+                .select(AccountMfa::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(mfa)
+    }
+
+    /// 覆盖账户已有的未启用记录
+    pub async fn save_pending<'a>(&self, insertable: &InsertableAccountMfa<'a>) -> repository::Result<AccountMfa>{
+        let mut conn = self.pool.get().await?;
+        let mfa = conn
+            .transaction::<AccountMfa, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    AsyncDsl::execute(
+                        diesel::delete(MfaDsl::account_mfa)
+                            .filter(MfaDsl::account_id.eq(insertable.account_id))
+                            .filter(MfaDsl::mfa_state.eq("pending")),
+                        &mut tx,
+                    )
+                        .await?;
+                    let mfa = AsyncDsl::get_result(
+                        diesel::insert_into(MfaDsl::account_mfa)
+                            .values(insertable)
+                            .returning(AccountMfa::as_returning()),
+                        &mut tx,
+                    )
+                        .await?;
+                    Ok(mfa)
+                })
+            })
+            .await?;
+        Ok(mfa)
+    }
+
+    pub async fn enable(&self, account_id: &str, recovery_codes: &str, last_step: i64) -> repository::Result<Option<AccountMfa>>{
+        let mut conn = self.pool.get().await?;
+        let mfa = AsyncDsl::get_result(
+            diesel::update(MfaDsl::account_mfa)
+                .filter(MfaDsl::account_id.eq(account_id))
+                .filter(MfaDsl::mfa_state.eq("pending"))
+                .set((
+                    MfaDsl::mfa_state.eq("enabled"),
+                    MfaDsl::recovery_codes.eq(recovery_codes),
+                    MfaDsl::last_step.eq(last_step),
+                    MfaDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(AccountMfa::as_returning()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(mfa)
+    }
+
+    /// 仅当时间步大于上次使用的时间步时更新, 返回是否更新成功
+    pub async fn update_last_step(&self, account_id: &str, last_step: i64) -> repository::Result<bool>{
+        let mut conn = self.pool.get().await?;
+        let rows = AsyncDsl::execute(
+            diesel::update(MfaDsl::account_mfa)
+                .filter(MfaDsl::account_id.eq(account_id))
+                .filter(MfaDsl::last_step.lt(last_step))
+                .set((
+                    MfaDsl::last_step.eq(last_step),
+                    MfaDsl::uts.eq(Local::now().naive_local()),
+                )),
+            &mut conn,
+        )
+            .await?;
+        Ok(rows > 0)
+    }
+
+    /// 以旧值为条件替换恢复码, 防止同一恢复码被并发使用
+    pub async fn update_recovery_codes(&self, account_id: &str, old_codes: &str, recovery_codes: &str) -> repository::Result<bool>{
+        let mut conn = self.pool.get().await?;
+        let rows = AsyncDsl::execute(
+            diesel::update(MfaDsl::account_mfa)
+                .filter(MfaDsl::account_id.eq(account_id))
+                .filter(MfaDsl::recovery_codes.eq(old_codes))
+                .set((
+                    MfaDsl::recovery_codes.eq(recovery_codes),
+                    MfaDsl::uts.eq(Local::now().naive_local()),
+                )),
+            &mut conn,
+        )
+            .await?;
+        Ok(rows > 0)
+    }
+
+    pub async fn delete(&self, account_id: &str) -> repository::Result<usize>{
+        let mut conn = self.pool.get().await?;
+        let rows = AsyncDsl::execute(
+            diesel::delete(MfaDsl::account_mfa)
+                .filter(MfaDsl::account_id.eq(account_id)),
+            &mut conn,
+        )
+            .await?;
+        Ok(rows)
+    }
+}
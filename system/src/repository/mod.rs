@@ -24,3 +24,8 @@ pub mod ticket;
 //     #[diesel(sql_type = diesel::sql_types::BigInt)]
 //     pub row_result: i64,
 // }
+pub mod mfa;
+pub mod device;
+pub mod oauth;
+pub mod federation;
+pub mod api_key;
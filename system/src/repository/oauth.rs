@@ -0,0 +1,71 @@
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use laurel_actix::types::repository;
+use laurel_pg::{AsyncDsl, DbPool};
+use crate::model::oauth::{InsertableOAuthClient, OAuthClient};
+use crate::schema::schema::oauth_client::dsl as ClientDsl;
+
+#[derive(Clone, Debug)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, client_id: &str) -> repository::Result<Option<OAuthClient>>{
+        let mut conn = self.pool.get().await?;
+        let client = AsyncDsl::first(
+            ClientDsl::oauth_client
+                .filter(ClientDsl::client_id.eq(client_id))
+                .select(OAuthClient::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(client)
+    }
+
+    pub async fn list(&self) -> repository::Result<Vec<OAuthClient>>{
+        let mut conn = self.pool.get().await?;
+        let clients = AsyncDsl::load(
+            ClientDsl::oauth_client
+                .order_by(ClientDsl::cts.desc())
+                .select(OAuthClient::as_select()),
+            &mut conn,
+        )
+            .await?;
+        Ok(clients)
+    }
+
+    pub async fn save<'a>(&self, insertable: &InsertableOAuthClient<'a>) -> repository::Result<OAuthClient>{
+        let mut conn = self.pool.get().await?;
+        let client = AsyncDsl::get_result(
+            diesel::insert_into(ClientDsl::oauth_client)
+                .values(insertable)
+                .returning(OAuthClient::as_returning()),
+            &mut conn,
+        )
+            .await?;
+        Ok(client)
+    }
+
+    pub async fn update_state(&self, client_id: &str, client_state: &str) -> repository::Result<Option<OAuthClient>>{
+        let mut conn = self.pool.get().await?;
+        let client = AsyncDsl::get_result(
+            diesel::update(ClientDsl::oauth_client)
+                .filter(ClientDsl::client_id.eq(client_id))
+                .set((
+                    ClientDsl::client_state.eq(client_state),
+                    ClientDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(OAuthClient::as_returning()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(client)
+    }
+}
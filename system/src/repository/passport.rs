@@ -1,5 +1,7 @@
-use crate::model::passport::PassportEntity;
+use chrono::Local;
+use crate::model::passport::{PassportEntity, PassportHistory};
 use crate::schema::schema::passport::dsl as PassportDsl;
+use crate::schema::schema::passport_history::dsl as PassportHistoryDsl;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use laurel_actix::types::{repository};
@@ -25,4 +27,31 @@ impl PassportRepository {
             .optional()?;
         Ok(passport)
     }
+
+    pub async fn update_password(&self, account_id: &str, password: &str, hash_version: &str) -> repository::Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let rows = diesel::update(PassportDsl::passport)
+            .filter(PassportDsl::account_id.eq(account_id))
+            .set((
+                PassportDsl::password.eq(password),
+                PassportDsl::hash_version.eq(hash_version),
+                PassportDsl::uts.eq(Local::now().naive_local()),
+            ))
+            .execute(&mut conn)
+            .await?;
+        Ok(rows)
+    }
+
+    /// 最近设置过的密码, 按时间倒序
+    pub async fn list_history(&self, account_id: &str, limit: i64) -> repository::Result<Vec<PassportHistory>> {
+        let mut conn = self.pool.get().await?;
+        let histories = PassportHistoryDsl::passport_history
+            .filter(PassportHistoryDsl::account_id.eq(account_id))
+            .order_by(PassportHistoryDsl::id.desc())
+            .limit(limit)
+            .select(PassportHistory::as_select())
+            .load(&mut conn)
+            .await?;
+        Ok(histories)
+    }
 }
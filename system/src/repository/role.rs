@@ -1,11 +1,16 @@
-use crate::model::role::{InsertableRole, Role, UpdatableRole};
-//use crate::schema::schema::role as RoleSchema;
+use crate::model::role::{InsertablePermission, InsertableRole, InsertableRoleAccount, Role, RoleQueryReq, UpdatableRole};
+use crate::schema::schema::role as RoleSchema;
 use crate::schema::schema::role::dsl as RoleDsl;
+use crate::schema::schema::role_account::dsl as RoleAccountDsl;
+use crate::schema::schema::permission::dsl as PermissionDsl;
+use crate::schema::schema::menu::dsl as MenuDsl;
+use chrono::Local;
 use diesel::ExpressionMethods;
 use diesel::associations::HasTable;
-use diesel::{OptionalExtension, QueryDsl, SelectableHelper};
+use diesel::{JoinOnDsl, NullableExpressionMethods, OptionalExtension, PgTextExpressionMethods, QueryDsl, SelectableHelper};
 use diesel_async::*;
 use laurel_actix::types::repository;
+use laurel_common::types::Pagination;
 use laurel_pg::{AsyncDsl, DbPool};
 
 #[derive(Clone, Debug)]
@@ -71,4 +76,282 @@ impl RoleRepository {
 
         Ok(role)
     }
+
+    pub async fn page(&self, query: &RoleQueryReq, page: u32, size: u32) -> repository::Result<Pagination<Role>> {
+        let mut conn = self.pool.get().await?;
+        let total = AsyncDsl::get_result::<i64>(
+            self.apply_filters(query, RoleDsl::role.into_boxed())
+                .select(diesel::dsl::count_star()),
+            &mut conn,
+        )
+        .await?;
+        let offset = (page - 1) * size;
+        if total <= 0 {
+            return Ok(Pagination {
+                page,
+                size,
+                pages: 0,
+                total: 0,
+                data: Some(vec![]),
+            });
+        }
+        let pages = (total as f64 / size as f64).ceil() as u64;
+        let roles = AsyncDsl::load(
+            self.apply_filters(query, RoleDsl::role.into_boxed())
+                .order_by(RoleSchema::weight.asc())
+                .then_order_by(RoleSchema::id.desc())
+                .offset(offset as i64)
+                .limit(size as i64)
+                .select(Role::as_select()),
+            &mut conn,
+        )
+        .await?;
+        Ok(Pagination {
+            page,
+            size,
+            pages,
+            total: total as u64,
+            data: Some(roles),
+        })
+    }
+
+    pub async fn list_by_ids(&self, role_ids: &[String]) -> repository::Result<Vec<Role>> {
+        let mut conn = self.pool.get().await?;
+        let roles = AsyncDsl::load(
+            RoleDsl::role
+                .filter(RoleDsl::role_id.eq_any(role_ids))
+                .order_by(RoleDsl::weight.asc())
+                .select(Role::as_select()),
+            &mut conn,
+        )
+        .await?;
+        Ok(roles)
+    }
+
+    /// 账户拥有的角色
+    pub async fn list_by_account(&self, account_id: &str) -> repository::Result<Vec<Role>> {
+        let mut conn = self.pool.get().await?;
+        let roles = AsyncDsl::load(
+            RoleDsl::role
+                .inner_join(RoleAccountDsl::role_account.on(RoleAccountDsl::role_id.eq(RoleDsl::role_id)))
+                .filter(RoleAccountDsl::account_id.eq(account_id))
+                .filter(RoleDsl::role_status.ne("deleted"))
+                .order_by(RoleDsl::weight.asc())
+                .select(Role::as_select()),
+            &mut conn,
+        )
+        .await?;
+        Ok(roles)
+    }
+
+    pub async fn list_account_ids(&self, role_id: &str) -> repository::Result<Vec<String>> {
+        let mut conn = self.pool.get().await?;
+        let ids = AsyncDsl::load(
+            RoleAccountDsl::role_account
+                .filter(RoleAccountDsl::role_id.eq(role_id))
+                .order_by(RoleAccountDsl::id.asc())
+                .select(RoleAccountDsl::account_id),
+            &mut conn,
+        )
+        .await?;
+        Ok(ids)
+    }
+
+    /// 角色下追加账户, 已存在的忽略
+    pub async fn add_accounts(&self, role_id: &str, account_ids: &[String]) -> repository::Result<usize> {
+        if account_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.get().await?;
+        let now = Local::now().naive_local();
+        let insertables: Vec<InsertableRoleAccount> = account_ids
+            .iter()
+            .map(|account_id| InsertableRoleAccount {
+                role_id,
+                account_id: account_id.as_str(),
+                cts: now,
+                uts: now,
+            })
+            .collect();
+        let rows = AsyncDsl::execute(
+            diesel::insert_into(RoleAccountDsl::role_account)
+                .values(&insertables)
+                .on_conflict((RoleAccountDsl::role_id, RoleAccountDsl::account_id))
+                .do_nothing(),
+            &mut conn,
+        )
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn remove_accounts(&self, role_id: &str, account_ids: &[String]) -> repository::Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let rows = AsyncDsl::execute(
+            diesel::delete(RoleAccountDsl::role_account)
+                .filter(RoleAccountDsl::role_id.eq(role_id))
+                .filter(RoleAccountDsl::account_id.eq_any(account_ids)),
+            &mut conn,
+        )
+        .await?;
+        Ok(rows)
+    }
+
+    /// 覆盖账户的角色
+    pub async fn replace_account_roles(&self, account_id: &str, role_ids: &[String]) -> repository::Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let rows = conn
+            .transaction::<usize, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    AsyncDsl::execute(
+                        diesel::delete(RoleAccountDsl::role_account)
+                            .filter(RoleAccountDsl::account_id.eq(account_id)),
+                        &mut tx,
+                    )
+                    .await?;
+                    if role_ids.is_empty() {
+                        return Ok(0);
+                    }
+                    let now = Local::now().naive_local();
+                    let insertables: Vec<InsertableRoleAccount> = role_ids
+                        .iter()
+                        .map(|role_id| InsertableRoleAccount {
+                            role_id: role_id.as_str(),
+                            account_id,
+                            cts: now,
+                            uts: now,
+                        })
+                        .collect();
+                    let rows = AsyncDsl::execute(
+                        diesel::insert_into(RoleAccountDsl::role_account)
+                            .values(&insertables)
+                            .on_conflict_do_nothing(),
+                        &mut tx,
+                    )
+                    .await?;
+                    Ok(rows)
+                })
+            })
+            .await?;
+        Ok(rows)
+    }
+
+    pub async fn list_menu_ids(&self, role_id: &str) -> repository::Result<Vec<String>> {
+        let mut conn = self.pool.get().await?;
+        let ids = AsyncDsl::load(
+            PermissionDsl::permission
+                .filter(PermissionDsl::role_id.eq(role_id))
+                .order_by(PermissionDsl::id.asc())
+                .select(PermissionDsl::menu_id),
+            &mut conn,
+        )
+        .await?;
+        Ok(ids)
+    }
+
+    /// 账户经由开启状态的角色获得的菜单
+    pub async fn list_menu_ids_by_account(&self, account_id: &str) -> repository::Result<Vec<String>> {
+        let mut conn = self.pool.get().await?;
+        let ids = AsyncDsl::load(
+            PermissionDsl::permission
+                .inner_join(RoleDsl::role.on(RoleDsl::role_id.eq(PermissionDsl::role_id)))
+                .inner_join(RoleAccountDsl::role_account.on(RoleAccountDsl::role_id.eq(PermissionDsl::role_id)))
+                .filter(RoleAccountDsl::account_id.eq(account_id))
+                .filter(RoleDsl::role_status.eq("open"))
+                .select(PermissionDsl::menu_id)
+                .distinct(),
+            &mut conn,
+        )
+        .await?;
+        Ok(ids)
+    }
+
+    /// 账户经由启用角色获得的菜单权限码
+    pub async fn list_authorities_by_account(&self, account_id: &str) -> repository::Result<Vec<String>> {
+        let mut conn = self.pool.get().await?;
+        let authorities = AsyncDsl::load(
+            PermissionDsl::permission
+                .inner_join(RoleDsl::role.on(RoleDsl::role_id.eq(PermissionDsl::role_id)))
+                .inner_join(RoleAccountDsl::role_account.on(RoleAccountDsl::role_id.eq(PermissionDsl::role_id)))
+                .inner_join(MenuDsl::menu.on(MenuDsl::menu_id.eq(PermissionDsl::menu_id)))
+                .filter(RoleAccountDsl::account_id.eq(account_id))
+                .filter(RoleDsl::role_status.eq("open"))
+                .filter(MenuDsl::menu_status.eq("open"))
+                .filter(MenuDsl::authority.is_not_null())
+                .select(MenuDsl::authority.assume_not_null())
+                .distinct(),
+            &mut conn,
+        )
+        .await?;
+        Ok(authorities)
+    }
+
+    /// 覆盖角色授权的菜单
+    pub async fn replace_menus(&self, role_id: &str, menu_ids: &[String]) -> repository::Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let rows = conn
+            .transaction::<usize, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    AsyncDsl::execute(
+                        diesel::delete(PermissionDsl::permission)
+                            .filter(PermissionDsl::role_id.eq(role_id)),
+                        &mut tx,
+                    )
+                    .await?;
+                    if menu_ids.is_empty() {
+                        return Ok(0);
+                    }
+                    let now = Local::now().naive_local();
+                    let insertables: Vec<InsertablePermission> = menu_ids
+                        .iter()
+                        .map(|menu_id| InsertablePermission {
+                            role_id,
+                            menu_id: menu_id.as_str(),
+                            cts: now,
+                            uts: now,
+                        })
+                        .collect();
+                    let rows = AsyncDsl::execute(
+                        diesel::insert_into(PermissionDsl::permission)
+                            .values(&insertables)
+                            .on_conflict_do_nothing(),
+                        &mut tx,
+                    )
+                    .await?;
+                    Ok(rows)
+                })
+            })
+            .await?;
+        Ok(rows)
+    }
+
+    fn apply_filters<'a>(
+        &self,
+        params: &'a RoleQueryReq,
+        mut query: RoleSchema::BoxedQuery<'a, diesel::pg::Pg>,
+    ) -> RoleSchema::BoxedQuery<'a, diesel::pg::Pg> {
+        if let Some(param) = &params.role_id
+            && !param.is_empty()
+        {
+            query = query.filter(RoleDsl::role_id.eq(param.as_str()));
+        }
+        if let Some(param) = &params.role_name
+            && !param.is_empty()
+        {
+            query = query.filter(RoleDsl::role_name.ilike(format!("%{}%", param)));
+        }
+        if let Some(param) = &params.role_type
+            && !param.is_empty()
+        {
+            query = query.filter(RoleDsl::role_type.eq(param.as_str()));
+        }
+        match &params.role_status {
+            Some(param) if !param.is_empty() => {
+                query = query.filter(RoleDsl::role_status.eq(param.as_str()));
+            }
+            _ => {
+                query = query.filter(RoleDsl::role_status.ne("deleted"));
+            }
+        }
+        query
+    }
 }
@@ -1,4 +1,5 @@
-use diesel::SelectableHelper;
+use chrono::{Local, NaiveDateTime};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
 use diesel_async::AsyncConnection;
 use laurel_actix::types::repository;
 use laurel_pg::{AsyncDsl, DbPool};
@@ -16,6 +17,19 @@ impl Repository {
         Self { pool }
     }
 
+    pub async fn find(&self, ticket_id: &str) -> repository::Result<Option<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let ticket = AsyncDsl::first(
+            TicketDsl::ticket
+                .filter(TicketDsl::ticket_id.eq(ticket_id))
+                .select(Ticket::as_select()),
+            &mut conn,
+        )
+            .await
+            .optional()?;
+        Ok(ticket)
+    }
+
     pub async fn save<'a>(&self, insertable: &InsertableTicket<'a>) -> repository::Result<Ticket>{
         let mut conn = self.pool.get().await?;
         let ticket = conn
@@ -34,4 +48,124 @@ impl Repository {
             .await?;
         Ok(ticket)
     }
-}
\ No newline at end of file
+
+    /// 账户下未过期的正常票据, 按登录时间倒序
+    pub async fn list_active_by_account(&self, account_id: &str) -> repository::Result<Vec<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let tickets = AsyncDsl::load(
+            TicketDsl::ticket
+                .filter(TicketDsl::account_id.eq(account_id))
+                .filter(TicketDsl::ticket_state.eq("normal"))
+                .filter(TicketDsl::ets.gt(Local::now().naive_local()))
+                .order_by(TicketDsl::cts.desc())
+                .select(Ticket::as_select()),
+            &mut conn,
+        )
+            .await?;
+        Ok(tickets)
+    }
+
+    /// 仅更新正常状态的票据, 返回被更新的票据
+    pub async fn update_state(&self, ticket_id: &str, ticket_state: &str) -> repository::Result<Option<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let ticket = conn
+            .transaction::<Option<Ticket>, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    let ticket = AsyncDsl::get_result(
+                        diesel::update(TicketDsl::ticket)
+                            .filter(TicketDsl::ticket_id.eq(ticket_id))
+                            .filter(TicketDsl::ticket_state.eq("normal"))
+                            .set((
+                                TicketDsl::ticket_state.eq(ticket_state),
+                                TicketDsl::uts.eq(Local::now().naive_local()),
+                            ))
+                            .returning(Ticket::as_returning()),
+                        &mut tx,
+                    )
+                        .await
+                        .optional()?;
+                    Ok(ticket)
+                })
+            })
+            .await?;
+        Ok(ticket)
+    }
+
+    /// 仅更新客户端下正常状态的票据, 返回被更新的票据
+    pub async fn update_state_by_client(&self, client_id: &str, ticket_state: &str) -> repository::Result<Vec<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let tickets = AsyncDsl::get_results(
+            diesel::update(TicketDsl::ticket)
+                .filter(TicketDsl::client_id.eq(client_id))
+                .filter(TicketDsl::ticket_state.eq("normal"))
+                .set((
+                    TicketDsl::ticket_state.eq(ticket_state),
+                    TicketDsl::uts.eq(Local::now().naive_local()),
+                ))
+                .returning(Ticket::as_returning()),
+            &mut conn,
+        )
+            .await?;
+        Ok(tickets)
+    }
+
+    pub async fn update_state_by_account(&self, account_id: &str, ticket_state: &str) -> repository::Result<Vec<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let tickets = conn
+            .transaction::<Vec<Ticket>, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    let tickets = AsyncDsl::get_results(
+                        diesel::update(TicketDsl::ticket)
+                            .filter(TicketDsl::account_id.eq(account_id))
+                            .filter(TicketDsl::ticket_state.eq("normal"))
+                            .set((
+                                TicketDsl::ticket_state.eq(ticket_state),
+                                TicketDsl::uts.eq(Local::now().naive_local()),
+                            ))
+                            .returning(Ticket::as_returning()),
+                        &mut tx,
+                    )
+                        .await?;
+                    Ok(tickets)
+                })
+            })
+            .await?;
+        Ok(tickets)
+    }
+
+    /// 轮换 refresh token, 仅当 refresh_id 仍为旧值时成功
+    pub async fn rotate(
+        &self,
+        ticket_id: &str,
+        refresh_id: &str,
+        new_refresh_id: &str,
+        token: &str,
+        ets: NaiveDateTime,
+    ) -> repository::Result<Option<Ticket>>{
+        let mut conn = self.pool.get().await?;
+        let ticket = conn
+            .transaction::<Option<Ticket>, anyhow::Error, _>(|mut tx| {
+                Box::pin(async move {
+                    let ticket = AsyncDsl::get_result(
+                        diesel::update(TicketDsl::ticket)
+                            .filter(TicketDsl::ticket_id.eq(ticket_id))
+                            .filter(TicketDsl::refresh_id.eq(refresh_id))
+                            .filter(TicketDsl::ticket_state.eq("normal"))
+                            .set((
+                                TicketDsl::refresh_id.eq(new_refresh_id),
+                                TicketDsl::token.eq(token),
+                                TicketDsl::uts.eq(Local::now().naive_local()),
+                                TicketDsl::ets.eq(ets),
+                            ))
+                            .returning(Ticket::as_returning()),
+                        &mut tx,
+                    )
+                        .await
+                        .optional()?;
+                    Ok(ticket)
+                })
+            })
+            .await?;
+        Ok(ticket)
+    }
+}
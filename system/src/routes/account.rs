@@ -1,22 +1,57 @@
-use crate::model::account::{AccountLoginVo, LoginVo};
+use crate::model::account::{AccountCreateReq, AccountEntity, AccountLoginVo, AccountPageVo, AccountQueryReq, AccountState, AccountStateReq, AccountVo, ImpersonateReq, ImpersonationVo, LoginCodeReq, LoginCodeSendReq, LoginStep, LoginVo, PasswordChangeReq, PasswordRenewReq, PasswordResetReq};
+use crate::model::mfa::LoginMfaReq;
+use crate::model::qr_login::{QrLoginConfirmReq, QrLoginCreateReq, QrLoginPollReq, QrLoginScanReq, QrLoginVo, QrScanVo, QrStatusVo};
+use crate::model::ticket::{IssuedTicket, TicketVo, TokenRefreshReq, TokenRefreshVo};
 use crate::service::account::AccountService;
-use actix_web::{HttpRequest, get, post, web};
+use crate::service::impersonation::ImpersonationService;
+use crate::service::qr_login::QrLoginService;
+use crate::service::ticket::TicketService;
+use crate::service::token::TokenService;
+use std::sync::Arc;
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use actix_web::cookie::Cookie;
+use chrono::Local;
 use tracing::error;
 use laurel_actix::Data;
+use laurel_actix::config::SessionConfig;
+use laurel_actix::error::AppError;
+use laurel_actix::session;
+use laurel_actix::types::common::ApiResult;
 use laurel_actix::handler::Token;
+use laurel_actix::permission::Permission;
 use laurel_actix::types::{Autowired, RequestBody, RequestExtension, RequestParam, route};
+use laurel_common::types::{HappyEnum, Pagination, SelectOption};
+use crate::utils::token_utils;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/api/system/account").service(login).service(test));
+    cfg.service(
+        web::scope("/api/system/account")
+            .service(login)
+            .service(login_mfa)
+            .service(send_login_code)
+            .service(login_code)
+            .service(create_qr_login)
+            .service(qr_login_status)
+            .service(login_qr)
+            .service(scan_qr_login)
+            .service(confirm_qr_login)
+            .service(refresh_token)
+            .service(logout)
+            .service(create)
+            .service(change_password)
+            .service(renew_password)
+            .service(reset_password)
+            .service(page)
+            .service(list_state_options)
+            .service(change_state)
+            .service(impersonate)
+            .service(stop_impersonate)
+            .service(test),
+    );
 }
 
-#[post("/login")]
-async fn login(
-    _req : HttpRequest,
-    account_service: Autowired<AccountService>,
-    req: RequestBody<AccountLoginVo>,
-) -> route::Result<LoginVo> {
-    let ua = match _req.headers().get(actix_web::http::header::USER_AGENT){
+pub(super) fn user_agent(req: &HttpRequest) -> Option<&str> {
+    match req.headers().get(actix_web::http::header::USER_AGENT){
         Some(ua) => {
             match ua.to_str(){
                 Ok(ua) => Some(ua),
@@ -27,19 +62,297 @@ async fn login(
             }
         },
         None => None,
+    }
+}
+
+fn session_cookies(session: &SessionConfig, issued: &IssuedTicket) -> Vec<Cookie<'static>> {
+    session::issue_cookies(
+        session,
+        issued.ticket.token.as_str(),
+        issued.refresh_token.as_str(),
+        token_utils::token().as_str(),
+        (issued.ticket.ets - Local::now().naive_local()).num_seconds(),
+    )
+}
+
+/// cookie 会话模式下令牌写入 cookie, 响应中不再返回
+pub(super) fn login_response(session: &SessionConfig, result: (AccountEntity, LoginStep)) -> HttpResponse {
+    let cookies = match &result.1 {
+        LoginStep::Issued(issued) if session.cookie_mode() => session_cookies(session, issued),
+        _ => vec![],
     };
+    let mut vo = LoginVo::from(result);
+    if !cookies.is_empty() {
+        vo.token = None;
+        vo.refresh_token = None;
+    }
+    session::respond(ApiResult::of(vo), cookies)
+}
+
+#[post("/login")]
+async fn login(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<AccountLoginVo>,
+) -> route::Response {
+    let ua = user_agent(&_req);
     let ip = laurel_actix::utils::ip(&_req);
     let login_vo = req.into_inner();
+    Ok(login_response(&session, account_service.login(&login_vo, ua, ip).await?))
+}
+
+#[post("/login/mfa")]
+async fn login_mfa(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<LoginMfaReq>,
+) -> route::Response {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Ok(login_response(&session, account_service.login_mfa(&req, ua, ip).await?))
+}
+
+#[post("/login/code/send")]
+async fn send_login_code(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    req: RequestBody<LoginCodeSendReq>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    account_service.send_login_code(&req, ip).await?;
+    Data!(true)
+}
+
+#[post("/login/code")]
+async fn login_code(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<LoginCodeReq>,
+) -> route::Response {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Ok(login_response(&session, account_service.login_code(&req, ua, ip).await?))
+}
+
+#[post("/login/qr/create")]
+async fn create_qr_login(
+    _req : HttpRequest,
+    qr_login_service: Autowired<QrLoginService>,
+    req: RequestBody<QrLoginCreateReq>,
+) -> route::Result<QrLoginVo> {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(qr_login_service.create(&req, ua, ip).await?)
+}
+
+#[post("/login/qr/status")]
+async fn qr_login_status(
+    qr_login_service: Autowired<QrLoginService>,
+    req: RequestBody<QrLoginPollReq>,
+) -> route::Result<QrStatusVo> {
+    Data!(qr_login_service.status(&req).await?)
+}
+
+#[post("/login/qr")]
+async fn login_qr(
+    _req : HttpRequest,
+    qr_login_service: Autowired<QrLoginService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<QrLoginPollReq>,
+) -> route::Response {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Ok(login_response(&session, qr_login_service.login(&req, ua, ip).await?))
+}
+
+#[post("/login/qr/scan")]
+async fn scan_qr_login(
+    qr_login_service: Autowired<QrLoginService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<QrLoginScanReq>,
+) -> route::Result<QrScanVo> {
+    Data!(qr_login_service.scan(&token, &req).await?)
+}
+
+#[post("/login/qr/confirm")]
+async fn confirm_qr_login(
+    _req : HttpRequest,
+    qr_login_service: Autowired<QrLoginService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<QrLoginConfirmReq>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    qr_login_service.confirm(&token, &req, ip).await?;
+    Data!(true)
+}
+
+#[post("/token/refresh")]
+async fn refresh_token(
+    _req : HttpRequest,
+    token_service: Autowired<Arc<TokenService>>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<TokenRefreshReq>,
+) -> route::Response {
+    // cookie 会话未传 refresh token 时从 cookie 读取, 接口不经过令牌校验, 需自行校验 CSRF
+    let from_cookie = req.refresh_token.is_empty() && session.cookie_mode();
+    let refresh_token = if from_cookie {
+        if !session::csrf_valid(&_req, &session) {
+            return Err(AppError::Forbidden("csrf token mismatch".to_string()));
+        }
+        _req.cookie(session.refresh_cookie_name.as_str())
+            .map(|c| c.value().to_string())
+            .unwrap_or_default()
+    } else {
+        req.refresh_token.clone()
+    };
+    let issued = token_service.refresh(refresh_token.as_str(), "").await?;
+    let cookies = if from_cookie { session_cookies(&session, &issued) } else { vec![] };
+    let mut vo = TokenRefreshVo::from(issued);
+    if from_cookie {
+        vo.token = String::new();
+        vo.refresh_token = String::new();
+    }
+    Ok(session::respond(ApiResult::of(vo), cookies))
+}
+
+#[post("/logout")]
+async fn logout(
+    _req : HttpRequest,
+    ticket_service: Autowired<TicketService>,
+    session: Autowired<SessionConfig>,
+    token: RequestExtension<Token>,
+) -> route::Response {
+    let ip = laurel_actix::utils::ip(&_req);
+    let ticket = ticket_service
+        .logout(&token, ip)
+        .await?
+        .map(TicketVo::from);
+    let cookies = if session.cookie_mode() { session::clear_cookies(&session) } else { vec![] };
+    Ok(session::respond(ApiResult::<TicketVo>::of(ticket), cookies))
+}
+
+#[post("/create", wrap = "Permission::new(\"system:account:create\")")]
+async fn create(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<AccountCreateReq>,
+) -> route::Result<AccountVo> {
+    let ip = laurel_actix::utils::ip(&_req);
     Data!(
-        LoginVo::from(account_service.login(&login_vo, ua, ip).await?)
+        AccountVo::from(account_service.create(&token, &req, ip).await?)
     )
 }
 
+#[post("/password/change")]
+async fn change_password(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<PasswordChangeReq>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    account_service.change_password(&token, &req, ip).await?;
+    Data!(true)
+}
+
+#[post("/password/renew")]
+async fn renew_password(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<PasswordRenewReq>,
+) -> route::Response {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Ok(login_response(&session, account_service.renew_password(&req, ua, ip).await?))
+}
+
+#[post("/password/reset", wrap = "Permission::new(\"system:account:reset\")")]
+async fn reset_password(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<PasswordResetReq>,
+) -> route::Result<AccountVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        AccountVo::from(account_service.reset_password(&token, &req, ip).await?)
+    )
+}
+
+#[post("/page", wrap = "Permission::new(\"system:account:view\")")]
+async fn page(
+    account_service: Autowired<AccountService>,
+    req: RequestBody<AccountQueryReq>,
+) -> route::Result<Pagination<AccountPageVo>> {
+    let (page, size) = match &req.page {
+        Some(p) => (p.page, p.size),
+        _ => (1, 10),
+    };
+    Data!(
+        account_service.page(&req, page, size).await?.to_with_index()
+    )
+}
+
+#[get("/state/options")]
+async fn list_state_options() -> route::Result<Vec<SelectOption<&'static str, &'static str>>> {
+    Data!(
+        AccountState::options()
+    )
+}
+
+#[post("/state", wrap = "Permission::new(\"system:account:state\")")]
+async fn change_state(
+    _req : HttpRequest,
+    account_service: Autowired<AccountService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<AccountStateReq>,
+) -> route::Result<AccountVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        AccountVo::from(account_service.change_state(&token, &req, ip).await?)
+    )
+}
+
+/// 所需权限由 uc_config.impersonation.permission 配置, 在服务内校验
+#[post("/impersonate")]
+async fn impersonate(
+    _req : HttpRequest,
+    impersonation_service: Autowired<ImpersonationService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<ImpersonateReq>,
+) -> route::Result<ImpersonationVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        ImpersonationVo::from(impersonation_service.start(&token, &req, ip).await?)
+    )
+}
+
+#[post("/impersonate/stop")]
+async fn stop_impersonate(
+    _req : HttpRequest,
+    impersonation_service: Autowired<ImpersonationService>,
+    token: RequestExtension<Token>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    impersonation_service.stop(&token, ip).await?;
+    Data!(true)
+}
+
 #[get("/test")]
 async fn test(token: RequestExtension<Token>) -> route::Result<Token> {
     Data!(
         Token {
             account_id: token.account_id.clone(),
+            ticket_id: token.ticket_id.clone(),
+            impersonator: token.impersonator.clone(),
+            api_key: token.api_key.clone(),
+            scopes: token.scopes.clone(),
+            client_id: token.client_id.clone(),
         }
     )
 }
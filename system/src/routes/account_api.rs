@@ -1,16 +1,19 @@
+use std::sync::Arc;
 use crate::model::account::AccountQuery;
 use crate::service::account::AccountService;
 use actix_web::{get, web};
-use laurel_actix::error::AppError;
+use laurel_actix::error::{AppError, BizError};
 use laurel_actix::handler::TokenHandler;
 use laurel_actix::types::{Autowired, RequestParam, route};
 use laurel_uc_api::account::{AccountBo, TokenParseQuery, TokenPayloadBo};
 use tracing::error;
 use laurel_actix::Data;
+use laurel_actix::signature::Signed;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/interface/system/uc/account")
+            .wrap(Signed)
             .service(find_account)
             .service(parse_token),
     );
@@ -39,7 +42,7 @@ async fn find_account(
 
 #[get("/token")]
 async fn parse_token(
-    token_service: Autowired<Box<dyn TokenHandler>>,
+    token_service: Autowired<Arc<dyn TokenHandler>>,
     token: RequestParam<TokenParseQuery>,
 ) -> route::Result<TokenPayloadBo> {
     match token_service.parse(token.token.as_str()).await {
@@ -47,11 +50,20 @@ async fn parse_token(
             Some(t) => Data!(
                 TokenPayloadBo {
                     account_id: t.account_id,
+                    ticket_id: t.ticket_id,
+                    impersonator: t.impersonator,
+                    api_key: t.api_key,
+                    scopes: t.scopes,
+                    client_id: t.client_id,
                 }
             ),
             _ => Data!(None),
         },
         Err(err) => {
+            // 业务拒绝(如会话被挤下线)原样返回, 由调用方透传给前端
+            if let Some(biz) = err.downcast_ref::<BizError>() {
+                return Err(AppError::AnyhowError(BizError::new(biz.code, biz.message.clone()).into()));
+            }
             error!("parse token: {} error: {}", token.token, err);
             Err(AppError::AuthError(format!("invalid token: {}", token.token).to_string()).into())
         }
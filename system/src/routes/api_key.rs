@@ -0,0 +1,56 @@
+use crate::model::api_key::{ApiKeyCreateReq, ApiKeyRevokeReq, ApiKeyVo};
+use crate::service::api_key::ApiKeyService;
+use actix_web::{HttpRequest, get, post, web};
+use laurel_actix::Data;
+use laurel_actix::handler::Token;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, route};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/api-key")
+            .service(create_key)
+            .service(list_keys)
+            .service(revoke_key),
+    );
+}
+
+#[post("/create")]
+async fn create_key(
+    _req : HttpRequest,
+    api_key_service: Autowired<ApiKeyService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<ApiKeyCreateReq>,
+) -> route::Result<ApiKeyVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        api_key_service.create(&token, &req, ip).await?
+    )
+}
+
+#[get("/list")]
+async fn list_keys(
+    api_key_service: Autowired<ApiKeyService>,
+    token: RequestExtension<Token>,
+) -> route::Result<Vec<ApiKeyVo>> {
+    Data!(
+        api_key_service
+            .list(&token)
+            .await?
+            .into_iter()
+            .map(ApiKeyVo::from)
+            .collect::<Vec<_>>()
+    )
+}
+
+#[post("/revoke")]
+async fn revoke_key(
+    _req : HttpRequest,
+    api_key_service: Autowired<ApiKeyService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<ApiKeyRevokeReq>,
+) -> route::Result<ApiKeyVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        ApiKeyVo::from(api_key_service.revoke(&token, &req, ip).await?)
+    )
+}
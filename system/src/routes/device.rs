@@ -0,0 +1,36 @@
+use crate::model::device::{DeviceRevokeReq, TrustedDeviceVo};
+use crate::service::device::DeviceService;
+use actix_web::{HttpRequest, get, post, web};
+use laurel_actix::Data;
+use laurel_actix::handler::Token;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, route};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/device")
+            .service(list_devices)
+            .service(revoke_device),
+    );
+}
+
+#[get("/list")]
+async fn list_devices(
+    device_service: Autowired<DeviceService>,
+    token: RequestExtension<Token>,
+) -> route::Result<Vec<TrustedDeviceVo>> {
+    Data!(
+        device_service.list(&token).await?
+    )
+}
+
+#[post("/revoke")]
+async fn revoke_device(
+    _req : HttpRequest,
+    device_service: Autowired<DeviceService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<DeviceRevokeReq>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    device_service.revoke(&token, &req, ip).await?;
+    Data!(true)
+}
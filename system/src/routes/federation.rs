@@ -0,0 +1,43 @@
+use actix_web::{HttpRequest, get, post, web};
+use laurel_actix::Data;
+use laurel_actix::config::SessionConfig;
+use laurel_actix::types::{Autowired, RequestBody, route};
+use crate::model::federation::{FederatedProviderVo, FederationAuthorizeReq, FederationAuthorizeVo, FederationLoginReq};
+use crate::routes::account::{login_response, user_agent};
+use crate::service::federation::FederationService;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/federation")
+            .service(list_providers)
+            .service(authorize)
+            .service(login),
+    );
+}
+
+#[get("/providers")]
+async fn list_providers(
+    federation_service: Autowired<FederationService>,
+) -> route::Result<Vec<FederatedProviderVo>> {
+    Data!(federation_service.providers())
+}
+
+#[post("/authorize")]
+async fn authorize(
+    federation_service: Autowired<FederationService>,
+    req: RequestBody<FederationAuthorizeReq>,
+) -> route::Result<FederationAuthorizeVo> {
+    Data!(federation_service.authorize(&req).await?)
+}
+
+#[post("/login")]
+async fn login(
+    _req : HttpRequest,
+    federation_service: Autowired<FederationService>,
+    session: Autowired<SessionConfig>,
+    req: RequestBody<FederationLoginReq>,
+) -> route::Response {
+    let ua = user_agent(&_req);
+    let ip = laurel_actix::utils::ip(&_req);
+    Ok(login_response(&session, federation_service.login(&req, ua, ip).await?))
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use crate::service::oauth::OAuthService;
+use crate::service::token::TokenService;
+use actix_web::{HttpResponse, get, web};
+use laurel_actix::types::Autowired;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/.well-known")
+            .service(jwks)
+            .service(openid_configuration),
+    );
+}
+
+/// 标准 JWKS 格式, 不包装 ApiResult
+#[get("/jwks.json")]
+async fn jwks(token_service: Autowired<Arc<TokenService>>) -> HttpResponse {
+    HttpResponse::Ok().json(token_service.jwks())
+}
+
+/// OIDC 发现文档
+#[get("/openid-configuration")]
+async fn openid_configuration(oauth_service: Autowired<OAuthService>) -> HttpResponse {
+    HttpResponse::Ok().json(oauth_service.discovery())
+}
@@ -1,10 +1,12 @@
 use crate::model::menu::{
     Menu, MenuActionType, MenuCreateReq, MenuQueryReq, MenuStatus, MenuType, MenuUpdateReq, MenuVo,
+    MineMenuVo,
 };
 use crate::service::menu::MenuService;
 use actix_web::{get, post, web};
 use laurel_actix::Data;
-use laurel_actix::types::{Autowired, RequestBody, RequestParam, route};
+use laurel_actix::handler::Token;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, RequestParam, route};
 use laurel_common::types::{HappyEnum, Pagination, SelectOption};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
@@ -14,6 +16,7 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .service(list_menu_status_options)
             .service(tree_all_menus)
             .service(tree_used_menus)
+            .service(tree_mine_menus)
             .service(page_menus)
             .service(list_menu_type_options)
             .service(list_menu_action_options)
@@ -101,6 +104,21 @@ async fn tree_used_menus(
     ))
 }
 
+#[get("/tree/mine")]
+async fn tree_mine_menus(
+    menu_service: web::Data<MenuService>,
+    token: RequestExtension<Token>,
+    query: RequestParam<MenuQueryReq>,
+) -> route::Result<MineMenuVo> {
+    let (menus, authorities) = menu_service
+        .list_granted_menus(query.app_id.as_str(), token.account_id.as_str())
+        .await?;
+    Data!(MineMenuVo {
+        menus: Menu::build_tree(menus),
+        authorities,
+    })
+}
+
 #[post("/tree/all")]
 async fn tree_all_menus(
     menu_service: web::Data<MenuService>,
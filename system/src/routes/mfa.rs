@@ -0,0 +1,69 @@
+use crate::model::mfa::{MfaCodeReq, MfaStatusVo, RecoveryCodesVo, TotpEnrollVo};
+use crate::service::mfa::MfaService;
+use actix_web::{HttpRequest, get, post, web};
+use laurel_actix::Data;
+use laurel_actix::handler::Token;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, route};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/mfa")
+            .service(status)
+            .service(enroll)
+            .service(activate)
+            .service(disable)
+            .service(regenerate_recovery_codes),
+    );
+}
+
+#[get("/status")]
+async fn status(
+    mfa_service: Autowired<MfaService>,
+    token: RequestExtension<Token>,
+) -> route::Result<MfaStatusVo> {
+    Data!(
+        MfaStatusVo::from(mfa_service.find(token.account_id.as_str()).await?)
+    )
+}
+
+#[post("/totp/enroll")]
+async fn enroll(
+    mfa_service: Autowired<MfaService>,
+    token: RequestExtension<Token>,
+) -> route::Result<TotpEnrollVo> {
+    Data!(mfa_service.enroll(&token).await?)
+}
+
+#[post("/totp/activate")]
+async fn activate(
+    _req: HttpRequest,
+    mfa_service: Autowired<MfaService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<MfaCodeReq>,
+) -> route::Result<RecoveryCodesVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(mfa_service.activate(&token, req.code.as_str(), ip).await?)
+}
+
+#[post("/totp/disable")]
+async fn disable(
+    _req: HttpRequest,
+    mfa_service: Autowired<MfaService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<MfaCodeReq>,
+) -> route::Result<bool> {
+    let ip = laurel_actix::utils::ip(&_req);
+    mfa_service.disable(&token, req.code.as_str(), ip).await?;
+    Data!(true)
+}
+
+#[post("/recovery/regenerate")]
+async fn regenerate_recovery_codes(
+    _req: HttpRequest,
+    mfa_service: Autowired<MfaService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<MfaCodeReq>,
+) -> route::Result<RecoveryCodesVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(mfa_service.regenerate_recovery_codes(&token, req.code.as_str(), ip).await?)
+}
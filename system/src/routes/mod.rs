@@ -5,7 +5,15 @@ pub mod fe_micro_service;
 pub mod menu;
 mod account;
 mod account_api;
+mod api_key;
+mod device;
+mod federation;
+mod jwks;
+mod mfa;
+mod oauth;
 mod profile;
+mod role;
+mod ticket;
 
 
 
@@ -15,5 +23,13 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         .configure(dict::config)
         .configure(account::config)
         .configure(account_api::config)
-        .configure(profile::config);
+        .configure(api_key::config)
+        .configure(device::config)
+        .configure(federation::config)
+        .configure(jwks::config)
+        .configure(mfa::config)
+        .configure(oauth::config)
+        .configure(profile::config)
+        .configure(role::config)
+        .configure(ticket::config);
 }
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use tracing::error;
+use laurel_actix::Data;
+use laurel_actix::config::SessionConfig;
+use laurel_actix::handler::Token;
+use laurel_actix::permission::Permission;
+use laurel_actix::session;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, RequestParam, route};
+use crate::model::oauth::{AuthorizeReq, OAuthClientCreateReq, OAuthClientStateReq, OAuthClientVo, OAuthTokenReq};
+use crate::service::oauth::{OAuthError, OAuthService};
+use crate::service::token::TokenService;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/oauth2")
+            .service(authorize)
+            .service(issue_token)
+            .service(userinfo)
+            .service(create_client)
+            .service(list_clients)
+            .service(change_client_state),
+    );
+}
+
+fn redirect(location: String) -> HttpResponse {
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+/// OAuth2 端点按规范返回 error / error_description, 不包装 ApiResult
+fn oauth_error(err: anyhow::Error) -> HttpResponse {
+    match err.downcast_ref::<OAuthError>() {
+        Some(e) => {
+            let mut response = match e.error {
+                "invalid_client" | "invalid_token" => HttpResponse::Unauthorized(),
+                "insufficient_scope" => HttpResponse::Forbidden(),
+                _ => HttpResponse::BadRequest(),
+            };
+            response.insert_header((header::CACHE_CONTROL, "no-store")).json(e)
+        }
+        None => {
+            error!("oauth error: {}", err);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "server_error" }))
+        }
+    }
+}
+
+/// 授权端点不经过令牌校验: 自行识别会话, 未登录时跳转登录页
+#[get("/authorize")]
+async fn authorize(
+    _req: HttpRequest,
+    oauth_service: Autowired<OAuthService>,
+    token_service: Autowired<Arc<TokenService>>,
+    session: Autowired<SessionConfig>,
+    req: RequestParam<AuthorizeReq>,
+) -> HttpResponse {
+    let bearer = _req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let token = match session::credential(&_req, bearer, Some(&session)) {
+        Some((t, _)) => token_service.validate(t.as_str()).await.ok().flatten(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) => t,
+        None => return redirect(oauth_service.login_redirect(_req.uri().to_string().as_str())),
+    };
+    let ip = laurel_actix::utils::ip(&_req);
+    match oauth_service.authorize(&token, &req, ip).await {
+        Ok(location) => redirect(location),
+        Err(err) => oauth_error(err),
+    }
+}
+
+/// client_secret_basic: Authorization: Basic base64(client_id:client_secret)
+fn basic_credentials(req: &HttpRequest) -> Option<(String, String)> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let decoded = STANDARD.decode(value.strip_prefix("Basic ")?).ok()?;
+    let (id, secret) = String::from_utf8(decoded).ok()?.split_once(':').map(|(i, s)| (i.to_string(), s.to_string()))?;
+    Some((id, secret))
+}
+
+#[post("/token")]
+async fn issue_token(
+    _req: HttpRequest,
+    oauth_service: Autowired<OAuthService>,
+    req: web::Form<OAuthTokenReq>,
+) -> HttpResponse {
+    match oauth_service.token(&req, basic_credentials(&_req)).await {
+        Ok(vo) => HttpResponse::Ok()
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .json(vo),
+        Err(err) => oauth_error(err),
+    }
+}
+
+#[get("/userinfo")]
+async fn userinfo(
+    oauth_service: Autowired<OAuthService>,
+    token: RequestExtension<Token>,
+) -> HttpResponse {
+    match oauth_service.userinfo(&token).await {
+        Ok(info) => HttpResponse::Ok().json(info),
+        Err(err) => oauth_error(err),
+    }
+}
+
+#[post("/client/create", wrap = "Permission::new(\"system:oauth:client\")")]
+async fn create_client(
+    _req: HttpRequest,
+    oauth_service: Autowired<OAuthService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<OAuthClientCreateReq>,
+) -> route::Result<OAuthClientVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        oauth_service.create_client(&token, &req, ip).await?
+    )
+}
+
+#[get("/client/list", wrap = "Permission::new(\"system:oauth:client\")")]
+async fn list_clients(
+    oauth_service: Autowired<OAuthService>,
+) -> route::Result<Vec<OAuthClientVo>> {
+    Data!(
+        oauth_service
+            .list_clients()
+            .await?
+            .into_iter()
+            .map(OAuthClientVo::from)
+            .collect::<Vec<_>>()
+    )
+}
+
+#[post("/client/state", wrap = "Permission::new(\"system:oauth:client\")")]
+async fn change_client_state(
+    _req: HttpRequest,
+    oauth_service: Autowired<OAuthService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<OAuthClientStateReq>,
+) -> route::Result<OAuthClientVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        OAuthClientVo::from(oauth_service.change_client_state(&token, &req, ip).await?)
+    )
+}
@@ -1,12 +1,15 @@
 use crate::service::profile::ProfileService;
 use actix_web::{post, web};
 use laurel_actix::Data;
+use laurel_actix::signature::Signed;
 use laurel_actix::types::{Autowired, RequestBody, route};
 use laurel_uc_api::profile::{ProfileBo, ProfileQuery};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
-        web::scope("/interface/system/uc/profile").service(list_profiles),
+        web::scope("/interface/system/uc/profile")
+            .wrap(Signed)
+            .service(list_profiles),
     );
 }
 
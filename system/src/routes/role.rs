@@ -0,0 +1,177 @@
+use crate::model::role::{
+    AccountRolesReq, RoleAccountsReq, RoleCreateReq, RoleMenusReq, RoleQueryReq, RoleStatus,
+    RoleUpdateReq, RoleVo,
+};
+use crate::service::role::RoleService;
+use actix_web::{HttpRequest, get, post, web};
+use laurel_actix::Data;
+use laurel_actix::handler::Token;
+use laurel_actix::permission::Permission;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, RequestParam, route};
+use laurel_common::types::{HappyEnum, Pagination, SelectOption};
+use serde::Deserialize;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/role")
+            .service(create_role)
+            .service(update_role)
+            .service(page_roles)
+            .service(list_role_status_options)
+            .service(list_role_accounts)
+            .service(add_role_accounts)
+            .service(remove_role_accounts)
+            .service(list_account_roles)
+            .service(replace_account_roles)
+            .service(list_role_menus)
+            .service(grant_role_menus)
+            .service(find_role),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoleIdReq {
+    role_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountIdReq {
+    account_id: String,
+}
+
+#[post("/create", wrap = "Permission::new(\"system:role:edit\")")]
+async fn create_role(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<RoleCreateReq>,
+) -> route::Result<RoleVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        RoleVo::from(role_service.create(&token, &req, ip).await?)
+    )
+}
+
+#[post("/update", wrap = "Permission::new(\"system:role:edit\")")]
+async fn update_role(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<RoleUpdateReq>,
+) -> route::Result<RoleVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        RoleVo::from(role_service.update(&token, req.into_inner(), ip).await?)
+    )
+}
+
+#[get("", wrap = "Permission::new(\"system:role:view\")")]
+async fn find_role(
+    role_service: Autowired<RoleService>,
+    req: RequestParam<RoleIdReq>,
+) -> route::Result<RoleVo> {
+    Data!(
+        role_service
+            .find(req.role_id.as_str())
+            .await?
+            .map(RoleVo::from)
+    )
+}
+
+#[post("/page", wrap = "Permission::new(\"system:role:view\")")]
+async fn page_roles(
+    role_service: Autowired<RoleService>,
+    req: RequestBody<RoleQueryReq>,
+) -> route::Result<Pagination<RoleVo>> {
+    let (page, size) = match &req.pagination {
+        Some(p) => (p.page, p.size),
+        _ => (1, 10),
+    };
+    Data!(
+        role_service
+            .page(&req, page, size)
+            .await?
+            .to_with_index::<RoleVo>()
+    )
+}
+
+#[get("/status/options")]
+async fn list_role_status_options() -> route::Result<Vec<SelectOption<&'static str, &'static str>>> {
+    Data!(RoleStatus::options())
+}
+
+#[get("/accounts", wrap = "Permission::new(\"system:role:view\")")]
+async fn list_role_accounts(
+    role_service: Autowired<RoleService>,
+    req: RequestParam<RoleIdReq>,
+) -> route::Result<Vec<String>> {
+    Data!(role_service.list_account_ids(req.role_id.as_str()).await?)
+}
+
+#[post("/accounts/add", wrap = "Permission::new(\"system:role:grant\")")]
+async fn add_role_accounts(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<RoleAccountsReq>,
+) -> route::Result<usize> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(role_service.add_accounts(&token, &req, ip).await?)
+}
+
+#[post("/accounts/remove", wrap = "Permission::new(\"system:role:grant\")")]
+async fn remove_role_accounts(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<RoleAccountsReq>,
+) -> route::Result<usize> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(role_service.remove_accounts(&token, &req, ip).await?)
+}
+
+#[get("/account", wrap = "Permission::new(\"system:role:view\")")]
+async fn list_account_roles(
+    role_service: Autowired<RoleService>,
+    req: RequestParam<AccountIdReq>,
+) -> route::Result<Vec<RoleVo>> {
+    let roles: Vec<RoleVo> = role_service
+        .list_account_roles(req.account_id.as_str())
+        .await?
+        .into_iter()
+        .map(RoleVo::from)
+        .collect();
+    Data!(roles)
+}
+
+#[post("/account/replace", wrap = "Permission::new(\"system:role:grant\")")]
+async fn replace_account_roles(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<AccountRolesReq>,
+) -> route::Result<usize> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(role_service.replace_account_roles(&token, &req, ip).await?)
+}
+
+#[get("/menus", wrap = "Permission::new(\"system:role:view\")")]
+async fn list_role_menus(
+    role_service: Autowired<RoleService>,
+    req: RequestParam<RoleIdReq>,
+) -> route::Result<Vec<String>> {
+    Data!(role_service.list_menu_ids(req.role_id.as_str()).await?)
+}
+
+#[post("/menus/grant", wrap = "Permission::new(\"system:role:grant\")")]
+async fn grant_role_menus(
+    _req: HttpRequest,
+    role_service: Autowired<RoleService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<RoleMenusReq>,
+) -> route::Result<usize> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(role_service.grant_menus(&token, &req, ip).await?)
+}
@@ -0,0 +1,99 @@
+use crate::model::ticket::{AccountTicketRevokeReq, SessionKickReq, SessionVo, TicketRevokeReq, TicketVo};
+use crate::service::ticket::TicketService;
+use actix_web::{HttpRequest, get, post, web};
+use serde::Deserialize;
+use laurel_actix::Data;
+use laurel_actix::handler::Token;
+use laurel_actix::permission::Permission;
+use laurel_actix::types::{Autowired, RequestBody, RequestExtension, RequestParam, route};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/system/ticket")
+            .service(list_sessions)
+            .service(kick_session)
+            .service(list_account_sessions)
+            .service(revoke_ticket)
+            .service(revoke_account_tickets),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountIdReq {
+    account_id: String,
+}
+
+#[get("/sessions")]
+async fn list_sessions(
+    ticket_service: Autowired<TicketService>,
+    token: RequestExtension<Token>,
+) -> route::Result<Vec<SessionVo>> {
+    Data!(
+        ticket_service
+            .sessions(token.account_id.as_str(), token.ticket_id.as_str())
+            .await?
+    )
+}
+
+#[post("/sessions/kick")]
+async fn kick_session(
+    _req: HttpRequest,
+    ticket_service: Autowired<TicketService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<SessionKickReq>,
+) -> route::Result<TicketVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        ticket_service
+            .kick(&token, &req, ip)
+            .await?
+            .map(|t| t.into())
+    )
+}
+
+#[get("/sessions/account", wrap = "Permission::new(\"system:ticket:view\")")]
+async fn list_account_sessions(
+    ticket_service: Autowired<TicketService>,
+    token: RequestExtension<Token>,
+    req: RequestParam<AccountIdReq>,
+) -> route::Result<Vec<SessionVo>> {
+    Data!(
+        ticket_service
+            .sessions(req.account_id.as_str(), token.ticket_id.as_str())
+            .await?
+    )
+}
+
+#[post("/revoke", wrap = "Permission::new(\"system:ticket:revoke\")")]
+async fn revoke_ticket(
+    _req: HttpRequest,
+    ticket_service: Autowired<TicketService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<TicketRevokeReq>,
+) -> route::Result<TicketVo> {
+    let ip = laurel_actix::utils::ip(&_req);
+    Data!(
+        ticket_service
+            .revoke(&token, &req, ip)
+            .await?
+            .map(|t| t.into())
+    )
+}
+
+#[post("/revoke/account", wrap = "Permission::new(\"system:ticket:revoke\")")]
+async fn revoke_account_tickets(
+    _req: HttpRequest,
+    ticket_service: Autowired<TicketService>,
+    token: RequestExtension<Token>,
+    req: RequestBody<AccountTicketRevokeReq>,
+) -> route::Result<Vec<TicketVo>> {
+    let ip = laurel_actix::utils::ip(&_req);
+    let tickets: Vec<TicketVo> = ticket_service
+        .revoke_account(&token, &req, ip)
+        .await?
+        .into_iter()
+        .map(|t| t.into())
+        .collect();
+    Data!(tickets)
+}
@@ -135,13 +135,33 @@ diesel::table! {
         account_id -> Varchar,
         #[max_length = 64]
         salt -> Varchar,
-        #[max_length = 64]
+        #[max_length = 255]
         password -> Varchar,
+        #[max_length = 20]
+        hash_version -> Varchar,
+        pts -> Timestamp,
         cts -> Timestamp,
         uts -> Timestamp,
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    passport_history (id) {
+        id -> Int8,
+        #[max_length = 40]
+        account_id -> Varchar,
+        #[max_length = 64]
+        salt -> Varchar,
+        #[max_length = 255]
+        password -> Varchar,
+        #[max_length = 20]
+        hash_version -> Varchar,
+        cts -> Timestamp,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
 
@@ -166,15 +186,167 @@ diesel::table! {
         ticket_id -> Varchar,
         token -> Text,
         #[max_length = 40]
+        refresh_id -> Varchar,
+        #[max_length = 40]
         account_id -> Varchar,
         #[max_length = 20]
         login_type -> Varchar,
         #[max_length = 20]
         ticket_state -> Varchar,
+        #[max_length = 40]
+        impersonator -> Varchar,
+        #[max_length = 20]
+        device_class -> Varchar,
+        #[max_length = 40]
+        client_id -> Varchar,
+        #[max_length = 200]
+        scope -> Varchar,
         cts -> Timestamp,
         uts -> Timestamp,
         ets -> Timestamp,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(account, passport, profile, fe_micro_service, menu,role,dict,dict_value,ticket);
+diesel::table! {
+    use diesel::sql_types::*;
+
+    account_mfa (id) {
+        id -> Int8,
+        #[max_length = 40]
+        account_id -> Varchar,
+        #[max_length = 20]
+        mfa_type -> Varchar,
+        #[max_length = 64]
+        secret -> Varchar,
+        #[max_length = 20]
+        mfa_state -> Varchar,
+        recovery_codes -> Text,
+        last_step -> Int8,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    role_account (id) {
+        id -> Int8,
+        #[max_length = 40]
+        role_id -> Varchar,
+        #[max_length = 40]
+        account_id -> Varchar,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    permission (id) {
+        id -> Int8,
+        #[max_length = 40]
+        role_id -> Varchar,
+        #[max_length = 40]
+        menu_id -> Varchar,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    trusted_device (id) {
+        id -> Int8,
+        #[max_length = 40]
+        account_id -> Varchar,
+        #[max_length = 64]
+        fingerprint -> Varchar,
+        #[max_length = 128]
+        device_id -> Varchar,
+        #[max_length = 128]
+        browser -> Nullable<Varchar>,
+        #[max_length = 128]
+        os -> Nullable<Varchar>,
+        #[max_length = 128]
+        device -> Nullable<Varchar>,
+        #[max_length = 128]
+        host -> Nullable<Varchar>,
+        #[max_length = 128]
+        endpoint -> Nullable<Varchar>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        #[max_length = 40]
+        last_ticket_id -> Varchar,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    oauth_client (id) {
+        id -> Int8,
+        #[max_length = 40]
+        client_id -> Varchar,
+        #[max_length = 64]
+        client_secret -> Varchar,
+        #[max_length = 64]
+        client_name -> Varchar,
+        redirect_uris -> Text,
+        #[max_length = 200]
+        scopes -> Varchar,
+        #[max_length = 20]
+        client_state -> Varchar,
+        #[max_length = 40]
+        creator -> Varchar,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    federated_identity (id) {
+        id -> Int8,
+        #[max_length = 20]
+        provider -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        #[max_length = 40]
+        account_id -> Varchar,
+        #[max_length = 128]
+        email -> Nullable<Varchar>,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    api_key (id) {
+        id -> Int8,
+        #[max_length = 40]
+        key_id -> Varchar,
+        #[max_length = 40]
+        account_id -> Varchar,
+        #[max_length = 64]
+        key_name -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        scopes -> Nullable<Text>,
+        #[max_length = 20]
+        key_state -> Varchar,
+        ets -> Nullable<Timestamp>,
+        lts -> Nullable<Timestamp>,
+        cts -> Timestamp,
+        uts -> Timestamp,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(account, passport, profile, fe_micro_service, menu,role,dict,dict_value,ticket,account_mfa,role_account,permission,passport_history,trusted_device,oauth_client,federated_identity,api_key);
@@ -1,22 +1,40 @@
-use crate::model::account::{AccountEntity, AccountLoginVo};
+use crate::model::account::{AccountCreateReq, AccountEntity, AccountLoginVo, AccountQueryReq, AccountState, AccountStateReq, InsertableAccount, LoginCodeReq, LoginCodeSendReq, LoginStep, PasswordChangeReq, PasswordRenewReq, PasswordResetReq};
+use crate::model::device::LoginClient;
+use crate::model::mfa::{LoginMfaReq, MfaChallenge};
+use crate::service::device::DeviceService;
+use crate::service::mfa::MfaService;
 use crate::repository::account::AccountRepository;
+use crate::model::passport::{InsertablePassport, PassportEntity, PolicyViolation};
+use crate::service::audit::AuditService;
+use crate::service::login_code::LoginCodeService;
+use crate::service::login_guard::LoginGuard;
+use crate::service::session_limit::SessionLimiter;
+use laurel_actix::error::BizError;
+use laurel_actix::handler::Token;
 use crate::repository::passport::PassportRepository;
-use crate::utils::{passport_utils};
+use crate::utils::{codes, passport_utils, token_utils};
+use crate::utils::password_policy::PasswordPolicy;
 use anyhow::{Error};
+use bon::Builder;
 use laurel_actix::types::{service};
 use laurel_redis::Redis;
 use std::sync::Arc;
+use std::time::Duration;
 use chrono::{Local, NaiveDateTime};
-use tracing::error;
+use tracing::{error, warn};
 use laurel_common::date_time::DTF;
-use laurel_common::types::api;
+use laurel_common::types::{api, HappyEnum, Pagination};
 use laurel_id_api::id::IdApi;
 use laurel_logs_api::logs::{LogApi, LoginLogCreateReqBo};
-use laurel_tool_api::ua::{UaApi};
-use crate::model::ticket::{InsertableTicket, Ticket};
+use laurel_tool_api::ua::{Ua, UaApi};
+use crate::model::ticket::{InsertableTicket, IssuedTicket};
 use crate::repository;
 
-#[derive(Debug)]
+static RENEW_PREFIX: &str = "laurel:system:login:password:renew:";
+/// 修改过期密码票据有效期
+static RENEW_EXPIRE: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Builder)]
 pub struct AccountService {
     account_repository: Arc<AccountRepository>,
     passport_repository: Arc<PassportRepository>,
@@ -27,78 +45,231 @@ pub struct AccountService {
     id_api: IdApi,
     ip_api: laurel_tool_api::ip::IpApi,
     ua_api: UaApi,
+    password_params: argon2::Params,
+    password_policy: PasswordPolicy,
+    audit_service: Arc<AuditService>,
+    login_guard: Arc<LoginGuard>,
+    mfa_service: Arc<MfaService>,
+    login_code_service: Arc<LoginCodeService>,
+    device_service: Arc<DeviceService>,
+    session_limiter: Arc<SessionLimiter>,
 }
 
 impl AccountService {
-    pub fn new(
-        account_repository: Arc<AccountRepository>,
-        passport_repository: Arc<PassportRepository>,
-        redis: Redis,
-        log_api: Arc<LogApi>,
-        ticket_repository: Arc<repository::ticket::Repository>,
-        token_service: Arc<crate::service::token::TokenService>,
-        id_api: IdApi,
-        ip_api: laurel_tool_api::ip::IpApi,
-        ua_api: UaApi,
-    ) -> Self {
-        Self {
-            account_repository,
-            passport_repository,
-            redis,
-            log_api,
-            ticket_repository,
-            token_service,
-            id_api,
-            ip_api,
-            ua_api
-        }
-    }
-
-    async fn do_login(&self, req: &AccountLoginVo) -> service::Result<(AccountEntity, Ticket)>{
+    /// 登录标识: name 原样使用, email / phone 需规范化
+    fn identifier(account_type: &str, account: &str) -> service::Result<String> {
+        match account_type {
+            "name" => Ok(account.to_string()),
+            _ => LoginCodeService::normalize(account_type, account),
+        }
+    }
+
+    /// 账户不存在与密码错误返回相同提示, 避免探测账户
+    fn login_failed() -> Error {
+        Error::msg("account or password error")
+    }
+
+    async fn do_login(&self, req: &AccountLoginVo) -> service::Result<(AccountEntity, LoginStep)>{
+        let account_type = req.account_type.as_deref().unwrap_or("name");
         let account = self
             .account_repository
-            .find_by_name(req.account.as_str(), "name")
+            .find_by_name(Self::identifier(account_type, req.account.as_str())?.as_str(), account_type)
+            .await?;
+        let passport = match &account {
+            Some(account) => self.passport_repository.find(account.account_id.as_str()).await?,
+            None => None,
+        };
+        let (account, passport) = match (account, passport) {
+            (Some(account), Some(passport)) => (account, passport),
+            _ => {
+                // 同样计算一次摘要, 使耗时与密码错误一致
+                let _ = self.hash_password(req.password.as_str(), passport_utils::salt().as_str()).await;
+                return Err(Self::login_failed());
+            },
+        };
+        let verified = self.verify_password(
+            account.account_id.as_str(),
+            req.password.as_str(),
+            passport.salt.as_str(),
+            passport.password.as_str(),
+            passport.hash_version.as_str(),
+        ).await?;
+        if !verified{
+            return Err(Self::login_failed());
+        }
+        Self::ensure_active(&account)?;
+        if passport.hash_version != passport_utils::HASH_VERSION_ARGON2ID {
+            self.rehash(&passport, req.password.as_str()).await;
+        }
+        self.complete(account, account_type, &req.client).await
+    }
+
+    /// 验证码登录: 校验成功即作废
+    async fn do_login_code(&self, req: &LoginCodeReq) -> service::Result<(AccountEntity, LoginStep)>{
+        let account_type = req.account_type.as_str();
+        let target = LoginCodeService::normalize(account_type, req.account.as_str())?;
+        if !self.login_code_service.verify(account_type, target.as_str(), req.code.as_str()).await? {
+            return Err(Error::msg("login code error"));
+        }
+        let account = self
+            .account_repository
+            .find_by_name(target.as_str(), account_type)
             .await?
-            .expect("account not found");
-        let passport = self
+            .ok_or_else(|| Error::msg("account not found"))?;
+        Self::ensure_active(&account)?;
+        self.complete(account, account_type, &req.client).await
+    }
+
+    /// 第一因子通过后: 启用二次验证的账户返回挑战, 否则签发票据
+    async fn complete(&self, account: AccountEntity, login_type: &str, client: &LoginClient) -> service::Result<(AccountEntity, LoginStep)>{
+        if self.mfa_service.find_enabled(account.account_id.as_str()).await?.is_some() {
+            let challenge = self
+                .mfa_service
+                .challenge(&MfaChallenge::new(account.account_id.as_str(), login_type, false))
+                .await?;
+            return Ok((account, LoginStep::Challenge(challenge)));
+        }
+        self.finish(account, login_type, client).await
+    }
+
+    /// 全部验证通过: 密码过期时先要求修改, 否则签发票据
+    async fn finish(&self, account: AccountEntity, login_type: &str, client: &LoginClient) -> service::Result<(AccountEntity, LoginStep)>{
+        let expired = self
             .passport_repository
             .find(account.account_id.as_str())
             .await?
-            .expect("account passport not init");
-        let p = passport_utils::password(
-            account.account_id.as_str(),
-            req.password.as_str(),
-            passport.salt.as_str(),
-        )?;
-        if p != passport.password{
-            return Err(Error::msg("account passport error"));
+            .map(|p| self.password_policy.expired(p.pts, Local::now().naive_local()))
+            .unwrap_or(false);
+        if expired {
+            let ticket = token_utils::token();
+            self.redis
+                .set_with_expire(
+                    format!("{}{}", RENEW_PREFIX, ticket).as_str(),
+                    format!("{}:{}", login_type, account.account_id),
+                    RENEW_EXPIRE,
+                )
+                .await?;
+            return Ok((account, LoginStep::PasswordExpired(ticket)));
         }
+        let issued = self.issue(&account, login_type, client).await?;
+        Ok((account, LoginStep::Issued(issued)))
+    }
+
+    /// 二次验证: 挑战票据 + 动态码/恢复码, 通过后按第一因子的登录方式签发
+    async fn do_login_mfa(&self, account: AccountEntity, challenge: &MfaChallenge, req: &LoginMfaReq) -> service::Result<(AccountEntity, LoginStep)>{
+        let mfa = self
+            .mfa_service
+            .find_enabled(account.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("totp not enabled"))?;
+        if !self.mfa_service.verify(&mfa, req.code.as_str()).await? {
+            return Err(Error::msg("totp code error"));
+        }
+        self.mfa_service.clear_challenge(req.challenge.as_str()).await?;
+        Self::ensure_active(&account)?;
+        let login_type = challenge.login_type.as_str();
+        if !challenge.check_password_age() {
+            let issued = self.issue(&account, login_type, &req.client).await?;
+            return Ok((account, LoginStep::Issued(issued)));
+        }
+        self.finish(account, login_type, &req.client).await
+    }
+
+    /// 非正常状态的账户拒绝登录
+    fn ensure_active(account: &AccountEntity) -> service::Result<()> {
+        match AccountState::reject_code(account.account_state.as_str()) {
+            Some((code, message)) => Err(BizError::new(code, message).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// 签发票据, 超出在线会话数上限时按策略拒绝或下线旧会话
+    async fn issue(&self, account: &AccountEntity, login_type: &str, client: &LoginClient) -> service::Result<IssuedTicket>{
+        let lock = self.session_limiter.lock(account.account_id.as_str()).await?;
+        let issued = self.issue_limited(account, login_type, client).await;
+        self.session_limiter.unlock(account.account_id.as_str(), lock).await;
+        issued
+    }
+
+    async fn issue_limited(&self, account: &AccountEntity, login_type: &str, client: &LoginClient) -> service::Result<IssuedTicket>{
+        let device_class = client.device_class();
+        let overflow = self
+            .session_limiter
+            .admit(account.account_id.as_str(), login_type, device_class.as_str())
+            .await?;
         let ticket_id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let ets = self.token_service.expire_at(now);
+        let refresh_id = token_utils::token();
         let token = self.token_service.make(ticket_id.as_str())?;
+        let refresh_token = self.token_service.make_refresh(ticket_id.as_str(), refresh_id.as_str(), ets)?;
         let insertable = InsertableTicket{
             ticket_id: ticket_id.as_str(),
             token: token.as_str(),
+            refresh_id: refresh_id.as_str(),
             account_id: account.account_id.as_str(),
-            login_type: "name",
+            login_type,
             ticket_state: "normal",
-            cts: Local::now().naive_local(),
-            uts: Local::now().naive_local(),
-            ets: Local::now().naive_local(),
+            impersonator: "",
+            device_class: device_class.as_str(),
+            client_id: "",
+            scope: "",
+            cts: now,
+            uts: now,
+            ets,
         };
         let ticket = self.ticket_repository.save(&insertable).await?;
-        Ok((account, ticket))
+        if let Err(err) = self.token_service.cache(&ticket).await {
+            error!("cache ticket: {} error: {}", ticket.ticket_id, err);
+        }
+        self.session_limiter.replace(overflow, &ticket).await;
+        Ok(IssuedTicket {
+            ticket,
+            refresh_token,
+            expires_in: self.token_service.access_expire(),
+        })
+    }
+
+    /// argon2id 计算耗时, 放到阻塞线程池执行
+    async fn hash_password(&self, password: &str, salt: &str) -> service::Result<String> {
+        let (password, salt, params) = (password.to_string(), salt.to_string(), self.password_params.clone());
+        tokio::task::spawn_blocking(move || passport_utils::argon2id(password.as_str(), salt.as_str(), &params)).await?
     }
 
-    async fn process_ua(&self, req: &mut LoginLogCreateReqBo, ua: &str) {
-        if let Some((browser, os, device)) = self.ua_api.parse(ua)
+    async fn verify_password(&self, account_id: &str, password: &str, salt: &str, hash: &str, hash_version: &str) -> service::Result<bool> {
+        let args = [account_id, password, salt, hash, hash_version].map(|s| s.to_string());
+        tokio::task::spawn_blocking(move || {
+            let [account_id, password, salt, hash, hash_version] = &args;
+            passport_utils::verify(account_id, password, salt, hash, hash_version)
+        })
+        .await?
+    }
+
+    /// 旧版密码登录成功后升级为 argon2id, 失败不影响登录
+    async fn rehash(&self, passport: &PassportEntity, password: &str) {
+        let result = match self.hash_password(password, passport.salt.as_str()).await {
+            Ok(hash) => self
+                .passport_repository
+                .update_password(passport.account_id.as_str(), hash.as_str(), passport_utils::HASH_VERSION_ARGON2ID)
+                .await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            error!("rehash account: {} passport error: {}", passport.account_id, err);
+        }
+    }
+
+    async fn process_ua(&self, req: &mut LoginLogCreateReqBo, ua: &str) -> Option<Ua> {
+        let parsed = self.ua_api.parse(ua)
             .await
             .unwrap_or_else(api::ApiResult::from)
-            .data
-            .map(|data| data.show()){
+            .data;
+        if let Some((browser, os, device)) = parsed.as_ref().map(|data| data.show()){
             req.browser = browser;
             req.os = os;
             req.device = device;
         }
+        parsed
     }
 
     async fn process_ip(&self, req: &mut LoginLogCreateReqBo, ip: &str){
@@ -107,21 +278,52 @@ impl AccountService {
         req.location = Some(location);
     }
 
-    async fn after_login(&self, req: &AccountLoginVo, ua: Option<&str>, ip: String, result: &service::Result<(AccountEntity, Ticket)>){
+    async fn after_login(&self, account: &str, login_type: &str, ua: Option<&str>, client: &LoginClient, ip: String, result: &service::Result<(AccountEntity, LoginStep)>){
         let mut log_req = LoginLogCreateReqBo::default();
-        log_req.account = req.account.clone();
-        log_req.login_type = "name".to_string();
+        log_req.account = account.to_string();
+        log_req.login_type = login_type.to_string();
         log_req.ip = Some(ip.clone());
-        if let Some(ua) = ua{
-            self.process_ua(& mut log_req, ua).await;
-        }
+        let parsed_ua = match ua {
+            Some(ua) => self.process_ua(& mut log_req, ua).await,
+            None => None,
+        };
         self.process_ip(& mut log_req, ip.as_str()).await;
         match &result{
-            Ok((_account, token)) => {
+            Ok((account, LoginStep::Issued(issued))) => {
                 log_req.login_state = "normal".to_string();
                 log_req.login_result = Some("登录成功".to_string());
-                log_req.login_cts = token.cts.format(DTF).to_string();
-                log_req.ticket_id = token.ticket_id.clone();
+                log_req.login_cts = issued.ticket.cts.format(DTF).to_string();
+                log_req.ticket_id = issued.ticket.ticket_id.clone();
+                log_req.new_device = match self
+                    .device_service
+                    .register(account.account_id.as_str(), issued.ticket.ticket_id.as_str(), client, parsed_ua.as_ref(), ip.as_str())
+                    .await {
+                    Ok(new_device) => new_device,
+                    Err(err) => {
+                        error!("register device of account: {} error: {}", account.account_id, err);
+                        false
+                    }
+                };
+            },
+            Ok((_account, LoginStep::Challenge(challenge))) => {
+                log_req.login_state = "challenge".to_string();
+                log_req.login_result = Some("密码校验通过, 待二次验证".to_string());
+                log_req.login_cts = Local::now().naive_local().format(DTF).to_string();
+                log_req.ticket_id = challenge.clone();
+            },
+            Ok((_account, LoginStep::PasswordExpired(ticket))) => {
+                log_req.login_state = "password_expired".to_string();
+                log_req.login_result = Some("密码已过期, 待修改密码".to_string());
+                log_req.login_cts = Local::now().naive_local().format(DTF).to_string();
+                log_req.ticket_id = ticket.clone();
+            },
+            Err(err) if Self::is_locked(err) => {
+                log_req.login_state = "locked".to_string();
+                log_req.login_result = Some(format!("登录锁定: {}", err));
+                log_req.login_cts = Local::now().naive_local().format(DTF).to_string();
+                if let Ok(id) = self.id_api.id().await{
+                    log_req.ticket_id = id;
+                }
             },
             Err(err) => {
                 log_req.login_state = "error".to_string();
@@ -141,12 +343,420 @@ impl AccountService {
         });
     }
 
-    pub async fn login(&self, req: &AccountLoginVo, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, Ticket)> {
-        let result = self.do_login(req).await;
-        self.after_login(req, ua, ip, &result).await;
+    fn is_locked(err: &Error) -> bool {
+        err.downcast_ref::<BizError>()
+            .map(|e| e.code == codes::ACCOUNT_LOCKED || e.code == codes::IP_LOCKED)
+            .unwrap_or(false)
+    }
+
+    /// 登录尝试计数, 锁定期内或超过阈值直接拒绝
+    async fn guarded<F>(&self, account: &str, ip: &str, login: F) -> service::Result<(AccountEntity, LoginStep)>
+    where F: Future<Output = service::Result<(AccountEntity, LoginStep)>>
+    {
+        let attempt = self.login_guard.attempt(account, ip).await?;
+        let result = login.await;
+        let guard = match &result {
+            // 二次验证或修改过期密码前不清空失败记录
+            Ok((_, LoginStep::Challenge(_) | LoginStep::PasswordExpired(_))) => {
+                self.login_guard.verified(account, ip, attempt.as_str()).await
+            },
+            Ok(_) => self.login_guard.success(account, ip, attempt.as_str()).await,
+            // 失败的尝试已在登录前计入
+            Err(_) => Ok(()),
+        };
+        if let Err(err) = guard {
+            error!("login guard account: {} ip: {} error: {}", account, ip, err);
+        }
+        result
+    }
+
+    pub async fn login(&self, req: &AccountLoginVo, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let account_type = req.account_type.as_deref().unwrap_or("name");
+        let result = self
+            .guarded(req.account.as_str(), ip.as_str(), self.do_login(req))
+            .await;
+        self.after_login(req.account.as_str(), account_type, ua, &req.client, ip, &result).await;
+        result
+    }
+
+    /// 发送登录验证码, 未注册的标识同样返回成功以免暴露账户是否存在
+    pub async fn send_login_code(&self, req: &LoginCodeSendReq, ip: String) -> service::Result<()> {
+        let account_type = req.account_type.as_str();
+        let target = LoginCodeService::normalize(account_type, req.account.as_str())?;
+        self.login_code_service.limit(account_type, target.as_str(), ip.as_str()).await?;
+        if self
+            .account_repository
+            .find_by_name(target.as_str(), account_type)
+            .await?
+            .is_none() {
+            warn!("login code requested for unknown {}: {} from ip: {}", account_type, target, ip);
+            return Ok(());
+        }
+        self.login_code_service.send(account_type, target.as_str()).await
+    }
+
+    pub async fn login_code(&self, req: &LoginCodeReq, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let result = self
+            .guarded(req.account.as_str(), ip.as_str(), self.do_login_code(req))
+            .await;
+        self.after_login(req.account.as_str(), req.account_type.as_str(), ua, &req.client, ip, &result).await;
+        result
+    }
+
+    pub async fn login_mfa(&self, req: &LoginMfaReq, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let challenge = self
+            .mfa_service
+            .find_challenge(req.challenge.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("challenge expired"))?;
+        let account = self
+            .account_repository
+            .find_by_account_id(challenge.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let account_name = account.account_name.clone();
+        let result = self
+            .guarded(account_name.as_str(), ip.as_str(), self.do_login_mfa(account, &challenge, req))
+            .await;
+        self.after_login(account_name.as_str(), challenge.login_type.as_str(), ua, &req.client, ip, &result).await;
+        result
+    }
+
+    async fn complete_trusted(&self, account: AccountEntity, login_type: &str, mfa: bool, client: &LoginClient) -> service::Result<(AccountEntity, LoginStep)> {
+        Self::ensure_active(&account)?;
+        if mfa && self.mfa_service.find_enabled(account.account_id.as_str()).await?.is_some() {
+            let challenge = self
+                .mfa_service
+                .challenge(&MfaChallenge::new(account.account_id.as_str(), login_type, true))
+                .await?;
+            return Ok((account, LoginStep::Challenge(challenge)));
+        }
+        let issued = self.issue(&account, login_type, client).await?;
+        Ok((account, LoginStep::Issued(issued)))
+    }
+
+    /// 由外部身份源或已登录的会话完成认证的登录, 不校验本地密码有效期;
+    /// mfa 为 true 时已启用二次验证的账户仍需完成 TOTP
+    #[allow(clippy::too_many_arguments)]
+    pub async fn login_trusted<F>(&self, login_type: &str, fallback_name: &str, mfa: bool, client: &LoginClient, ua: Option<&str>, ip: String, resolve: F) -> service::Result<(AccountEntity, LoginStep)>
+    where F: Future<Output = service::Result<AccountEntity>>
+    {
+        let result = match resolve.await {
+            Ok(account) => self.complete_trusted(account, login_type, mfa, client).await,
+            Err(err) => Err(err),
+        };
+        // 未能识别账户时以调用方给出的名称记录
+        let account_name = match &result {
+            Ok((account, _)) => account.account_name.clone(),
+            Err(_) => fallback_name.to_string(),
+        };
+        self.after_login(account_name.as_str(), login_type, ua, client, ip, &result).await;
+        result
+    }
+
+    /// 为外部身份创建账户, 本地密码随机生成且不告知, 只能经身份源登录
+    pub async fn provision(&self, account_type: &str, account_name: &str, provider: &str, ip: &str) -> service::Result<AccountEntity> {
+        let salt = passport_utils::salt();
+        let hash = self.hash_password(token_utils::token().as_str(), salt.as_str()).await?;
+        let account_id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let account = self
+            .account_repository
+            .save(
+                &InsertableAccount {
+                    account_id: account_id.as_str(),
+                    account_name,
+                    account_state: "active",
+                    account_type,
+                    cts: now,
+                    uts: now,
+                },
+                &InsertablePassport {
+                    account_id: account_id.as_str(),
+                    salt: salt.as_str(),
+                    password: hash.as_str(),
+                    hash_version: passport_utils::HASH_VERSION_ARGON2ID,
+                    pts: now,
+                    cts: now,
+                    uts: now,
+                },
+            )
+            .await?;
+        if let Err(err) = self.token_service.mark_account_state(account.account_id.as_str(), "active").await {
+            error!("mark account: {} state error: {}", account.account_id, err);
+        }
+        self.audit_service.record(
+            account.account_id.as_str(),
+            "account_provision",
+            account.account_id.as_str(),
+            Some(format!("provider: {}, {}: {}", provider, account.account_type, account.account_name)),
+            Some(ip.to_string()),
+        );
+        Ok(account)
+    }
+
+    /// 新密码: 校验密码策略与历史密码后, 随机盐 + argon2id
+    async fn new_passport(&self, account_id: Option<&str>, account_name: &str, password: &str) -> service::Result<(String, String)> {
+        if password.is_empty() {
+            return Err(Error::msg("password is empty"));
+        }
+        let mut violations = self.password_policy.check(password, account_name);
+        if let Some(account_id) = account_id
+            && violations.is_empty()
+            && self.password_policy.history_size() > 0 {
+            let histories = self
+                .passport_repository
+                .list_history(account_id, self.password_policy.history_size())
+                .await?;
+            for history in histories {
+                if self.verify_password(
+                    account_id,
+                    password,
+                    history.salt.as_str(),
+                    history.password.as_str(),
+                    history.hash_version.as_str(),
+                ).await? {
+                    violations.push(PolicyViolation::new(
+                        "reuse",
+                        format!("不能使用最近{}次用过的密码", self.password_policy.history_size()),
+                    ));
+                    break;
+                }
+            }
+        }
+        if !violations.is_empty() {
+            return Err(BizError::new(codes::PASSWORD_POLICY, "密码不符合安全策略")
+                .with_data(serde_json::to_value(&violations)?)
+                .into());
+        }
+        let salt = passport_utils::salt();
+        let hash = self.hash_password(password, salt.as_str()).await?;
+        Ok((salt, hash))
+    }
+
+    pub async fn create(&self, operator: &Token, req: &AccountCreateReq, ip: String) -> service::Result<AccountEntity> {
+        let account_type = req.account_type.as_deref().unwrap_or("name");
+        if req.account_name.is_empty() {
+            return Err(Error::msg("account name is empty"));
+        }
+        if !["name", "email", "phone"].contains(&account_type) {
+            return Err(Error::msg(format!("account type: {} not support", account_type)));
+        }
+        let account_name = Self::identifier(account_type, req.account_name.as_str())?;
+        if self
+            .account_repository
+            .find_by_name(account_name.as_str(), account_type)
+            .await?
+            .is_some() {
+            return Err(Error::msg("account already exists"));
+        }
+        let account_state = req.account_state.as_deref().unwrap_or("active");
+        if account_state != "active" && account_state != "pending" {
+            return Err(Error::msg(format!("account state: {} not allowed on create", account_state)));
+        }
+        let (salt, hash) = self.new_passport(None, account_name.as_str(), req.password.as_str()).await?;
+        let account_id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let account = self
+            .account_repository
+            .save(
+                &InsertableAccount {
+                    account_id: account_id.as_str(),
+                    account_name: account_name.as_str(),
+                    account_state,
+                    account_type,
+                    cts: now,
+                    uts: now,
+                },
+                &InsertablePassport {
+                    account_id: account_id.as_str(),
+                    salt: salt.as_str(),
+                    password: hash.as_str(),
+                    hash_version: passport_utils::HASH_VERSION_ARGON2ID,
+                    pts: now,
+                    cts: now,
+                    uts: now,
+                },
+            )
+            .await?;
+        if let Err(err) = self.token_service.mark_account_state(account.account_id.as_str(), account_state).await {
+            error!("mark account: {} state error: {}", account.account_id, err);
+        }
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "account_create",
+            account.account_id.as_str(),
+            Some(format!("{}: {}", account.account_type, account.account_name)),
+            Some(ip),
+        );
+        Ok(account)
+    }
+
+    /// 修改本人密码, 需校验旧密码
+    pub async fn change_password(&self, token: &Token, req: &PasswordChangeReq, ip: String) -> service::Result<()> {
+        if !token.is_session() {
+            return Err(Error::msg("password can only be changed by a login session"));
+        }
+        let passport = self
+            .passport_repository
+            .find(token.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account passport not init"))?;
+        let verified = self.verify_password(
+            token.account_id.as_str(),
+            req.old_password.as_str(),
+            passport.salt.as_str(),
+            passport.password.as_str(),
+            passport.hash_version.as_str(),
+        ).await?;
+        if !verified {
+            return Err(Error::msg("old password error"));
+        }
+        let account = self
+            .account_repository
+            .find_by_account_id(token.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let (salt, hash) = self
+            .new_passport(Some(account.account_id.as_str()), account.account_name.as_str(), req.new_password.as_str())
+            .await?;
+        self.account_repository
+            .update_passport(
+                token.account_id.as_str(),
+                salt.as_str(),
+                hash.as_str(),
+                passport_utils::HASH_VERSION_ARGON2ID,
+            )
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "password_change",
+            token.account_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(())
+    }
+
+    /// 凭登录时返回的票据修改已过期的密码, 成功后完成登录
+    pub async fn renew_password(&self, req: &PasswordRenewReq, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let key = format!("{}{}", RENEW_PREFIX, req.ticket);
+        let (login_type, account_id) = self
+            .redis
+            .get_optional::<String>(key.as_str())
+            .await?
+            .and_then(|v| v.split_once(':').map(|(t, a)| (t.to_string(), a.to_string())))
+            .ok_or_else(|| Error::msg("renew ticket expired"))?;
+        let account = self
+            .account_repository
+            .find_by_account_id(account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        Self::ensure_active(&account)?;
+        let (salt, hash) = self
+            .new_passport(Some(account_id.as_str()), account.account_name.as_str(), req.new_password.as_str())
+            .await?;
+        self.account_repository
+            .update_passport(account_id.as_str(), salt.as_str(), hash.as_str(), passport_utils::HASH_VERSION_ARGON2ID)
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        self.redis.del(key.as_str()).await?;
+        self.audit_service.record(
+            account_id.as_str(),
+            "password_renew",
+            account_id.as_str(),
+            None,
+            Some(ip.clone()),
+        );
+        let account_name = account.account_name.clone();
+        let result = self.finish(account, login_type.as_str(), &req.client).await;
+        self.after_login(account_name.as_str(), login_type.as_str(), ua, &req.client, ip, &result).await;
         result
     }
 
+    /// 管理员重置密码, 同时结束该账户的全部会话
+    pub async fn reset_password(&self, operator: &Token, req: &PasswordResetReq, ip: String) -> service::Result<AccountEntity> {
+        let account = self
+            .account_repository
+            .find_by_account_id(req.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let (salt, hash) = self
+            .new_passport(Some(account.account_id.as_str()), account.account_name.as_str(), req.new_password.as_str())
+            .await?;
+        let account = self
+            .account_repository
+            .update_passport(
+                req.account_id.as_str(),
+                salt.as_str(),
+                hash.as_str(),
+                passport_utils::HASH_VERSION_ARGON2ID,
+            )
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let tickets = self
+            .token_service
+            .revoke_account(account.account_id.as_str(), "revoked")
+            .await?;
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "password_reset",
+            account.account_id.as_str(),
+            Some(format!("revoked: {}", tickets.len())),
+            Some(ip),
+        );
+        Ok(account)
+    }
+
+    pub async fn page(&self, req: &AccountQueryReq, page: u32, size: u32) -> service::Result<Pagination<AccountEntity>> {
+        self.account_repository.page(req, page, size).await
+    }
+
+    /// 管理员变更账户状态, 停用或锁定时结束该账户的全部会话
+    pub async fn change_state(&self, operator: &Token, req: &AccountStateReq, ip: String) -> service::Result<AccountEntity> {
+        if !AccountState::valid(req.account_state.as_str()) {
+            return Err(Error::msg(format!("account state: {} not found", req.account_state)));
+        }
+        let account = self
+            .account_repository
+            .find_by_account_id(req.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let from = account.account_state.as_str();
+        if !AccountState::can_transit(from, req.account_state.as_str()) {
+            return Err(Error::msg(format!("account state: {} -> {} not allowed", from, req.account_state)));
+        }
+        let updated = self
+            .account_repository
+            .update_state(req.account_id.as_str(), from, req.account_state.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account state changed, please retry"))?;
+        self.token_service
+            .mark_account_state(updated.account_id.as_str(), updated.account_state.as_str())
+            .await?;
+        let mut detail = format!("{} -> {}", from, updated.account_state);
+        if AccountState::reject_code(updated.account_state.as_str()).is_some() {
+            let tickets = self
+                .token_service
+                .revoke_account(updated.account_id.as_str(), "revoked")
+                .await?;
+            let keys = self.token_service.revoke_api_keys(updated.account_id.as_str()).await?;
+            detail = format!("{}, revoked: {}, api keys: {}", detail, tickets.len(), keys);
+        }
+        if let Some(reason) = &req.reason && !reason.is_empty() {
+            detail = format!("{}, reason: {}", detail, reason);
+        }
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "account_state_change",
+            updated.account_id.as_str(),
+            Some(detail),
+            Some(ip),
+        );
+        Ok(updated)
+    }
+
     pub async fn find_account_by_id(&self, account_id: &str) -> service::Result<Option<AccountEntity>> {
         let account = self
             .account_repository
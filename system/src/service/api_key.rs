@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use anyhow::Error;
+use bon::Builder;
+use chrono::{Duration, Local};
+use laurel_actix::handler::Token;
+use laurel_actix::permission::{CachedPermissionHandler, PermissionHandler, SUPER_AUTHORITY};
+use laurel_actix::types::service;
+use crate::model::api_key::{ApiKey, ApiKeyCreateReq, ApiKeyRevokeReq, ApiKeyVo, InsertableApiKey};
+use crate::repository;
+use crate::service::audit::AuditService;
+use crate::utils::{oauth_utils, token_utils};
+
+/// 单账户同时有效的 key 数上限
+static MAX_ACTIVE_KEYS: i64 = 20;
+
+/// 账户 API key, 供 CI 等自动化脚本调用接口. 密钥只保存摘要, 明文仅创建时返回一次
+#[derive(Debug, Builder)]
+pub struct ApiKeyService {
+    api_key_repository: Arc<repository::api_key::Repository>,
+    permission_cache: Arc<CachedPermissionHandler>,
+    audit_service: Arc<AuditService>,
+}
+
+impl ApiKeyService {
+    /// 限定的权限码必须是账户本身拥有的
+    async fn check_scopes(&self, account_id: &str, scopes: &[String]) -> service::Result<()> {
+        if scopes.is_empty() {
+            return Err(Error::msg("scopes is empty"));
+        }
+        let permissions = self
+            .permission_cache
+            .permissions(account_id)
+            .await
+            .map_err(|err| Error::msg(err.to_string()))?;
+        if permissions.contains(SUPER_AUTHORITY) {
+            return Ok(());
+        }
+        match scopes.iter().find(|s| !permissions.contains(s.as_str())) {
+            Some(scope) => Err(Error::msg(format!("permission: {} not granted to account", scope))),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn create(&self, token: &Token, req: &ApiKeyCreateReq, ip: String) -> service::Result<ApiKeyVo> {
+        // 不能用 key 再创建 key, 也不能在代登录期间替他人创建
+        if !token.is_session() {
+            return Err(Error::msg("api key can only be created by a login session"));
+        }
+        let key_name = req.key_name.trim();
+        if key_name.is_empty() || key_name.chars().count() > 64 {
+            return Err(Error::msg("key name must be 1 to 64 characters"));
+        }
+        if let Some(scopes) = &req.scopes {
+            self.check_scopes(token.account_id.as_str(), scopes).await?;
+        }
+        if req.expire_days.is_some_and(|days| days <= 0) {
+            return Err(Error::msg("expire days must be positive"));
+        }
+        if self.api_key_repository.count_active(token.account_id.as_str()).await? >= MAX_ACTIVE_KEYS {
+            return Err(Error::msg(format!("at most {} active api keys per account", MAX_ACTIVE_KEYS)));
+        }
+
+        let key_id = token_utils::token();
+        let secret = format!("{}{}", token_utils::token(), token_utils::token());
+        let now = Local::now().naive_local();
+        let scopes = req.scopes.as_ref().map(|s| s.join(" "));
+        let key = self
+            .api_key_repository
+            .save(&InsertableApiKey {
+                key_id: key_id.as_str(),
+                account_id: token.account_id.as_str(),
+                key_name,
+                key_hash: oauth_utils::secret_hash(secret.as_str()).as_str(),
+                scopes: scopes.as_deref(),
+                key_state: "normal",
+                ets: req.expire_days.map(|days| now + Duration::days(days)),
+                cts: now,
+                uts: now,
+            })
+            .await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "api_key_create",
+            key.key_id.as_str(),
+            Some(format!("name: {}, scopes: {}", key.key_name, scopes.as_deref().unwrap_or("*"))),
+            Some(ip),
+        );
+        let mut vo = ApiKeyVo::from(key);
+        vo.key = Some(token_utils::api_key(key_id.as_str(), secret.as_str()));
+        Ok(vo)
+    }
+
+    pub async fn list(&self, token: &Token) -> service::Result<Vec<ApiKey>> {
+        self.api_key_repository.list(token.account_id.as_str()).await
+    }
+
+    /// 吊销后立即失效, key 校验不经过缓存
+    pub async fn revoke(&self, token: &Token, req: &ApiKeyRevokeReq, ip: String) -> service::Result<ApiKey> {
+        if !token.is_session() {
+            return Err(Error::msg("api key can only be revoked by a login session"));
+        }
+        let key = self
+            .api_key_repository
+            .revoke(token.account_id.as_str(), req.key_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("api key not found"))?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "api_key_revoke",
+            key.key_id.as_str(),
+            Some(format!("name: {}", key.key_name)),
+            Some(ip),
+        );
+        Ok(key)
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use chrono::Local;
+use tracing::error;
+use laurel_common::date_time::DTF;
+use laurel_common::types::api;
+use laurel_logs_api::logs::{AuditLogCreateReqBo, LogApi};
+
+/// 审计详情中最多列出的 id 数, audit_log.detail 为 VARCHAR(400)
+static AUDIT_DETAIL_IDS: usize = 8;
+
+#[derive(Debug)]
+pub struct AuditService {
+    log_api: Arc<LogApi>,
+}
+
+impl AuditService {
+    pub fn new(log_api: Arc<LogApi>) -> Self {
+        Self { log_api }
+    }
+
+    /// 批量操作的 id 摘要: 总数与前若干个 id, 避免超出审计详情长度
+    pub fn summarize_ids(ids: &[String]) -> String {
+        let shown = ids.iter().take(AUDIT_DETAIL_IDS).map(|id| id.as_str()).collect::<Vec<_>>().join(",");
+        if ids.len() > AUDIT_DETAIL_IDS {
+            format!("count: {}, ids: {},...", ids.len(), shown)
+        } else {
+            format!("count: {}, ids: {}", ids.len(), shown)
+        }
+    }
+
+    /// 异步写入审计日志, 失败仅记录错误
+    pub fn record(&self, operator: &str, action: &str, target: &str, detail: Option<String>, ip: Option<String>) {
+        let req = AuditLogCreateReqBo {
+            operator: operator.to_string(),
+            action: action.to_string(),
+            target: target.to_string(),
+            detail,
+            ip,
+            audit_cts: Local::now().naive_local().format(DTF).to_string(),
+        };
+        let api = Arc::clone(&self.log_api);
+        tokio::task::spawn(async move {
+            let result: api::Result<i64> = api.save_audit_log(&req).await;
+            if !result.as_ref().is_ok_and(|r| r.is_successful()) {
+                error!("save audit log [{:?}] error: {:?}", &req, result)
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_ids() {
+        let ids: Vec<String> = (0..3).map(|i| i.to_string()).collect();
+        assert_eq!(AuditService::summarize_ids(&ids), "count: 3, ids: 0,1,2");
+        let ids: Vec<String> = (0..1000).map(|_| "x".repeat(40)).collect();
+        let detail = AuditService::summarize_ids(&ids);
+        assert!(detail.starts_with("count: 1000, ids: ") && detail.ends_with(",..."));
+        assert!(detail.len() <= 400);
+    }
+}
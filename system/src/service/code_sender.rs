@@ -0,0 +1,60 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use chrono::Local;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+use laurel_common::date_time::DTF;
+use crate::LoginCodeConfig;
+
+/// 一次性验证码投递, 生产环境接入邮件或短信网关
+#[async_trait::async_trait]
+pub trait CodeSender: Debug + Send + Sync {
+    /// channel: email / phone
+    async fn send(&self, channel: &str, target: &str, code: &str) -> anyhow::Result<()>;
+}
+
+/// 输出到日志, 仅用于本地开发
+#[derive(Debug)]
+pub struct ConsoleCodeSender;
+
+#[async_trait::async_trait]
+impl CodeSender for ConsoleCodeSender {
+    async fn send(&self, channel: &str, target: &str, code: &str) -> anyhow::Result<()> {
+        info!("login code [{}] {}: {}", channel, target, code);
+        Ok(())
+    }
+}
+
+/// 追加写入文件, 便于本地联调与测试读取
+#[derive(Debug)]
+pub struct FileCodeSender {
+    path: String,
+}
+
+impl FileCodeSender {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait::async_trait]
+impl CodeSender for FileCodeSender {
+    async fn send(&self, channel: &str, target: &str, code: &str) -> anyhow::Result<()> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_str())
+            .await?;
+        let line = format!("{} {} {} {}\n", Local::now().format(DTF), channel, target, code);
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+}
+
+/// 按配置选择投递方式: console / file
+pub fn from_config(config: &LoginCodeConfig) -> Arc<dyn CodeSender> {
+    match (config.sender.as_str(), &config.file) {
+        ("file", Some(path)) => Arc::new(FileCodeSender::new(path.clone())),
+        _ => Arc::new(ConsoleCodeSender),
+    }
+}
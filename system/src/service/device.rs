@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use anyhow::Error;
+use bon::Builder;
+use chrono::Local;
+use laurel_actix::handler::Token;
+use laurel_actix::types::service;
+use laurel_tool_api::ua::Ua;
+use crate::model::device::{DeviceRevokeReq, InsertableTrustedDevice, LoginClient, TrustedDeviceVo};
+use crate::repository;
+use crate::service::audit::AuditService;
+use crate::service::token::TokenService;
+use crate::utils::device_utils;
+
+/// 信任设备: 登录成功后登记, 用户可查看与移除
+#[derive(Debug, Builder)]
+pub struct DeviceService {
+    device_repository: Arc<repository::device::Repository>,
+    token_service: Arc<TokenService>,
+    audit_service: Arc<AuditService>,
+}
+
+impl DeviceService {
+    /// 登记本次登录的设备, 返回是否为首次出现的设备
+    pub async fn register(
+        &self,
+        account_id: &str,
+        ticket_id: &str,
+        client: &LoginClient,
+        ua: Option<&Ua>,
+        ip: &str,
+    ) -> service::Result<bool> {
+        let device_id = client.device.as_deref().unwrap_or_default();
+        let fingerprint = device_utils::fingerprint(device_id, ua);
+        let new_device = self
+            .device_repository
+            .find(account_id, fingerprint.as_str())
+            .await?
+            .is_none();
+        let (browser, os, device) = ua.map(|u| u.show()).unwrap_or_default();
+        let now = Local::now().naive_local();
+        self.device_repository
+            .save(&InsertableTrustedDevice {
+                account_id,
+                fingerprint: fingerprint.as_str(),
+                device_id,
+                browser: browser.as_deref(),
+                os: os.as_deref(),
+                device: device.as_deref(),
+                host: client.host.as_deref(),
+                endpoint: client.endpoint.as_deref(),
+                ip: Some(ip),
+                last_ticket_id: ticket_id,
+                cts: now,
+                uts: now,
+            })
+            .await?;
+        Ok(new_device)
+    }
+
+    pub async fn list(&self, token: &Token) -> service::Result<Vec<TrustedDeviceVo>> {
+        Ok(self
+            .device_repository
+            .list(token.account_id.as_str())
+            .await?
+            .into_iter()
+            .map(|d| TrustedDeviceVo::of(d, token.ticket_id.as_str()))
+            .collect())
+    }
+
+    /// 移除信任设备并吊销该设备最近一次登录的票据, 再次登录时按新设备处理
+    pub async fn revoke(&self, token: &Token, req: &DeviceRevokeReq, ip: String) -> service::Result<()> {
+        if !token.is_session() {
+            return Err(Error::msg("trusted device can only be revoked by a login session"));
+        }
+        let device = self
+            .device_repository
+            .delete(token.account_id.as_str(), req.fingerprint.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("device not found"))?;
+        if !device.last_ticket_id.is_empty() {
+            self.token_service.revoke(device.last_ticket_id.as_str(), "revoked").await?;
+        }
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "device_revoke",
+            device.fingerprint.as_str(),
+            Some(format!(
+                "{} / {} / {}",
+                device.browser.unwrap_or_default(),
+                device.os.unwrap_or_default(),
+                device.device.unwrap_or_default(),
+            )),
+            Some(ip),
+        );
+        Ok(())
+    }
+}
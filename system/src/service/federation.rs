@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anyhow::Error;
+use bon::Builder;
+use chrono::Local;
+use jsonwebtoken::jwk::JwkSet;
+use tracing::{error, warn};
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::{FederatedProviderConfig, FederationConfig};
+use crate::model::account::{AccountEntity, LoginStep};
+use crate::model::federation::{FederatedClaims, FederatedProviderVo, FederationAuthorizeReq, FederationAuthorizeVo, FederationLoginReq, FederationState, InsertableFederatedIdentity, ProviderMetadata, ProviderTokenVo};
+use crate::repository;
+use crate::repository::account::AccountRepository;
+use crate::repository::profile::ProfileRepository;
+use crate::service::account::AccountService;
+use crate::service::login_code::LoginCodeService;
+use crate::utils::{federation_utils, oauth_utils, token_utils};
+
+static STATE_PREFIX: &str = "laurel:system:federation:state:";
+
+/// 发现文档与验签公钥
+type Metadata = (ProviderMetadata, Arc<JwkSet>);
+
+/// 外部 OIDC 身份源登录: 授权码 + PKCE 换取 id_token, 校验后关联或创建本地账户, 再按普通登录签发票据
+#[derive(Debug, Builder)]
+pub struct FederationService {
+    identity_repository: Arc<repository::federation::Repository>,
+    account_repository: Arc<AccountRepository>,
+    profile_repository: Arc<ProfileRepository>,
+    account_service: Arc<AccountService>,
+    redis: Redis,
+    /// 访问身份源使用独立的客户端, 不附带服务间调用签名
+    http: reqwest::Client,
+    config: FederationConfig,
+    /// 身份源发现文档与公钥缓存
+    #[builder(skip)]
+    metadata: Mutex<HashMap<String, (Instant, Metadata)>>,
+}
+
+impl FederationService {
+    pub fn providers(&self) -> Vec<FederatedProviderVo> {
+        self.config
+            .providers
+            .iter()
+            .map(|p| FederatedProviderVo { id: p.id.clone(), name: p.name.clone() })
+            .collect()
+    }
+
+    fn provider(&self, id: &str) -> service::Result<&FederatedProviderConfig> {
+        self.config
+            .providers
+            .iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| Error::msg(format!("federated provider: {} not found", id)))
+    }
+
+    async fn fetch_metadata(&self, provider: &FederatedProviderConfig) -> service::Result<Metadata> {
+        let issuer = provider.issuer.trim_end_matches('/');
+        let metadata = self
+            .http
+            .get(format!("{}/.well-known/openid-configuration", issuer))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ProviderMetadata>()
+            .await?;
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(Error::msg(format!("federated provider: {} issuer mismatch: {}", provider.id, metadata.issuer)));
+        }
+        let jwks = self
+            .http
+            .get(metadata.jwks_uri.as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        Ok((metadata, Arc::new(jwks)))
+    }
+
+    /// 优先使用缓存, 过期或强制刷新(身份源轮换密钥)时重新获取
+    async fn metadata(&self, provider: &FederatedProviderConfig, refresh: bool) -> service::Result<Metadata> {
+        let ttl = Duration::from_secs(self.config.metadata_ttl);
+        if !refresh
+            && let Ok(cache) = self.metadata.lock()
+            && let Some((fetched, (metadata, jwks))) = cache.get(provider.id.as_str())
+            && fetched.elapsed() < ttl {
+            return Ok((metadata.clone(), Arc::clone(jwks)));
+        }
+        let (metadata, jwks) = self.fetch_metadata(provider).await?;
+        if let Ok(mut cache) = self.metadata.lock() {
+            cache.insert(provider.id.clone(), (Instant::now(), (metadata.clone(), Arc::clone(&jwks))));
+        }
+        Ok((metadata, jwks))
+    }
+
+    fn scope(provider: &FederatedProviderConfig) -> String {
+        if provider.scopes.is_empty() {
+            return "openid email profile".to_string();
+        }
+        provider.scopes.join(" ")
+    }
+
+    /// 生成身份源授权地址, state / nonce / PKCE 保存至回调
+    pub async fn authorize(&self, req: &FederationAuthorizeReq) -> service::Result<FederationAuthorizeVo> {
+        let provider = self.provider(req.provider.as_str())?;
+        let (metadata, _) = self.metadata(provider, false).await?;
+        let state = token_utils::token();
+        let federation_state = FederationState {
+            provider: provider.id.clone(),
+            nonce: token_utils::token(),
+            code_verifier: format!("{}{}", token_utils::token(), token_utils::token()),
+        };
+        self.redis
+            .set_with_expire(
+                format!("{}{}", STATE_PREFIX, state).as_str(),
+                serde_json::to_string(&federation_state)?,
+                Duration::from_secs(self.config.state_expire),
+            )
+            .await?;
+        let scope = Self::scope(provider);
+        let code_challenge = oauth_utils::code_challenge(federation_state.code_verifier.as_str());
+        let authorization_url = oauth_utils::redirect_with(
+            metadata.authorization_endpoint.as_str(),
+            &[
+                ("response_type", "code"),
+                ("client_id", provider.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", scope.as_str()),
+                ("state", state.as_str()),
+                ("nonce", federation_state.nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        );
+        Ok(FederationAuthorizeVo { authorization_url, state })
+    }
+
+    async fn exchange_code(&self, provider: &FederatedProviderConfig, metadata: &ProviderMetadata, code: &str, code_verifier: &str) -> service::Result<String> {
+        let response = self
+            .http
+            .post(metadata.token_endpoint.as_str())
+            .basic_auth(provider.client_id.as_str(), Some(provider.client_secret.as_str()))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::msg(format!("federated provider: {} token error: {} {}", provider.id, status, body)));
+        }
+        response
+            .json::<ProviderTokenVo>()
+            .await?
+            .id_token
+            .ok_or_else(|| Error::msg(format!("federated provider: {} returned no id token", provider.id)))
+    }
+
+    async fn verify(&self, provider: &FederatedProviderConfig, state: &FederationState, code: &str) -> service::Result<FederatedClaims> {
+        let (metadata, jwks) = self.metadata(provider, false).await?;
+        let id_token = self.exchange_code(provider, &metadata, code, state.code_verifier.as_str()).await?;
+        let validate = |jwks: &JwkSet| federation_utils::validate_id_token(
+            id_token.as_str(),
+            jwks,
+            metadata.issuer.as_str(),
+            provider.client_id.as_str(),
+            state.nonce.as_str(),
+        );
+        match validate(&jwks) {
+            Ok(claims) => Ok(claims),
+            Err(err) => {
+                warn!("validate id token of provider: {} error: {}, refresh jwks", provider.id, err);
+                let (_, jwks) = self.metadata(provider, true).await?;
+                validate(&jwks)
+            }
+        }
+    }
+
+    /// 已关联的外部身份直接登录, 首次登录按配置关联已有账户或创建账户
+    async fn resolve(&self, provider: &FederatedProviderConfig, state: &FederationState, code: &str, ip: &str) -> service::Result<AccountEntity> {
+        let claims = self.verify(provider, state, code).await?;
+        let email = claims.email.as_deref().filter(|_| claims.email_verified());
+        let account = match self.identity_repository.find(provider.id.as_str(), claims.sub.as_str()).await? {
+            Some(identity) => self
+                .account_repository
+                .find_by_account_id(identity.account_id.as_str())
+                .await?
+                .ok_or_else(|| Error::msg("federated account not found"))?,
+            None => match provider.link.as_str() {
+                "email" => {
+                    let email = email.ok_or_else(|| Error::msg("federated identity has no verified email"))?;
+                    self.account_repository
+                        .find_by_name(LoginCodeService::normalize("email", email)?.as_str(), "email")
+                        .await?
+                        .ok_or_else(|| Error::msg("no account matches federated email"))?
+                },
+                "provision" => self.provision(provider, &claims, ip).await?,
+                link => return Err(Error::msg(format!("federated link: {} not support", link))),
+            },
+        };
+        self.identity_repository
+            .save(&InsertableFederatedIdentity {
+                provider: provider.id.as_str(),
+                subject: claims.sub.as_str(),
+                account_id: account.account_id.as_str(),
+                email,
+                cts: Local::now().naive_local(),
+                uts: Local::now().naive_local(),
+            })
+            .await?;
+        Ok(account)
+    }
+
+    async fn provision(&self, provider: &FederatedProviderConfig, claims: &FederatedClaims, ip: &str) -> service::Result<AccountEntity> {
+        let account_name = federation_utils::account_name(provider.id.as_str(), claims.sub.as_str());
+        let account = self
+            .account_service
+            .provision("federated", account_name.as_str(), provider.id.as_str(), ip)
+            .await?;
+        let mut profiles = vec![];
+        if let Some(name) = claims.name.as_ref().or(claims.preferred_username.as_ref()) {
+            profiles.push((account.account_id.clone(), "nickname".to_string(), Some(name.clone())));
+        }
+        if let Some(email) = &claims.email {
+            profiles.push((account.account_id.clone(), "email".to_string(), Some(email.clone())));
+        }
+        if let Err(err) = self.profile_repository.save(profiles).await {
+            error!("save profile of federated account: {} error: {}", account.account_id, err);
+        }
+        Ok(account)
+    }
+
+    /// 身份源回调后登录, state 使用一次即作废
+    pub async fn login(&self, req: &FederationLoginReq, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let state = self
+            .redis
+            .get_del_optional::<String>(format!("{}{}", STATE_PREFIX, req.state).as_str())
+            .await?
+            .ok_or_else(|| Error::msg("federation state expired"))?;
+        let state = serde_json::from_str::<FederationState>(state.as_str())?;
+        let provider = self.provider(state.provider.as_str())?;
+        let resolve = self.resolve(provider, &state, req.code.as_str(), ip.as_str());
+        self.account_service
+            .login_trusted("federated", provider.id.as_str(), !provider.skip_mfa, &req.client, ua, ip.clone(), resolve)
+            .await
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use anyhow::Error;
+use bon::Builder;
+use chrono::{Duration, Local};
+use tracing::error;
+use laurel_actix::error::BizError;
+use laurel_actix::handler::Token;
+use laurel_actix::permission::{CachedPermissionHandler, PermissionHandler, SUPER_AUTHORITY};
+use laurel_actix::types::service;
+use laurel_id_api::id::IdApi;
+use crate::ImpersonationConfig;
+use crate::model::account::{AccountEntity, AccountState, ImpersonateReq};
+use crate::model::ticket::{InsertableTicket, IssuedTicket};
+use crate::repository;
+use crate::repository::account::AccountRepository;
+use crate::service::audit::AuditService;
+use crate::service::token::TokenService;
+use crate::utils::token_utils;
+
+/// 管理员代登录: 以目标账户身份签发限时票据, 不可刷新
+#[derive(Debug, Builder)]
+pub struct ImpersonationService {
+    account_repository: Arc<AccountRepository>,
+    ticket_repository: Arc<repository::ticket::Repository>,
+    token_service: Arc<TokenService>,
+    permission_cache: Arc<CachedPermissionHandler>,
+    audit_service: Arc<AuditService>,
+    id_api: IdApi,
+    config: ImpersonationConfig,
+}
+
+impl ImpersonationService {
+    async fn permissions(&self, account_id: &str) -> service::Result<Arc<HashSet<String>>> {
+        self.permission_cache
+            .permissions(account_id)
+            .await
+            .map_err(|err| Error::msg(err.to_string()))
+    }
+
+    /// 目标账户拥有而操作者没有的权限码, 操作者为超级权限时不限制
+    fn exceeded<'a>(operator: &HashSet<String>, target: &'a HashSet<String>) -> Option<&'a str> {
+        if operator.contains(SUPER_AUTHORITY) {
+            return None;
+        }
+        target
+            .iter()
+            .find(|p| !operator.contains(p.as_str()))
+            .map(|p| p.as_str())
+    }
+
+    fn forbidden(message: &str) -> Error {
+        BizError::new(403, message).into()
+    }
+
+    /// 开始代登录, 返回目标账户与代登录票据
+    pub async fn start(&self, operator: &Token, req: &ImpersonateReq, ip: String) -> service::Result<(AccountEntity, IssuedTicket)> {
+        if operator.impersonator.is_some() {
+            return Err(Error::msg("already impersonating"));
+        }
+        if operator.api_key.is_some() {
+            return Err(Self::forbidden("cannot impersonate with api key"));
+        }
+        if operator.is_oauth() {
+            return Err(Self::forbidden("cannot impersonate with oauth token"));
+        }
+        if operator.account_id == req.account_id {
+            return Err(Error::msg("cannot impersonate yourself"));
+        }
+        let permissions = self.permissions(operator.account_id.as_str()).await?;
+        let is_super = permissions.contains(SUPER_AUTHORITY);
+        if !is_super && !permissions.contains(self.config.permission.as_str()) {
+            return Err(Self::forbidden(format!("permission denied: {}", self.config.permission).as_str()));
+        }
+        let account = self
+            .account_repository
+            .find_by_account_id(req.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        if let Some((code, message)) = AccountState::reject_code(account.account_state.as_str()) {
+            return Err(BizError::new(code, message).into());
+        }
+        // 不能借代登录获得超出自身的权限
+        if let Some(permission) = Self::exceeded(&permissions, &*self.permissions(account.account_id.as_str()).await?) {
+            return Err(Self::forbidden(format!("cannot impersonate account with permission: {}", permission).as_str()));
+        }
+
+        let ticket_id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let ets = now + Duration::seconds(self.config.expire as i64);
+        let token = self.token_service.make(ticket_id.as_str())?;
+        let ticket = self
+            .ticket_repository
+            .save(&InsertableTicket {
+                ticket_id: ticket_id.as_str(),
+                token: token.as_str(),
+                refresh_id: token_utils::token().as_str(),
+                account_id: account.account_id.as_str(),
+                login_type: "impersonate",
+                ticket_state: "normal",
+                impersonator: operator.account_id.as_str(),
+                device_class: "",
+                client_id: "",
+                scope: "",
+                cts: now,
+                uts: now,
+                ets,
+            })
+            .await?;
+        if let Err(err) = self.token_service.cache(&ticket).await {
+            error!("cache ticket: {} error: {}", ticket.ticket_id, err);
+        }
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "impersonate_start",
+            account.account_id.as_str(),
+            Some(format!(
+                "ticket: {}, expire: {}s, reason: {}",
+                ticket.ticket_id,
+                self.config.expire,
+                req.reason.as_deref().unwrap_or(""),
+            )),
+            Some(ip),
+        );
+        let issued = IssuedTicket {
+            ticket,
+            refresh_token: String::new(),
+            expires_in: self.token_service.access_expire().min(self.config.expire),
+        };
+        Ok((account, issued))
+    }
+
+    /// 结束当前的代登录会话
+    pub async fn stop(&self, token: &Token, ip: String) -> service::Result<()> {
+        let impersonator = token
+            .impersonator
+            .as_deref()
+            .ok_or_else(|| Error::msg("not impersonating"))?;
+        self.token_service.revoke(token.ticket_id.as_str(), "logout").await?;
+        self.audit_service.record(
+            impersonator,
+            "impersonate_stop",
+            token.account_id.as_str(),
+            Some(format!("ticket: {}", token.ticket_id)),
+            Some(ip),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(codes: &[&str]) -> HashSet<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_exceeded() {
+        let operator = set(&["system:account:impersonate", "system:role:view"]);
+        assert_eq!(ImpersonationService::exceeded(&operator, &set(&["system:role:view"])), None);
+        assert_eq!(ImpersonationService::exceeded(&operator, &set(&[])), None);
+        assert_eq!(
+            ImpersonationService::exceeded(&operator, &set(&["system:role:view", "system:oauth:client"])),
+            Some("system:oauth:client")
+        );
+        assert_eq!(ImpersonationService::exceeded(&operator, &set(&[SUPER_AUTHORITY])), Some(SUPER_AUTHORITY));
+        assert_eq!(ImpersonationService::exceeded(&set(&[SUPER_AUTHORITY]), &set(&["system:oauth:client"])), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use rand::Rng;
+use tracing::warn;
+use laurel_actix::error::BizError;
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::LoginCodeConfig;
+use crate::service::code_sender::CodeSender;
+use crate::utils::codes;
+
+static CODE_PREFIX: &str = "laurel:system:login:code:";
+static ATTEMPT_PREFIX: &str = "laurel:system:login:code:attempt:";
+static COOLDOWN_PREFIX: &str = "laurel:system:login:code:cooldown:";
+static HOURLY_PREFIX: &str = "laurel:system:login:code:hourly:";
+static HOUR: Duration = Duration::from_secs(3600);
+
+/// 邮箱/手机一次性登录码: 存 redis, 限制发送频率与校验次数
+#[derive(Debug)]
+pub struct LoginCodeService {
+    redis: Redis,
+    sender: Arc<dyn CodeSender>,
+    config: LoginCodeConfig,
+}
+
+impl LoginCodeService {
+    pub fn new(redis: Redis, sender: Arc<dyn CodeSender>, config: LoginCodeConfig) -> Self {
+        Self { redis, sender, config }
+    }
+
+    /// 校验并规范化登录标识, 邮箱统一小写
+    pub fn normalize(account_type: &str, target: &str) -> service::Result<String> {
+        let target = target.trim();
+        match account_type {
+            "email" => {
+                let valid = target
+                    .split_once('@')
+                    .map(|(name, domain)| !name.is_empty() && domain.contains('.'))
+                    .unwrap_or(false);
+                if !valid {
+                    return Err(Error::msg(format!("invalid email: {}", target)));
+                }
+                Ok(target.to_lowercase())
+            }
+            "phone" => {
+                let digits = target.strip_prefix('+').unwrap_or(target);
+                if digits.len() < 6 || digits.len() > 20 || !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(Error::msg(format!("invalid phone: {}", target)));
+                }
+                Ok(target.to_string())
+            }
+            _ => Err(Error::msg(format!("account type: {} not support login code", account_type))),
+        }
+    }
+
+    fn key(prefix: &str, account_type: &str, target: &str) -> String {
+        format!("{}{}:{}", prefix, account_type, target)
+    }
+
+    /// 窗口内计数, 首次写入时设置过期
+    async fn count(&self, key: &str, window: Duration) -> service::Result<i64> {
+        let count = self.redis.incr(key).await?;
+        if count == 1 {
+            self.redis.expire::<()>(key, window).await?;
+        }
+        Ok(count)
+    }
+
+    /// 发送频率限制: 单标识冷却期 + 单标识/单ip每小时上限
+    pub async fn limit(&self, account_type: &str, target: &str, ip: &str) -> service::Result<()> {
+        let cooldown_key = Self::key(COOLDOWN_PREFIX, account_type, target);
+        if !self
+            .redis
+            .set_nx_with_expire(cooldown_key.as_str(), "1", Duration::from_secs(self.config.cooldown))
+            .await? {
+            let ttl = self.redis.ttl(cooldown_key.as_str()).await?;
+            return Err(BizError::new(
+                codes::LOGIN_CODE_TOO_FREQUENT,
+                format!("发送过于频繁, 请{}秒后重试", ttl.max(1)),
+            ).into());
+        }
+        let target_count = self
+            .count(Self::key(HOURLY_PREFIX, account_type, target).as_str(), HOUR)
+            .await?;
+        let ip_count = self
+            .count(format!("{}ip:{}", HOURLY_PREFIX, ip).as_str(), HOUR)
+            .await?;
+        if target_count > self.config.hourly_limit || ip_count > self.config.ip_hourly_limit {
+            warn!("login code of {}: {} from ip: {} exceeds hourly limit", account_type, target, ip);
+            return Err(BizError::new(codes::LOGIN_CODE_LIMITED, "验证码发送次数超限, 请稍后重试").into());
+        }
+        Ok(())
+    }
+
+    /// 生成并投递验证码, 覆盖未使用的旧码
+    pub async fn send(&self, account_type: &str, target: &str) -> service::Result<()> {
+        let code = format!("{:06}", rand::rng().random_range(0..1_000_000u32));
+        let expire = Duration::from_secs(self.config.expire);
+        self.redis
+            .set_with_expire(Self::key(CODE_PREFIX, account_type, target).as_str(), code.clone(), expire)
+            .await?;
+        self.redis.del(Self::key(ATTEMPT_PREFIX, account_type, target).as_str()).await?;
+        self.sender.send(account_type, target, code.as_str()).await
+    }
+
+    /// 校验验证码, 成功或超过尝试次数后作废
+    pub async fn verify(&self, account_type: &str, target: &str, code: &str) -> service::Result<bool> {
+        let code_key = Self::key(CODE_PREFIX, account_type, target);
+        let attempt_key = Self::key(ATTEMPT_PREFIX, account_type, target);
+        let saved = match self.redis.get_optional::<String>(code_key.as_str()).await? {
+            Some(saved) => saved,
+            None => return Ok(false),
+        };
+        if saved == code.trim() {
+            self.redis.del(code_key.as_str()).await?;
+            self.redis.del(attempt_key.as_str()).await?;
+            return Ok(true);
+        }
+        let attempts = self
+            .count(attempt_key.as_str(), Duration::from_secs(self.config.expire))
+            .await?;
+        if attempts >= self.config.max_attempts {
+            warn!("login code of {}: {} discarded after {} attempts", account_type, target, attempts);
+            self.redis.del(code_key.as_str()).await?;
+            self.redis.del(attempt_key.as_str()).await?;
+        }
+        Ok(false)
+    }
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(LoginCodeService::normalize("email", " Foo@Example.com ").unwrap(), "foo@example.com");
+    assert!(LoginCodeService::normalize("email", "foo@example").is_err());
+    assert!(LoginCodeService::normalize("phone", "+8613800138000").is_ok());
+    assert!(LoginCodeService::normalize("phone", "138-0013").is_err());
+    assert!(LoginCodeService::normalize("name", "admin").is_err());
+}
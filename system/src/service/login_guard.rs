@@ -0,0 +1,120 @@
+use std::time::Duration;
+use chrono::Local;
+use tracing::warn;
+use laurel_actix::error::BizError;
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::LoginGuardConfig;
+use crate::utils::{codes, token_utils};
+
+static FAILURE_PREFIX: &str = "laurel:system:login:failure:";
+static LOCK_PREFIX: &str = "laurel:system:login:lock:";
+
+/// 原子地检查锁定并记录本次尝试, 超过阈值时加锁, 返回锁定剩余秒数(未锁定为 0).
+/// KEYS: 失败记录, 锁; ARGV: 当前毫秒, 窗口毫秒, 阈值, 锁定秒数, 本次尝试id
+static ATTEMPT_SCRIPT: &str = r#"
+local ttl = redis.call('TTL', KEYS[2])
+if ttl > 0 then
+    return ttl
+end
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', tonumber(ARGV[1]) - tonumber(ARGV[2]))
+redis.call('ZADD', KEYS[1], ARGV[1], ARGV[5])
+redis.call('PEXPIRE', KEYS[1], ARGV[2])
+if redis.call('ZCARD', KEYS[1]) > tonumber(ARGV[3]) then
+    redis.call('SET', KEYS[2], '1', 'EX', ARGV[4])
+    redis.call('DEL', KEYS[1])
+    return tonumber(ARGV[4])
+end
+return 0
+"#;
+
+/// 登录尝试计数: 按账户名与来源ip分别做滑动窗口统计, 校验密码前计入, 超限后锁定
+#[derive(Debug)]
+pub struct LoginGuard {
+    redis: Redis,
+    config: LoginGuardConfig,
+}
+
+impl LoginGuard {
+    pub fn new(redis: Redis, config: LoginGuardConfig) -> Self {
+        Self { redis, config }
+    }
+
+    /// 失败记录与锁使用相同的 hash tag, 集群模式下位于同一 slot
+    fn account_key(prefix: &str, account: &str) -> String {
+        format!("{}account:{{{}}}", prefix, account)
+    }
+
+    fn ip_key(prefix: &str, ip: &str) -> String {
+        format!("{}ip:{{{}}}", prefix, ip)
+    }
+
+    /// 锁定提示不区分账户是否存在
+    fn locked(code: u16, ttl: i64) -> anyhow::Error {
+        BizError::new(code, format!("登录失败次数过多, 请{}秒后重试", ttl)).into()
+    }
+
+    /// 登录前计入一次尝试, 处于锁定期或超过阈值时拒绝; 返回本次尝试id
+    pub async fn attempt(&self, account: &str, ip: &str) -> service::Result<String> {
+        let attempt = token_utils::token();
+        let ttl = self
+            .record(
+                Self::account_key(FAILURE_PREFIX, account),
+                Self::account_key(LOCK_PREFIX, account),
+                self.config.max_account_failures,
+                attempt.as_str(),
+            )
+            .await?;
+        if ttl > 0 {
+            warn!("account: {} locked by too many login failures", account);
+            return Err(Self::locked(codes::ACCOUNT_LOCKED, ttl));
+        }
+        let ttl = self
+            .record(
+                Self::ip_key(FAILURE_PREFIX, ip),
+                Self::ip_key(LOCK_PREFIX, ip),
+                self.config.max_ip_failures,
+                attempt.as_str(),
+            )
+            .await?;
+        if ttl > 0 {
+            warn!("ip: {} locked by too many login failures", ip);
+            return Err(Self::locked(codes::IP_LOCKED, ttl));
+        }
+        Ok(attempt)
+    }
+
+    /// 密码正确但尚未完成登录(二次验证、修改过期密码), 本次尝试不计为失败
+    pub async fn verified(&self, account: &str, ip: &str, attempt: &str) -> service::Result<()> {
+        self.redis.zrem(Self::account_key(FAILURE_PREFIX, account).as_str(), attempt).await?;
+        self.redis.zrem(Self::ip_key(FAILURE_PREFIX, ip).as_str(), attempt).await?;
+        Ok(())
+    }
+
+    /// 登录成功清空账户的失败记录
+    pub async fn success(&self, account: &str, ip: &str, attempt: &str) -> service::Result<()> {
+        self.redis.del(Self::account_key(FAILURE_PREFIX, account).as_str()).await?;
+        self.redis.zrem(Self::ip_key(FAILURE_PREFIX, ip).as_str(), attempt).await?;
+        Ok(())
+    }
+
+    async fn record(&self, failure_key: String, lock_key: String, max: i64, attempt: &str) -> service::Result<i64> {
+        let now = Local::now().timestamp_millis();
+        let window = Duration::from_secs(self.config.window).as_millis() as i64;
+        let ttl = self
+            .redis
+            .eval::<i64>(
+                ATTEMPT_SCRIPT,
+                vec![failure_key, lock_key],
+                vec![
+                    now.to_string(),
+                    window.to_string(),
+                    max.to_string(),
+                    self.config.lock_duration.to_string(),
+                    attempt.to_string(),
+                ],
+            )
+            .await?;
+        Ok(ttl)
+    }
+}
@@ -2,6 +2,8 @@ use crate::model::menu::{
     InsertAbleMenu, Menu, MenuCreateReq, MenuQueryReq, MenuUpdateReq, UpdatableMenu,
 };
 use crate::repository::menu::MenuRepository;
+use crate::repository::role::RoleRepository;
+use std::collections::HashSet;
 use anyhow::Error;
 use bon::Builder;
 use chrono::Local;
@@ -13,6 +15,7 @@ use laurel_actix::types::service;
 #[derive(Debug, Builder)]
 pub struct MenuService {
     pub menu_repository: Arc<MenuRepository>,
+    pub role_repository: Arc<RoleRepository>,
     pub id_api: IdApi,
 }
 
@@ -30,6 +33,21 @@ impl MenuService {
         self.menu_repository.list_menus_recursive(app_id).await
     }
 
+    /// 账户已授权的菜单与按钮权限码
+    pub async fn list_granted_menus(&self, app_id: &str, account_id: &str) -> service::Result<(Vec<Menu>, Vec<String>)> {
+        let granted: HashSet<String> = self
+            .role_repository
+            .list_menu_ids_by_account(account_id)
+            .await?
+            .into_iter()
+            .collect();
+        if granted.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+        let menus = self.menu_repository.list_menus_recursive(app_id).await?;
+        Ok(Menu::retain_granted(menus, &granted))
+    }
+
     pub async fn page_menus(
         &self,
         req: &MenuQueryReq,
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use bon::Builder;
+use chrono::Local;
+use tracing::warn;
+use laurel_actix::handler::Token;
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::model::mfa::{AccountMfa, InsertableAccountMfa, MfaChallenge, RecoveryCodesVo, TotpEnrollVo};
+use crate::repository;
+use crate::repository::account::AccountRepository;
+use crate::service::audit::AuditService;
+use crate::utils::{token_utils, totp_utils};
+
+static CHALLENGE_PREFIX: &str = "laurel:system:mfa:challenge:";
+/// 二次验证挑战有效期
+static CHALLENGE_EXPIRE: Duration = Duration::from_secs(300);
+static RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Builder)]
+pub struct MfaService {
+    mfa_repository: Arc<repository::mfa::Repository>,
+    account_repository: Arc<AccountRepository>,
+    audit_service: Arc<AuditService>,
+    redis: Redis,
+    issuer: String,
+}
+
+impl MfaService {
+    pub async fn find(&self, account_id: &str) -> service::Result<Option<AccountMfa>> {
+        self.mfa_repository.find(account_id).await
+    }
+
+    /// 已启用的二次验证
+    pub async fn find_enabled(&self, account_id: &str) -> service::Result<Option<AccountMfa>> {
+        Ok(self.find(account_id).await?.filter(|m| m.enabled()))
+    }
+
+    fn check_session(token: &Token) -> service::Result<()> {
+        if !token.is_session() {
+            return Err(Error::msg("totp can only be changed by a login session"));
+        }
+        Ok(())
+    }
+
+    /// 生成待确认的秘钥, 已启用时需先关闭
+    pub async fn enroll(&self, token: &Token) -> service::Result<TotpEnrollVo> {
+        Self::check_session(token)?;
+        if self.find_enabled(token.account_id.as_str()).await?.is_some() {
+            return Err(Error::msg("totp already enabled"));
+        }
+        let account = self
+            .account_repository
+            .find_by_account_id(token.account_id.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("account not found"))?;
+        let secret = totp_utils::secret();
+        let now = Local::now().naive_local();
+        self.mfa_repository
+            .save_pending(&InsertableAccountMfa {
+                account_id: token.account_id.as_str(),
+                mfa_type: "totp",
+                secret: secret.as_str(),
+                mfa_state: "pending",
+                recovery_codes: "",
+                last_step: 0,
+                cts: now,
+                uts: now,
+            })
+            .await?;
+        Ok(TotpEnrollVo {
+            uri: totp_utils::uri(self.issuer.as_str(), account.account_name.as_str(), secret.as_str()),
+            secret,
+        })
+    }
+
+    /// 校验首个动态码后启用, 返回恢复码
+    pub async fn activate(&self, token: &Token, code: &str, ip: String) -> service::Result<RecoveryCodesVo> {
+        Self::check_session(token)?;
+        let mfa = self
+            .find(token.account_id.as_str())
+            .await?
+            .filter(|m| !m.enabled())
+            .ok_or_else(|| Error::msg("totp not enrolled"))?;
+        let step = totp_utils::verify(mfa.secret.as_str(), code, Local::now().timestamp() as u64, 1)?
+            .ok_or_else(|| Error::msg("totp code error"))?;
+        let codes = totp_utils::recovery_codes(RECOVERY_CODE_COUNT);
+        self.mfa_repository
+            .enable(token.account_id.as_str(), Self::join_hashes(&codes).as_str(), step as i64)
+            .await?
+            .ok_or_else(|| Error::msg("totp not enrolled"))?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "mfa_enable",
+            token.account_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(RecoveryCodesVo { codes })
+    }
+
+    pub async fn disable(&self, token: &Token, code: &str, ip: String) -> service::Result<()> {
+        Self::check_session(token)?;
+        let mfa = self.enabled_or_err(token.account_id.as_str()).await?;
+        if !self.verify(&mfa, code).await? {
+            return Err(Error::msg("totp code error"));
+        }
+        self.mfa_repository.delete(token.account_id.as_str()).await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "mfa_disable",
+            token.account_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(())
+    }
+
+    /// 重新生成恢复码, 旧恢复码全部失效
+    pub async fn regenerate_recovery_codes(&self, token: &Token, code: &str, ip: String) -> service::Result<RecoveryCodesVo> {
+        Self::check_session(token)?;
+        let mfa = self.enabled_or_err(token.account_id.as_str()).await?;
+        if !self.verify(&mfa, code).await? {
+            return Err(Error::msg("totp code error"));
+        }
+        // verify 可能消耗了恢复码, 以最新记录为准
+        let mfa = self.enabled_or_err(token.account_id.as_str()).await?;
+        let codes = totp_utils::recovery_codes(RECOVERY_CODE_COUNT);
+        if !self
+            .mfa_repository
+            .update_recovery_codes(token.account_id.as_str(), mfa.recovery_codes.as_str(), Self::join_hashes(&codes).as_str())
+            .await? {
+            return Err(Error::msg("recovery codes changed, please retry"));
+        }
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "mfa_recovery_regenerate",
+            token.account_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(RecoveryCodesVo { codes })
+    }
+
+    /// 校验动态码或恢复码; 动态码不可重放, 恢复码使用后作废
+    pub async fn verify(&self, mfa: &AccountMfa, code: &str) -> service::Result<bool> {
+        if let Some(step) = totp_utils::verify(mfa.secret.as_str(), code, Local::now().timestamp() as u64, 1)? {
+            return self
+                .mfa_repository
+                .update_last_step(mfa.account_id.as_str(), step as i64)
+                .await;
+        }
+        let hash = totp_utils::hash_recovery_code(code);
+        let hashes = mfa.recovery_code_hashes();
+        if !hashes.contains(&hash.as_str()) {
+            return Ok(false);
+        }
+        let remain = hashes
+            .into_iter()
+            .filter(|h| *h != hash.as_str())
+            .collect::<Vec<&str>>();
+        let used = self
+            .mfa_repository
+            .update_recovery_codes(mfa.account_id.as_str(), mfa.recovery_codes.as_str(), remain.join(",").as_str())
+            .await?;
+        if used {
+            warn!("account: {} used recovery code, remain: {}", mfa.account_id, remain.len());
+        }
+        Ok(used)
+    }
+
+    fn challenge_key(challenge: &str) -> String {
+        format!("{}{}", CHALLENGE_PREFIX, challenge)
+    }
+
+    /// 第一因子通过后签发短期挑战票据
+    pub async fn challenge(&self, challenge: &MfaChallenge) -> service::Result<String> {
+        let ticket = token_utils::token();
+        self.redis
+            .set_with_expire(Self::challenge_key(ticket.as_str()).as_str(), serde_json::to_string(challenge)?, CHALLENGE_EXPIRE)
+            .await?;
+        Ok(ticket)
+    }
+
+    /// 挑战票据对应的账户与登录方式
+    pub async fn find_challenge(&self, challenge: &str) -> service::Result<Option<MfaChallenge>> {
+        match self.redis.get_optional::<String>(Self::challenge_key(challenge).as_str()).await? {
+            Some(c) => Ok(Some(serde_json::from_str::<MfaChallenge>(c.as_str())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn clear_challenge(&self, challenge: &str) -> service::Result<()> {
+        self.redis.del(Self::challenge_key(challenge).as_str()).await?;
+        Ok(())
+    }
+
+    async fn enabled_or_err(&self, account_id: &str) -> service::Result<AccountMfa> {
+        self.find_enabled(account_id)
+            .await?
+            .ok_or_else(|| Error::msg("totp not enabled"))
+    }
+
+    fn join_hashes(codes: &[String]) -> String {
+        codes
+            .iter()
+            .map(|c| totp_utils::hash_recovery_code(c))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
@@ -3,4 +3,18 @@ pub mod fe_micro_service;
 pub mod menu;
 pub mod token;
 pub mod account;
-pub mod profile;
\ No newline at end of file
+pub mod profile;
+pub mod role;
+pub mod audit;
+pub mod login_guard;
+pub mod mfa;
+pub mod ticket;pub mod permission;
+pub mod code_sender;
+pub mod login_code;
+pub mod impersonation;
+pub mod device;
+pub mod session_limit;
+pub mod oauth;
+pub mod federation;
+pub mod api_key;
+pub mod qr_login;
@@ -0,0 +1,432 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use bon::Builder;
+use chrono::{Local, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+use laurel_actix::handler::Token;
+use laurel_actix::types::service;
+use laurel_id_api::id::IdApi;
+use laurel_redis::Redis;
+use crate::OAuthConfig;
+use crate::model::account::AccountState;
+use crate::model::oauth::{AuthorizationCode, AuthorizeReq, DiscoveryVo, IdTokenClaims, InsertableOAuthClient, OAuthClient, OAuthClientCreateReq, OAuthClientStateReq, OAuthClientVo, OAuthTokenReq, OAuthTokenVo, UserInfoVo};
+use crate::model::ticket::{InsertableTicket, IssuedTicket};
+use crate::repository;
+use crate::repository::account::AccountRepository;
+use crate::repository::profile::ProfileRepository;
+use crate::service::audit::AuditService;
+use crate::service::token::TokenService;
+use crate::utils::jwt_utils::JwtKeys;
+use crate::utils::{oauth_utils, token_utils};
+
+static CODE_PREFIX: &str = "laurel:system:oauth:code:";
+static API_PREFIX: &str = "/api/system/oauth2";
+
+/// OAuth2 规范的错误, 路由层据此返回 error / error_description
+#[derive(Debug, Error, Serialize)]
+#[error("{error}: {error_description}")]
+pub struct OAuthError {
+    pub error: &'static str,
+    pub error_description: String,
+}
+
+impl OAuthError {
+    pub fn of(error: &'static str, description: impl Into<String>) -> Error {
+        OAuthError { error, error_description: description.into() }.into()
+    }
+}
+
+/// OAuth2 授权码 + PKCE 与 OIDC. 令牌即系统票据, 与账户登录共用校验、刷新与吊销;
+/// 客户端均为内部工具, 不单独展示授权确认页
+#[derive(Debug, Builder)]
+pub struct OAuthService {
+    client_repository: Arc<repository::oauth::Repository>,
+    account_repository: Arc<AccountRepository>,
+    profile_repository: Arc<ProfileRepository>,
+    ticket_repository: Arc<repository::ticket::Repository>,
+    token_service: Arc<TokenService>,
+    jwt_keys: Arc<JwtKeys>,
+    audit_service: Arc<AuditService>,
+    redis: Redis,
+    id_api: IdApi,
+    config: OAuthConfig,
+}
+
+impl OAuthService {
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.issuer.trim_end_matches('/'), path)
+    }
+
+    pub fn discovery(&self) -> DiscoveryVo {
+        DiscoveryVo {
+            issuer: self.config.issuer.clone(),
+            authorization_endpoint: self.endpoint(format!("{}/authorize", API_PREFIX).as_str()),
+            token_endpoint: self.endpoint(format!("{}/token", API_PREFIX).as_str()),
+            userinfo_endpoint: self.endpoint(format!("{}/userinfo", API_PREFIX).as_str()),
+            jwks_uri: self.endpoint("/.well-known/jwks.json"),
+            response_types_supported: vec!["code"],
+            grant_types_supported: vec!["authorization_code", "refresh_token"],
+            subject_types_supported: vec!["public"],
+            id_token_signing_alg_values_supported: vec![self.jwt_keys.algorithm()],
+            scopes_supported: self.config.scopes.clone(),
+            token_endpoint_auth_methods_supported: vec!["client_secret_basic", "client_secret_post", "none"],
+            code_challenge_methods_supported: vec!["S256"],
+            claims_supported: vec![
+                "sub", "name", "preferred_username", "picture", "email", "email_verified", "phone_number", "phone_number_verified",
+            ],
+        }
+    }
+
+    /// 未登录时跳转登录页, 登录后回到授权地址
+    pub fn login_redirect(&self, authorize_uri: &str) -> String {
+        oauth_utils::redirect_with(self.config.login_page.as_str(), &[("redirect", authorize_uri)])
+    }
+
+    pub async fn create_client(&self, operator: &Token, req: &OAuthClientCreateReq, ip: String) -> service::Result<OAuthClientVo> {
+        let client_name = req.client_name.trim();
+        if client_name.is_empty() {
+            return Err(Error::msg("client name is required"));
+        }
+        if req.redirect_uris.is_empty() {
+            return Err(Error::msg("redirect uri is required"));
+        }
+        for uri in &req.redirect_uris {
+            let valid = (uri.starts_with("https://") || uri.starts_with("http://"))
+                && !uri.contains('#')
+                && !uri.contains(char::is_whitespace);
+            if !valid {
+                return Err(Error::msg(format!("invalid redirect uri: {}", uri)));
+            }
+        }
+        let scopes = if req.scopes.is_empty() { self.config.scopes.clone() } else { req.scopes.clone() };
+        if let Some(scope) = scopes.iter().find(|s| !self.config.scopes.contains(s)) {
+            return Err(Error::msg(format!("scope: {} not supported", scope)));
+        }
+
+        let client_id = token_utils::token();
+        let secret = if req.public { None } else { Some(format!("{}{}", token_utils::token(), token_utils::token())) };
+        let now = Local::now().naive_local();
+        let client = self
+            .client_repository
+            .save(&InsertableOAuthClient {
+                client_id: client_id.as_str(),
+                client_secret: secret.as_deref().map(oauth_utils::secret_hash).unwrap_or_default().as_str(),
+                client_name,
+                redirect_uris: req.redirect_uris.join(" ").as_str(),
+                scopes: scopes.join(" ").as_str(),
+                client_state: "normal",
+                creator: operator.account_id.as_str(),
+                cts: now,
+                uts: now,
+            })
+            .await?;
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "oauth_client_create",
+            client.client_id.as_str(),
+            Some(format!("name: {}, public: {}", client.client_name, client.public())),
+            Some(ip),
+        );
+        let mut vo = OAuthClientVo::from(client);
+        vo.client_secret = secret;
+        Ok(vo)
+    }
+
+    pub async fn list_clients(&self) -> service::Result<Vec<OAuthClient>> {
+        self.client_repository.list().await
+    }
+
+    /// 停用客户端时吊销其签发的全部票据
+    pub async fn change_client_state(&self, operator: &Token, req: &OAuthClientStateReq, ip: String) -> service::Result<OAuthClient> {
+        if req.client_state != "normal" && req.client_state != "disabled" {
+            return Err(Error::msg(format!("invalid client state: {}", req.client_state)));
+        }
+        let client = self
+            .client_repository
+            .update_state(req.client_id.as_str(), req.client_state.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("client not found"))?;
+        if !client.enabled() {
+            self.token_service.revoke_client(client.client_id.as_str(), "revoked").await?;
+        }
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "oauth_client_state",
+            client.client_id.as_str(),
+            Some(format!("state: {}", client.client_state)),
+            Some(ip),
+        );
+        Ok(client)
+    }
+
+    async fn client(&self, client_id: &str) -> service::Result<OAuthClient> {
+        match self.client_repository.find(client_id).await? {
+            Some(client) if client.enabled() => Ok(client),
+            _ => Err(OAuthError::of("invalid_client", "unknown or disabled client")),
+        }
+    }
+
+    /// 校验客户端身份: 机密客户端必须提供正确的密钥
+    async fn authenticate(&self, client_id: Option<&str>, client_secret: Option<&str>) -> service::Result<OAuthClient> {
+        let client = self
+            .client(client_id.ok_or_else(|| OAuthError::of("invalid_client", "client_id is required"))?)
+            .await?;
+        if !client.public() && !oauth_utils::secret_matches(client_secret.unwrap_or_default(), client.client_secret.as_str()) {
+            return Err(OAuthError::of("invalid_client", "client authentication failed"));
+        }
+        Ok(client)
+    }
+
+    async fn ensure_active(&self, account_id: &str) -> service::Result<()> {
+        let account = self
+            .account_repository
+            .find_by_account_id(account_id)
+            .await?
+            .ok_or_else(|| OAuthError::of("access_denied", "account not found"))?;
+        if AccountState::reject_code(account.account_state.as_str()).is_some() {
+            return Err(OAuthError::of("access_denied", "account is not active"));
+        }
+        Ok(())
+    }
+
+    /// 处理授权请求, 返回回调地址. 客户端或回调地址无效时返回错误, 其余错误经回调地址告知客户端
+    pub async fn authorize(&self, token: &Token, req: &AuthorizeReq, ip: String) -> service::Result<String> {
+        let client = self.client(req.client_id.as_str()).await?;
+        if !client.redirect_uri_list().contains(&req.redirect_uri.as_str()) {
+            return Err(OAuthError::of("invalid_request", "redirect_uri mismatch"));
+        }
+        let state = req.state.as_deref().unwrap_or_default();
+        let fail = |error: &str, description: &str| {
+            let mut params = vec![("error", error), ("error_description", description)];
+            if !state.is_empty() {
+                params.push(("state", state));
+            }
+            oauth_utils::redirect_with(req.redirect_uri.as_str(), &params)
+        };
+        if req.response_type != "code" {
+            return Ok(fail("unsupported_response_type", "only code is supported"));
+        }
+        if req.code_challenge.is_empty() || req.code_challenge_method.as_deref() != Some("S256") {
+            return Ok(fail("invalid_request", "PKCE with S256 is required"));
+        }
+        if token.impersonator.is_some() {
+            return Ok(fail("access_denied", "not allowed while impersonating"));
+        }
+        if token.api_key.is_some() {
+            return Ok(fail("access_denied", "api key cannot authorize clients"));
+        }
+        if token.is_oauth() {
+            return Ok(fail("access_denied", "oauth token cannot authorize clients"));
+        }
+        let scope = oauth_utils::grant_scope(req.scope.as_str(), &client.scope_list());
+        if scope.is_empty() {
+            return Ok(fail("invalid_scope", "no requested scope is allowed"));
+        }
+        if let Err(err) = self.ensure_active(token.account_id.as_str()).await {
+            return match err.downcast_ref::<OAuthError>() {
+                Some(e) => Ok(fail(e.error, e.error_description.as_str())),
+                None => Err(err),
+            };
+        }
+
+        let code = token_utils::token();
+        let authorization = AuthorizationCode {
+            client_id: client.client_id.clone(),
+            redirect_uri: req.redirect_uri.clone(),
+            account_id: token.account_id.clone(),
+            scope: scope.clone(),
+            nonce: req.nonce.clone(),
+            code_challenge: req.code_challenge.clone(),
+            auth_time: Utc::now().timestamp(),
+        };
+        self.redis
+            .set_with_expire(
+                format!("{}{}", CODE_PREFIX, code).as_str(),
+                serde_json::to_string(&authorization)?,
+                Duration::from_secs(self.config.code_expire),
+            )
+            .await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "oauth_authorize",
+            client.client_id.as_str(),
+            Some(format!("scope: {}", scope)),
+            Some(ip),
+        );
+        let mut params = vec![("code", code.as_str())];
+        if !state.is_empty() {
+            params.push(("state", state));
+        }
+        Ok(oauth_utils::redirect_with(req.redirect_uri.as_str(), &params))
+    }
+
+    /// 令牌端点: 授权码换取令牌, 或使用 refresh token 续期
+    pub async fn token(&self, req: &OAuthTokenReq, basic: Option<(String, String)>) -> service::Result<OAuthTokenVo> {
+        let (client_id, client_secret) = match &basic {
+            Some((id, secret)) => (Some(id.as_str()), Some(secret.as_str())),
+            None => (req.client_id.as_deref(), req.client_secret.as_deref()),
+        };
+        let client = self.authenticate(client_id, client_secret).await?;
+        match req.grant_type.as_str() {
+            "authorization_code" => self.exchange_code(&client, req).await,
+            "refresh_token" => self.refresh(&client, req).await,
+            _ => Err(OAuthError::of("unsupported_grant_type", req.grant_type.clone())),
+        }
+    }
+
+    async fn exchange_code(&self, client: &OAuthClient, req: &OAuthTokenReq) -> service::Result<OAuthTokenVo> {
+        let code = req.code.as_deref().unwrap_or_default();
+        let authorization = self
+            .redis
+            .get_del_optional::<String>(format!("{}{}", CODE_PREFIX, code).as_str())
+            .await?
+            .ok_or_else(|| OAuthError::of("invalid_grant", "invalid or expired code"))?;
+        let authorization = serde_json::from_str::<AuthorizationCode>(authorization.as_str())?;
+        if authorization.client_id != client.client_id
+            || Some(authorization.redirect_uri.as_str()) != req.redirect_uri.as_deref() {
+            return Err(OAuthError::of("invalid_grant", "code was issued to another client or redirect_uri"));
+        }
+        if !oauth_utils::verify_pkce(req.code_verifier.as_deref().unwrap_or_default(), authorization.code_challenge.as_str()) {
+            return Err(OAuthError::of("invalid_grant", "code_verifier mismatch"));
+        }
+        self.ensure_active(authorization.account_id.as_str()).await?;
+
+        let issued = self.issue(authorization.account_id.as_str(), client, authorization.scope.as_str()).await?;
+        let id_token = if oauth_utils::has_scope(authorization.scope.as_str(), "openid") {
+            let now = Utc::now().timestamp();
+            Some(self.jwt_keys.encode(&IdTokenClaims {
+                iss: self.config.issuer.clone(),
+                sub: authorization.account_id.clone(),
+                aud: client.client_id.clone(),
+                exp: now + issued.expires_in as i64,
+                iat: now,
+                auth_time: authorization.auth_time,
+                nonce: authorization.nonce.clone(),
+                at_hash: oauth_utils::at_hash(issued.ticket.token.as_str()),
+            })?)
+        } else {
+            None
+        };
+        Ok(OAuthTokenVo {
+            access_token: issued.ticket.token,
+            token_type: "Bearer",
+            expires_in: issued.expires_in,
+            refresh_token: issued.refresh_token,
+            id_token,
+            scope: authorization.scope,
+        })
+    }
+
+    async fn refresh(&self, client: &OAuthClient, req: &OAuthTokenReq) -> service::Result<OAuthTokenVo> {
+        let refresh_token = req.refresh_token.as_deref().unwrap_or_default();
+        let invalid = || OAuthError::of("invalid_grant", "invalid refresh token");
+        let payload = self.token_service.decode(refresh_token, "refresh").map_err(|_| invalid())?;
+        match self.ticket_repository.find(payload.ticket_id.as_str()).await? {
+            Some(ticket) if ticket.client_id == client.client_id => {},
+            _ => return Err(invalid()),
+        }
+        let issued = self.token_service.refresh(refresh_token, client.client_id.as_str()).await.map_err(|err| {
+            error!("refresh oauth ticket: {} error: {}", payload.ticket_id, err);
+            invalid()
+        })?;
+        Ok(OAuthTokenVo {
+            access_token: issued.ticket.token,
+            token_type: "Bearer",
+            expires_in: issued.expires_in,
+            refresh_token: issued.refresh_token,
+            id_token: None,
+            scope: issued.ticket.scope,
+        })
+    }
+
+    async fn issue(&self, account_id: &str, client: &OAuthClient, scope: &str) -> service::Result<IssuedTicket> {
+        let ticket_id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let ets = self.token_service.expire_at(now);
+        let refresh_id = token_utils::token();
+        let token = self.token_service.make(ticket_id.as_str())?;
+        let refresh_token = self.token_service.make_refresh(ticket_id.as_str(), refresh_id.as_str(), ets)?;
+        let ticket = self
+            .ticket_repository
+            .save(&InsertableTicket {
+                ticket_id: ticket_id.as_str(),
+                token: token.as_str(),
+                refresh_id: refresh_id.as_str(),
+                account_id,
+                login_type: "oauth",
+                ticket_state: "normal",
+                impersonator: "",
+                device_class: "",
+                client_id: client.client_id.as_str(),
+                scope,
+                cts: now,
+                uts: now,
+                ets,
+            })
+            .await?;
+        if let Err(err) = self.token_service.cache(&ticket).await {
+            error!("cache ticket: {} error: {}", ticket.ticket_id, err);
+        }
+        Ok(IssuedTicket {
+            ticket,
+            refresh_token,
+            expires_in: self.token_service.access_expire(),
+        })
+    }
+
+    /// 按令牌的授权范围返回用户信息
+    pub async fn userinfo(&self, token: &Token) -> service::Result<UserInfoVo> {
+        let ticket = self
+            .ticket_repository
+            .find(token.ticket_id.as_str())
+            .await?
+            .ok_or_else(|| OAuthError::of("invalid_token", "ticket not found"))?;
+        let scope = ticket.scope.as_str();
+        if !oauth_utils::has_scope(scope, "openid") {
+            return Err(OAuthError::of("insufficient_scope", "openid scope is required"));
+        }
+        let account = self
+            .account_repository
+            .find_by_account_id(ticket.account_id.as_str())
+            .await?
+            .ok_or_else(|| OAuthError::of("invalid_token", "account not found"))?;
+        let profiles = self
+            .profile_repository
+            .list_with_keys(
+                account.account_id.as_str(),
+                &vec!["nickname".to_string(), "avatar".to_string(), "email".to_string(), "phone".to_string()],
+            )
+            .await?;
+        let profile = |key: &str| {
+            profiles
+                .iter()
+                .find(|p| p.profile_key == key)
+                .and_then(|p| p.profile_value.clone())
+                .filter(|v| !v.is_empty())
+        };
+        let mut info = UserInfoVo {
+            sub: account.account_id.clone(),
+            ..Default::default()
+        };
+        if oauth_utils::has_scope(scope, "profile") {
+            info.name = profile("nickname").or_else(|| Some(account.account_name.clone()));
+            info.preferred_username = Some(account.account_name.clone());
+            info.picture = profile("avatar");
+        }
+        // 登录标识本身为邮箱/手机号时视为已验证
+        if oauth_utils::has_scope(scope, "email") {
+            let verified = account.account_type == "email";
+            info.email = if verified { Some(account.account_name.clone()) } else { profile("email") };
+            info.email_verified = info.email.as_ref().map(|_| verified);
+        }
+        if oauth_utils::has_scope(scope, "phone") {
+            let verified = account.account_type == "phone";
+            info.phone_number = if verified { Some(account.account_name.clone()) } else { profile("phone") };
+            info.phone_number_verified = info.phone_number.as_ref().map(|_| verified);
+        }
+        Ok(info)
+    }
+}
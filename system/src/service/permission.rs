@@ -0,0 +1,28 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use laurel_actix::handler::TokenResult;
+use laurel_actix::permission::PermissionHandler;
+use crate::repository::role::RoleRepository;
+
+/// 按账户所属角色的菜单授权解析权限码
+#[derive(Clone, Debug)]
+pub struct PermissionService {
+    role_repository: Arc<RoleRepository>,
+}
+
+impl PermissionService {
+    pub fn new(role_repository: Arc<RoleRepository>) -> Self {
+        Self { role_repository }
+    }
+}
+
+impl PermissionHandler for PermissionService {
+    fn permissions(&self, account_id: &str) -> TokenResult<Arc<HashSet<String>>> {
+        let repository = Arc::clone(&self.role_repository);
+        let account_id = account_id.to_string();
+        Box::pin(async move {
+            let authorities = repository.list_authorities_by_account(account_id.as_str()).await?;
+            Ok(Arc::new(authorities.into_iter().collect()))
+        })
+    }
+}
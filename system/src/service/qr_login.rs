@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use bon::Builder;
+use laurel_actix::handler::Token;
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::QrLoginConfig;
+use crate::model::account::{AccountEntity, LoginStep};
+use crate::model::qr_login::{QrChallenge, QrLoginConfirmReq, QrLoginCreateReq, QrLoginPollReq, QrLoginScanReq, QrLoginVo, QrScanVo, QrStatus, QrStatusVo};
+use crate::repository::account::AccountRepository;
+use crate::service::account::AccountService;
+use crate::service::audit::AuditService;
+use crate::utils::{oauth_utils, token_utils};
+
+static CHALLENGE_PREFIX: &str = "laurel:system:login:qr:";
+/// 仅当挑战仍为读取时的内容才写入, 保留剩余有效期; 返回是否写入
+static CAS_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+    return 0
+end
+local ttl = redis.call('PTTL', KEYS[1])
+if ttl <= 0 then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[2], 'PX', ttl)
+return 1
+"#;
+
+/// 扫码登录: 桌面端发起挑战并轮询状态, 已登录的手机端扫码确认后为桌面端签发票据
+#[derive(Debug, Builder)]
+pub struct QrLoginService {
+    redis: Redis,
+    account_repository: Arc<AccountRepository>,
+    account_service: Arc<AccountService>,
+    audit_service: Arc<AuditService>,
+    config: QrLoginConfig,
+}
+
+impl QrLoginService {
+    fn key(challenge: &str) -> String {
+        format!("{}{}", CHALLENGE_PREFIX, challenge)
+    }
+
+    /// 挑战及其原始内容, 原始内容用于更新时比较
+    async fn load(&self, challenge: &str) -> service::Result<Option<(String, QrChallenge)>> {
+        match self.redis.get_optional::<String>(Self::key(challenge).as_str()).await? {
+            Some(c) => {
+                let qr = serde_json::from_str::<QrChallenge>(c.as_str())?;
+                Ok(Some((c, qr)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// 更新状态, 读取后已被其他请求修改或已过期时失败
+    async fn update(&self, challenge: &str, loaded: String, qr: &QrChallenge) -> service::Result<()> {
+        let updated = self
+            .redis
+            .eval::<i64>(CAS_SCRIPT, vec![Self::key(challenge)], vec![loaded, serde_json::to_string(qr)?])
+            .await?;
+        if updated != 1 {
+            return Err(Error::msg("qr login state changed, please retry"));
+        }
+        Ok(())
+    }
+
+    /// 桌面端取得挑战, 凭 poll_token 查询状态
+    async fn polled(&self, req: &QrLoginPollReq) -> service::Result<Option<QrChallenge>> {
+        match self.load(req.challenge.as_str()).await?.map(|(_, qr)| qr) {
+            Some(qr) if oauth_utils::secret_matches(req.poll_token.as_str(), qr.poll_hash.as_str()) => Ok(Some(qr)),
+            Some(_) => Err(Error::msg("qr login poll token mismatch")),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create(&self, req: &QrLoginCreateReq, ua: Option<&str>, ip: String) -> service::Result<QrLoginVo> {
+        let challenge = token_utils::token();
+        let poll_token = format!("{}{}", token_utils::token(), token_utils::token());
+        let qr = QrChallenge {
+            status: QrStatus::Pending,
+            poll_hash: oauth_utils::secret_hash(poll_token.as_str()),
+            account_id: None,
+            client: req.client.clone(),
+            ua: ua.map(|u| u.to_string()),
+            ip,
+        };
+        self.redis
+            .set_with_expire(
+                Self::key(challenge.as_str()).as_str(),
+                serde_json::to_string(&qr)?,
+                Duration::from_secs(self.config.expire),
+            )
+            .await?;
+        Ok(QrLoginVo {
+            challenge,
+            poll_token,
+            expires_in: self.config.expire,
+        })
+    }
+
+    pub async fn status(&self, req: &QrLoginPollReq) -> service::Result<QrStatusVo> {
+        let status = self
+            .polled(req)
+            .await?
+            .map(|qr| qr.status)
+            .unwrap_or(QrStatus::Expired);
+        Ok(QrStatusVo { status })
+    }
+
+    fn check_session(token: &Token) -> service::Result<()> {
+        if !token.is_session() {
+            return Err(Error::msg("qr login must be confirmed by a login session"));
+        }
+        Ok(())
+    }
+
+    /// 手机端扫码, 返回桌面端信息供用户核对
+    pub async fn scan(&self, token: &Token, req: &QrLoginScanReq) -> service::Result<QrScanVo> {
+        Self::check_session(token)?;
+        let (loaded, mut qr) = self
+            .load(req.challenge.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("qr login expired"))?;
+        let rescan = qr.status == QrStatus::Scanned && qr.account_id.as_deref() == Some(token.account_id.as_str());
+        if qr.status != QrStatus::Pending && !rescan {
+            return Err(Error::msg("qr login already scanned"));
+        }
+        qr.status = QrStatus::Scanned;
+        qr.account_id = Some(token.account_id.clone());
+        self.update(req.challenge.as_str(), loaded, &qr).await?;
+        Ok(QrScanVo {
+            ua: qr.ua,
+            ip: qr.ip,
+            host: qr.client.host,
+            endpoint: qr.client.endpoint,
+        })
+    }
+
+    /// 手机端确认或拒绝, 只有扫码的账户可以操作
+    pub async fn confirm(&self, token: &Token, req: &QrLoginConfirmReq, ip: String) -> service::Result<()> {
+        Self::check_session(token)?;
+        let (loaded, mut qr) = self
+            .load(req.challenge.as_str())
+            .await?
+            .ok_or_else(|| Error::msg("qr login expired"))?;
+        if qr.status != QrStatus::Scanned || qr.account_id.as_deref() != Some(token.account_id.as_str()) {
+            return Err(Error::msg("qr login not scanned by current account"));
+        }
+        qr.status = if req.approve { QrStatus::Confirmed } else { QrStatus::Denied };
+        self.update(req.challenge.as_str(), loaded, &qr).await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            if req.approve { "qr_login_confirm" } else { "qr_login_deny" },
+            req.challenge.as_str(),
+            Some(format!("desktop ip: {}", qr.ip)),
+            Some(ip),
+        );
+        Ok(())
+    }
+
+    /// 桌面端在确认后完成登录, 挑战随即作废
+    pub async fn login(&self, req: &QrLoginPollReq, ua: Option<&str>, ip: String) -> service::Result<(AccountEntity, LoginStep)> {
+        let qr = self
+            .polled(req)
+            .await?
+            .ok_or_else(|| Error::msg("qr login expired"))?;
+        if qr.status != QrStatus::Confirmed {
+            return Err(Error::msg("qr login not confirmed"));
+        }
+        // 并发完成登录时只有一个请求能取到挑战
+        let qr = match self.redis.get_del_optional::<String>(Self::key(req.challenge.as_str()).as_str()).await? {
+            Some(c) => serde_json::from_str::<QrChallenge>(c.as_str())?,
+            None => return Err(Error::msg("qr login expired")),
+        };
+        let account_id = qr.account_id.clone().unwrap_or_default();
+        let resolve = async {
+            self.account_repository
+                .find_by_account_id(account_id.as_str())
+                .await?
+                .ok_or_else(|| Error::msg("account not found"))
+        };
+        self.account_service
+            .login_trusted("qr", account_id.as_str(), false, &qr.client, ua, ip, resolve)
+            .await
+    }
+}
@@ -0,0 +1,190 @@
+use crate::model::role::{
+    AccountRolesReq, InsertableRole, Role, RoleCreateReq, RoleMenusReq, RoleAccountsReq, RoleQueryReq,
+    RoleStatus, RoleUpdateReq, UpdatableRole,
+};
+use crate::repository::role::RoleRepository;
+use crate::service::audit::AuditService;
+use anyhow::Error;
+use bon::Builder;
+use chrono::Local;
+use laurel_actix::handler::Token;
+use laurel_actix::permission::CachedPermissionHandler;
+use laurel_actix::types::service;
+use laurel_common::types::{HappyEnum, Pagination};
+use laurel_id_api::id::IdApi;
+use std::sync::Arc;
+
+#[derive(Debug, Builder)]
+pub struct RoleService {
+    role_repository: Arc<RoleRepository>,
+    audit_service: Arc<AuditService>,
+    permission_cache: Arc<CachedPermissionHandler>,
+    id_api: IdApi,
+}
+
+impl RoleService {
+    pub async fn find(&self, role_id: &str) -> service::Result<Option<Role>> {
+        self.role_repository.find(role_id).await
+    }
+
+    pub async fn page(&self, req: &RoleQueryReq, page: u32, size: u32) -> service::Result<Pagination<Role>> {
+        self.role_repository.page(req, page, size).await
+    }
+
+    /// 存在且未删除的角色
+    async fn find_available(&self, role_id: &str) -> service::Result<Role> {
+        match self.role_repository.find(role_id).await? {
+            Some(role) if role.role_status != "deleted" => Ok(role),
+            Some(_) => Err(Error::msg("当前角色已删除")),
+            None => Err(Error::msg("当前角色不存在")),
+        }
+    }
+
+    pub async fn create(&self, operator: &Token, req: &RoleCreateReq, ip: String) -> service::Result<Role> {
+        if req.role_name.trim().is_empty() {
+            return Err(Error::msg("角色名称不能为空"));
+        }
+        let role_status = match &req.role_status {
+            Some(x) if !x.is_empty() => x.as_str(),
+            _ => "open",
+        };
+        if !RoleStatus::valid(role_status) {
+            return Err(Error::msg("角色状态不正确"));
+        }
+        let id = self.id_api.id().await?;
+        let now = Local::now().naive_local();
+        let role = self
+            .role_repository
+            .save(&InsertableRole {
+                role_id: id.as_str(),
+                role_name: req.role_name.as_str(),
+                role_type: req.role_type.as_str(),
+                weight: req.weight,
+                role_status,
+                cts: now,
+                uts: now,
+            })
+            .await?;
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "role_create",
+            role.role_id.as_str(),
+            Some(role.role_name.clone()),
+            Some(ip),
+        );
+        Ok(role)
+    }
+
+    pub async fn update(&self, operator: &Token, req: RoleUpdateReq, ip: String) -> service::Result<Role> {
+        self.find_available(req.role_id.as_str()).await?;
+        if let Some(status) = &req.role_status
+            && !RoleStatus::valid(status) {
+            return Err(Error::msg("角色状态不正确"));
+        }
+        let updatable = UpdatableRole::builder()
+            .maybe_role_name(req.role_name)
+            .maybe_role_type(req.role_type)
+            .maybe_weight(req.weight)
+            .maybe_role_status(req.role_status)
+            .uts(Local::now().naive_local())
+            .build();
+        let role = self
+            .role_repository
+            .update(req.role_id.as_str(), &updatable)
+            .await?
+            .ok_or_else(|| Error::msg("当前角色不存在, 更新失败"))?;
+        // 角色状态变化影响其下全部账户
+        self.permission_cache.clear();
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "role_update",
+            role.role_id.as_str(),
+            serde_json::to_string(&updatable).ok(),
+            Some(ip),
+        );
+        Ok(role)
+    }
+
+    pub async fn list_account_ids(&self, role_id: &str) -> service::Result<Vec<String>> {
+        self.role_repository.list_account_ids(role_id).await
+    }
+
+    pub async fn add_accounts(&self, operator: &Token, req: &RoleAccountsReq, ip: String) -> service::Result<usize> {
+        self.find_available(req.role_id.as_str()).await?;
+        let rows = self
+            .role_repository
+            .add_accounts(req.role_id.as_str(), &req.account_ids)
+            .await?;
+        req.account_ids.iter().for_each(|id| self.permission_cache.evict(id));
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "role_account_add",
+            req.role_id.as_str(),
+            Some(AuditService::summarize_ids(&req.account_ids)),
+            Some(ip),
+        );
+        Ok(rows)
+    }
+
+    pub async fn remove_accounts(&self, operator: &Token, req: &RoleAccountsReq, ip: String) -> service::Result<usize> {
+        let rows = self
+            .role_repository
+            .remove_accounts(req.role_id.as_str(), &req.account_ids)
+            .await?;
+        req.account_ids.iter().for_each(|id| self.permission_cache.evict(id));
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "role_account_remove",
+            req.role_id.as_str(),
+            Some(AuditService::summarize_ids(&req.account_ids)),
+            Some(ip),
+        );
+        Ok(rows)
+    }
+
+    pub async fn list_account_roles(&self, account_id: &str) -> service::Result<Vec<Role>> {
+        self.role_repository.list_by_account(account_id).await
+    }
+
+    pub async fn replace_account_roles(&self, operator: &Token, req: &AccountRolesReq, ip: String) -> service::Result<usize> {
+        let roles = self.role_repository.list_by_ids(&req.role_ids).await?;
+        if roles.iter().any(|r| r.role_status == "deleted")
+            || req.role_ids.iter().any(|id| !roles.iter().any(|r| &r.role_id == id)) {
+            return Err(Error::msg("角色不存在或已删除"));
+        }
+        let rows = self
+            .role_repository
+            .replace_account_roles(req.account_id.as_str(), &req.role_ids)
+            .await?;
+        self.permission_cache.evict(req.account_id.as_str());
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "account_role_replace",
+            req.account_id.as_str(),
+            Some(AuditService::summarize_ids(&req.role_ids)),
+            Some(ip),
+        );
+        Ok(rows)
+    }
+
+    pub async fn list_menu_ids(&self, role_id: &str) -> service::Result<Vec<String>> {
+        self.role_repository.list_menu_ids(role_id).await
+    }
+
+    pub async fn grant_menus(&self, operator: &Token, req: &RoleMenusReq, ip: String) -> service::Result<usize> {
+        self.find_available(req.role_id.as_str()).await?;
+        let rows = self
+            .role_repository
+            .replace_menus(req.role_id.as_str(), &req.menu_ids)
+            .await?;
+        self.permission_cache.clear();
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "role_menu_grant",
+            req.role_id.as_str(),
+            Some(AuditService::summarize_ids(&req.menu_ids)),
+            Some(ip),
+        );
+        Ok(rows)
+    }
+}
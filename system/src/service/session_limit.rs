@@ -0,0 +1,207 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use bon::Builder;
+use tracing::{error, info};
+use laurel_actix::error::BizError;
+use laurel_actix::types::service;
+use laurel_redis::Redis;
+use crate::SessionLimitConfig;
+use crate::model::ticket::Ticket;
+use crate::repository;
+use crate::service::audit::AuditService;
+use crate::service::token::TokenService;
+use crate::utils::{codes, token_utils};
+
+static LOCK_PREFIX: &str = "laurel:system:session:lock:";
+/// 锁的最长持有时间, 进程异常退出时自动释放
+static LOCK_EXPIRE: Duration = Duration::from_secs(10);
+static LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+static LOCK_RETRIES: u32 = 100;
+
+/// 仅释放自己持有的锁. KEYS: 锁; ARGV: 持有者id
+static UNLOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// 同时在线会话数限制: 按账户、登录方式与终端类型分别计数
+#[derive(Debug, Builder)]
+pub struct SessionLimiter {
+    redis: Redis,
+    ticket_repository: Arc<repository::ticket::Repository>,
+    token_service: Arc<TokenService>,
+    audit_service: Arc<AuditService>,
+    config: SessionLimitConfig,
+}
+
+/// 适用于本次登录的上限及其计数范围(登录方式, 终端类型), 0 表示不限制
+fn limits<'a>(config: &SessionLimitConfig, login_type: &'a str, device_class: &'a str) -> [(usize, Option<&'a str>, Option<&'a str>); 3] {
+    [
+        (config.max_sessions, None, None),
+        (config.login_types.get(login_type).copied().unwrap_or(0), Some(login_type), None),
+        (config.device_classes.get(device_class).copied().unwrap_or(0), None, Some(device_class)),
+    ]
+}
+
+/// 为新会话腾出名额需要下线的会话, 按签发时间从早到晚选取
+fn overflow(config: &SessionLimitConfig, mut tickets: Vec<Ticket>, login_type: &str, device_class: &str) -> Vec<Ticket> {
+    // 代登录与 OAuth2 客户端的会话不占用名额
+    tickets.retain(|t| t.impersonator.is_empty() && t.client_id.is_empty());
+    tickets.sort_by_key(|t| t.cts);
+    let mut overflow: Vec<Ticket> = vec![];
+    for (limit, scope_type, scope_class) in limits(config, login_type, device_class) {
+        if limit == 0 {
+            continue;
+        }
+        let in_scope = |t: &Ticket| {
+            scope_type.is_none_or(|s| t.login_type == s) && scope_class.is_none_or(|s| t.device_class == s)
+        };
+        let count = tickets.iter().filter(|t| in_scope(t)).count();
+        if count < limit {
+            continue;
+        }
+        let mut excess = count + 1 - limit;
+        let mut i = 0;
+        while excess > 0 && i < tickets.len() {
+            if in_scope(&tickets[i]) {
+                overflow.push(tickets.remove(i));
+                excess -= 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    overflow
+}
+
+impl SessionLimiter {
+    fn enabled(&self) -> bool {
+        self.config.max_sessions > 0
+            || self.config.login_types.values().any(|&l| l > 0)
+            || self.config.device_classes.values().any(|&l| l > 0)
+    }
+
+    /// 按账户加锁, 使计数、签发与下线旧会话串行执行, 避免并发登录同时通过检查; 未配置上限时不加锁
+    pub async fn lock(&self, account_id: &str) -> service::Result<Option<String>> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let key = format!("{}{}", LOCK_PREFIX, account_id);
+        let owner = token_utils::token();
+        for _ in 0..LOCK_RETRIES {
+            if self.redis.set_nx_with_expire(key.as_str(), owner.as_str(), LOCK_EXPIRE).await? {
+                return Ok(Some(owner));
+            }
+            tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+        }
+        Err(Error::msg("login in progress, please retry later"))
+    }
+
+    pub async fn unlock(&self, account_id: &str, owner: Option<String>) {
+        let Some(owner) = owner else {
+            return;
+        };
+        let key = format!("{}{}", LOCK_PREFIX, account_id);
+        if let Err(err) = self.redis.eval::<i64>(UNLOCK_SCRIPT, vec![key], vec![owner]).await {
+            error!("unlock session limit of account: {} error: {}", account_id, err);
+        }
+    }
+
+    /// 签发前检查: reject 策略超限时拒绝登录, 否则返回待下线的会话
+    pub async fn admit(&self, account_id: &str, login_type: &str, device_class: &str) -> service::Result<Vec<Ticket>> {
+        let tickets = self.ticket_repository.list_active_by_account(account_id).await?;
+        let overflow = overflow(&self.config, tickets, login_type, device_class);
+        if !overflow.is_empty() && self.config.policy == "reject" {
+            info!("account: {} exceeds session limit, login rejected", account_id);
+            return Err(BizError::new(codes::SESSION_LIMIT, "在线会话数已达上限, 请先退出其他设备").into());
+        }
+        Ok(overflow)
+    }
+
+    /// 新会话签发后下线超出上限的旧会话
+    pub async fn replace(&self, overflow: Vec<Ticket>, current: &Ticket) {
+        for ticket in overflow {
+            if let Err(err) = self.token_service.revoke(ticket.ticket_id.as_str(), "replaced").await {
+                error!("replace ticket: {} error: {}", ticket.ticket_id, err);
+                continue;
+            }
+            self.audit_service.record(
+                current.account_id.as_str(),
+                "session_replace",
+                ticket.ticket_id.as_str(),
+                Some(format!(
+                    "replaced by ticket: {}, login type: {}, device class: {}",
+                    current.ticket_id, current.login_type, current.device_class,
+                )),
+                None,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::{Duration, Local};
+
+    fn ticket(ticket_id: &str, login_type: &str, device_class: &str, minutes: i64) -> Ticket {
+        let cts = Local::now().naive_local() - Duration::minutes(minutes);
+        Ticket {
+            id: 0,
+            ticket_id: ticket_id.to_string(),
+            token: String::new(),
+            refresh_id: String::new(),
+            account_id: "1".to_string(),
+            login_type: login_type.to_string(),
+            ticket_state: "normal".to_string(),
+            impersonator: String::new(),
+            device_class: device_class.to_string(),
+            client_id: String::new(),
+            scope: String::new(),
+            cts,
+            uts: cts,
+            ets: cts + Duration::days(7),
+        }
+    }
+
+    fn replaced(config: &SessionLimitConfig, login_type: &str, device_class: &str) -> Vec<String> {
+        let mut impersonated = ticket("e", "impersonate", "", 50);
+        impersonated.impersonator = "2".to_string();
+        let tickets = vec![
+            ticket("a", "name", "web", 30),
+            ticket("b", "email", "app", 20),
+            ticket("c", "name", "web", 10),
+            ticket("d", "name", "app", 40),
+            impersonated,
+        ];
+        overflow(config, tickets, login_type, device_class)
+            .into_iter()
+            .map(|t| t.ticket_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_overflow() {
+        let config = SessionLimitConfig::default();
+        assert!(replaced(&config, "name", "web").is_empty());
+        let config = SessionLimitConfig { max_sessions: 3, ..Default::default() };
+        assert_eq!(replaced(&config, "name", "web"), vec!["d", "a"]);
+        let config = SessionLimitConfig {
+            max_sessions: 4,
+            device_classes: HashMap::from([("app".to_string(), 1)]),
+            ..Default::default()
+        };
+        assert_eq!(replaced(&config, "name", "web"), vec!["d"]);
+        assert_eq!(replaced(&config, "name", "app"), vec!["d", "b"]);
+        let config = SessionLimitConfig {
+            login_types: HashMap::from([("name".to_string(), 2)]),
+            ..Default::default()
+        };
+        assert_eq!(replaced(&config, "name", "web"), vec!["d", "a"]);
+        assert!(replaced(&config, "email", "app").is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use anyhow::Error;
+use bon::Builder;
+use tracing::warn;
+use laurel_actix::handler::Token;
+use laurel_actix::types::service;
+use laurel_common::types::api;
+use laurel_logs_api::logs::{LogApi, LoginLogTicketsReqBo};
+use crate::model::ticket::{AccountTicketRevokeReq, SessionKickReq, SessionVo, Ticket, TicketRevokeReq};
+use crate::repository;
+use crate::service::audit::AuditService;
+use crate::service::token::TokenService;
+
+#[derive(Debug, Builder)]
+pub struct TicketService {
+    token_service: Arc<TokenService>,
+    ticket_repository: Arc<repository::ticket::Repository>,
+    log_api: Arc<LogApi>,
+    audit_service: Arc<AuditService>,
+}
+
+impl TicketService {
+    pub async fn logout(&self, token: &Token, ip: String) -> service::Result<Option<Ticket>> {
+        let ticket = self.token_service.revoke(token.ticket_id.as_str(), "logout").await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "logout",
+            token.ticket_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(ticket)
+    }
+
+    /// 账户的在线会话, 终端信息取自登录日志; 日志服务不可用时仅返回票据信息
+    pub async fn sessions(&self, account_id: &str, current_ticket_id: &str) -> service::Result<Vec<SessionVo>> {
+        let tickets = self.ticket_repository.list_active_by_account(account_id).await?;
+        if tickets.is_empty() {
+            return Ok(vec![]);
+        }
+        let req = LoginLogTicketsReqBo {
+            ticket_ids: tickets.iter().map(|t| t.ticket_id.clone()).collect(),
+        };
+        let result = self.log_api.list_login_logs_by_tickets(&req).await.unwrap_or_else(api::ApiResult::from);
+        if !result.is_successful() {
+            warn!("list login logs of account: {} error: {}", account_id, result.message);
+        }
+        let mut logs = result.data.unwrap_or_default();
+        Ok(tickets
+            .into_iter()
+            .map(|ticket| {
+                let log = logs
+                    .iter()
+                    .position(|l| l.ticket_id == ticket.ticket_id)
+                    .map(|i| logs.swap_remove(i));
+                SessionVo::of(ticket, log, current_ticket_id)
+            })
+            .collect())
+    }
+
+    /// 下线当前账户的某个会话
+    pub async fn kick(&self, token: &Token, req: &SessionKickReq, ip: String) -> service::Result<Option<Ticket>> {
+        match self.ticket_repository.find(req.ticket_id.as_str()).await? {
+            Some(t) if t.account_id == token.account_id => {},
+            _ => return Err(Error::msg("会话不存在")),
+        }
+        let ticket = self.token_service.revoke(req.ticket_id.as_str(), "kicked").await?;
+        self.audit_service.record(
+            token.account_id.as_str(),
+            "session_kick",
+            req.ticket_id.as_str(),
+            None,
+            Some(ip),
+        );
+        Ok(ticket)
+    }
+
+    pub async fn revoke(&self, operator: &Token, req: &TicketRevokeReq, ip: String) -> service::Result<Option<Ticket>> {
+        let ticket = self.token_service.revoke(req.ticket_id.as_str(), "revoked").await?;
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "ticket_revoke",
+            req.ticket_id.as_str(),
+            req.reason.clone(),
+            Some(ip),
+        );
+        Ok(ticket)
+    }
+
+    pub async fn revoke_account(&self, operator: &Token, req: &AccountTicketRevokeReq, ip: String) -> service::Result<Vec<Ticket>> {
+        let tickets = self.token_service.revoke_account(req.account_id.as_str(), "revoked").await?;
+        self.audit_service.record(
+            operator.account_id.as_str(),
+            "account_ticket_revoke",
+            req.account_id.as_str(),
+            Some(format!("revoked: {}, reason: {}", tickets.len(), req.reason.as_deref().unwrap_or(""))),
+            Some(ip),
+        );
+        Ok(tickets)
+    }
+}
@@ -1,72 +1,352 @@
-use tracing::log::info;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{Local, NaiveDateTime, Utc};
+use tracing::log::{info, warn};
+use laurel_actix::error::BizError;
 use laurel_actix::handler::{Token, TokenHandler, TokenResult};
 use laurel_actix::types::service;
 use laurel_redis::Redis;
-use crate::model::ticket::{JwtPayload};
+use crate::model::account::AccountState;
+use crate::model::ticket::{CachedTicket, IssuedTicket, JwtPayload, Ticket};
+use crate::repository;
+use crate::repository::account::AccountRepository;
+use crate::utils::jwt_utils::{self, JwtKeys};
+use crate::utils::{codes, oauth_utils, token_utils};
+
+static TICKET_CACHE_PREFIX: &str = "laurel:system:ticket:";
+static ACCOUNT_STATE_PREFIX: &str = "laurel:system:account:state:";
+static TOKEN_TYPE_ACCESS: &str = "access";
+static TOKEN_TYPE_REFRESH: &str = "refresh";
+static API_KEY_USED_PREFIX: &str = "laurel:system:api_key:used:";
+/// OAuth2 客户端令牌唯一可访问的接口
+static OAUTH_USERINFO_PATH: &str = "/api/system/oauth2/userinfo";
+/// 最近使用时间的记录间隔
+static API_KEY_TOUCH_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Debug)]
 pub struct TokenService {
     redis: Redis,
+    ticket_repository: Arc<repository::ticket::Repository>,
+    api_key_repository: Arc<repository::api_key::Repository>,
+    account_repository: Arc<AccountRepository>,
     exclude_paths: Vec<String>,
     exclude_start_path: Vec<String>,
-    secret: String,
+    jwt_keys: Arc<JwtKeys>,
+    ticket_expire: Duration,
+    access_expire: Duration,
 }
 
 impl TokenService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         redis: Redis,
+        ticket_repository: Arc<repository::ticket::Repository>,
+        api_key_repository: Arc<repository::api_key::Repository>,
+        account_repository: Arc<AccountRepository>,
         exclude_paths: Vec<String>,
         exclude_start_path: Vec<String>,
-        secret: String,
+        jwt_keys: Arc<JwtKeys>,
+        ticket_expire: Duration,
+        access_expire: Duration,
     ) -> Self {
         Self {
             redis,
+            ticket_repository,
+            api_key_repository,
+            account_repository,
             exclude_paths,
             exclude_start_path,
-            secret,
+            jwt_keys,
+            ticket_expire,
+            access_expire,
+        }
+    }
+
+    /// 新签发票据的过期时间
+    pub fn expire_at(&self, from: NaiveDateTime) -> NaiveDateTime {
+        from + self.ticket_expire
+    }
+
+    /// access token 有效期(秒)
+    pub fn access_expire(&self) -> u64 {
+        self.access_expire.as_secs()
+    }
+
+    /// 验签公钥, 供其他服务本地校验令牌
+    pub fn jwks(&self) -> &jsonwebtoken::jwk::JwkSet {
+        self.jwt_keys.jwks()
+    }
+
+    /// exp 为 Unix 时间戳(秒)
+    fn payload(&self, ticket_id: &str, typ: &str, jti: Option<String>, exp: i64) -> JwtPayload {
+        JwtPayload {
+            ticket_id: ticket_id.to_string(),
+            typ: typ.to_string(),
+            jti,
+            iss: self.jwt_keys.issuer().to_string(),
+            aud: self.jwt_keys.audience().clone(),
+            iat: Utc::now().timestamp() as u64,
+            exp: exp as u64,
         }
     }
 
+    /// 签发短期 access token
     pub fn make(&self, ticket_id: &str) -> service::Result<String>{
-        let token = jsonwebtoken::encode(
-            &jsonwebtoken::Header::default(),
-            &JwtPayload {
-                ticket_id: ticket_id.to_string(),
-            },
-            &jsonwebtoken::EncodingKey::from_secret(self.secret.as_bytes()),
-        )?;
-        Ok(token)
-    }
-
-    pub async fn parse(&self, token: &str) -> service::Result<String>{
-        let ticket_id = jsonwebtoken::decode::<JwtPayload>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(self.secret.as_bytes()),
-            &jsonwebtoken::Validation::default(),
-        )?
-        .claims
-        .ticket_id;
-        Ok(ticket_id)
+        let exp = (Utc::now() + self.access_expire).timestamp();
+        self.jwt_keys.encode(&self.payload(ticket_id, TOKEN_TYPE_ACCESS, None, exp))
+    }
+
+    /// 签发 refresh token, 有效期与 ticket.ets 一致
+    pub fn make_refresh(&self, ticket_id: &str, refresh_id: &str, ets: NaiveDateTime) -> service::Result<String>{
+        self.jwt_keys.encode(&self.payload(ticket_id, TOKEN_TYPE_REFRESH, Some(refresh_id.to_string()), jwt_utils::timestamp(ets)))
+    }
+
+    /// 校验签名、签发方、受众、过期时间与类型
+    pub fn decode(&self, token: &str, typ: &str) -> service::Result<JwtPayload>{
+        let payload = self.jwt_keys.decode::<JwtPayload>(token)?;
+        if payload.typ != typ {
+            return Err(anyhow::Error::msg(format!("token type mismatch: {}", payload.typ)));
+        }
+        Ok(payload)
+    }
+
+    fn cache_key(ticket_id: &str) -> String {
+        format!("{}{}", TICKET_CACHE_PREFIX, ticket_id)
+    }
+
+    /// 缓存会话, 过期时间与 ticket.ets 一致
+    pub async fn cache(&self, ticket: &Ticket) -> service::Result<()>{
+        let ttl = (ticket.ets - Local::now().naive_local()).num_seconds();
+        if ticket.ticket_state != "normal" || ttl <= 0 {
+            return Ok(());
+        }
+        let payload = serde_json::to_string(&CachedTicket::from(ticket))?;
+        self.redis
+            .set_with_expire(Self::cache_key(ticket.ticket_id.as_str()).as_str(), payload, Duration::from_secs(ttl as u64))
+            .await?;
+        Ok(())
+    }
+
+    /// 移除会话缓存
+    pub async fn evict(&self, ticket_id: &str) -> service::Result<()>{
+        self.redis.del(Self::cache_key(ticket_id).as_str()).await?;
+        Ok(())
+    }
+
+    /// 结束单个会话
+    pub async fn revoke(&self, ticket_id: &str, ticket_state: &str) -> service::Result<Option<Ticket>>{
+        let ticket = self.ticket_repository.update_state(ticket_id, ticket_state).await?;
+        self.evict(ticket_id).await?;
+        Ok(ticket)
+    }
+
+    /// 结束账户下的全部会话
+    pub async fn revoke_account(&self, account_id: &str, ticket_state: &str) -> service::Result<Vec<Ticket>>{
+        let tickets = self.ticket_repository.update_state_by_account(account_id, ticket_state).await?;
+        for ticket in &tickets {
+            self.evict(ticket.ticket_id.as_str()).await?;
+        }
+        Ok(tickets)
+    }
+
+    /// 结束 OAuth2 客户端签发的全部会话
+    pub async fn revoke_client(&self, client_id: &str, ticket_state: &str) -> service::Result<Vec<Ticket>>{
+        let tickets = self.ticket_repository.update_state_by_client(client_id, ticket_state).await?;
+        for ticket in &tickets {
+            self.evict(ticket.ticket_id.as_str()).await?;
+        }
+        Ok(tickets)
+    }
+
+    /// 轮换 refresh token 并签发新的 access token.
+    /// 已使用过的 refresh token 再次出现时视为泄露, 吊销整个票据.
+    /// client_id 为空时只续期本人登录的票据, OAuth2 票据须经 /oauth2/token 由签发的客户端续期
+    pub async fn refresh(&self, refresh_token: &str, client_id: &str) -> service::Result<IssuedTicket>{
+        let payload = self.decode(refresh_token, TOKEN_TYPE_REFRESH)?;
+        let refresh_id = payload.jti.unwrap_or_default();
+        let ticket = self
+            .ticket_repository
+            .find(payload.ticket_id.as_str())
+            .await?
+            .ok_or_else(|| anyhow::Error::msg("ticket not found"))?;
+        Self::check_replaced(&ticket)?;
+        if ticket.client_id != client_id {
+            return Err(anyhow::Error::msg("ticket client mismatch"));
+        }
+        if ticket.ticket_state != "normal" || ticket.ets <= Local::now().naive_local() {
+            return Err(anyhow::Error::msg("ticket expired"));
+        }
+        // 代登录票据限时, 不可续期
+        if !ticket.impersonator.is_empty() {
+            return Err(anyhow::Error::msg("impersonation ticket cannot be refreshed"));
+        }
+        if refresh_id.is_empty() || ticket.refresh_id != refresh_id {
+            return Err(self.revoke_reused(ticket.ticket_id.as_str()).await);
+        }
+
+        let now = Local::now().naive_local();
+        let ets = self.expire_at(now);
+        let new_refresh_id = token_utils::token();
+        let token = self.make(ticket.ticket_id.as_str())?;
+        let refresh_token = self.make_refresh(ticket.ticket_id.as_str(), new_refresh_id.as_str(), ets)?;
+        let ticket = match self
+            .ticket_repository
+            .rotate(ticket.ticket_id.as_str(), refresh_id.as_str(), new_refresh_id.as_str(), token.as_str(), ets)
+            .await? {
+            Some(t) => t,
+            // 并发请求已完成轮换
+            None => return Err(self.revoke_reused(ticket.ticket_id.as_str()).await),
+        };
+        self.cache(&ticket).await?;
+        Ok(IssuedTicket {
+            ticket,
+            refresh_token,
+            expires_in: self.access_expire(),
+        })
+    }
+
+    async fn revoke_reused(&self, ticket_id: &str) -> anyhow::Error{
+        warn!("ticket: {} refresh token reused, revoke ticket", ticket_id);
+        if let Err(err) = self.revoke(ticket_id, "revoked").await {
+            return err;
+        }
+        anyhow::Error::msg("refresh token reused")
+    }
+
+    /// 记录非正常状态的账户, 校验令牌时据此拒绝
+    pub async fn mark_account_state(&self, account_id: &str, account_state: &str) -> service::Result<()>{
+        let key = format!("{}{}", ACCOUNT_STATE_PREFIX, account_id);
+        if AccountState::reject_code(account_state).is_none() {
+            self.redis.del(key.as_str()).await?;
+        } else {
+            self.redis.set(key.as_str(), account_state.to_string()).await?;
+        }
+        Ok(())
+    }
+
+    async fn check_account_state(&self, account_id: &str) -> service::Result<()>{
+        let key = format!("{}{}", ACCOUNT_STATE_PREFIX, account_id);
+        if let Some(state) = self.redis.get::<Option<String>>(key.as_str()).await?
+            && let Some((code, message)) = AccountState::reject_code(state.as_str()) {
+            return Err(BizError::new(code, message).into());
+        }
+        Ok(())
+    }
+
+    /// 先查 redis 标记, 未标记时以账户表为准并补写标记, 避免 redis 数据丢失后放行停用账户
+    async fn check_account_state_db(&self, account_id: &str) -> service::Result<()>{
+        self.check_account_state(account_id).await?;
+        let account = self
+            .account_repository
+            .find_by_account_id(account_id)
+            .await?
+            .ok_or_else(|| anyhow::Error::msg("account not found"))?;
+        if let Some((code, message)) = AccountState::reject_code(account.account_state.as_str()) {
+            self.mark_account_state(account_id, account.account_state.as_str()).await?;
+            return Err(BizError::new(code, message).into());
+        }
+        Ok(())
+    }
+
+    /// 吊销账户下全部 API key
+    pub async fn revoke_api_keys(&self, account_id: &str) -> service::Result<usize>{
+        self.api_key_repository.revoke_account(account_id).await
+    }
+
+    /// 因超出在线会话数上限被下线的会话, 返回明确的状态码便于前端提示
+    fn check_replaced(ticket: &Ticket) -> service::Result<()>{
+        if ticket.ticket_state == "replaced" {
+            return Err(BizError::new(codes::SESSION_REPLACED, "账户已在其他设备登录, 当前会话已下线").into());
+        }
+        Ok(())
+    }
+
+    /// 校验token: 签名 -> 缓存 -> ticket表, 并拒绝非正常状态的账户
+    pub async fn validate(&self, token: &str) -> service::Result<Option<Token>>{
+        if let Some((key_id, secret)) = token_utils::parse_api_key(token) {
+            return self.validate_api_key(key_id, secret).await;
+        }
+        let ticket_id = match self.decode(token, TOKEN_TYPE_ACCESS) {
+            Ok(payload) => payload.ticket_id,
+            Err(err) => {
+                info!("decode token error: {}", err);
+                return Ok(None);
+            }
+        };
+        let cache = self.redis.get::<Option<String>>(Self::cache_key(ticket_id.as_str()).as_str()).await?;
+        // 无法解析的旧格式缓存按未命中处理, 由 ticket 表重建
+        if let Some(cached) = cache.and_then(|c| serde_json::from_str::<CachedTicket>(c.as_str()).ok()) {
+            if !cached.matches(token) {
+                warn!("ticket: {} token mismatch", ticket_id);
+                return Ok(None);
+            }
+            self.check_account_state(cached.token.account_id.as_str()).await?;
+            return Ok(Some(cached.token));
+        }
+
+        let ticket = match self.ticket_repository.find(ticket_id.as_str()).await? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        if ticket.token != token {
+            warn!("ticket: {} token mismatch", ticket.ticket_id);
+            return Ok(None);
+        }
+        // 账户停用时票据已被吊销, 先校验账户以返回明确的状态码
+        self.check_account_state_db(ticket.account_id.as_str()).await?;
+        Self::check_replaced(&ticket)?;
+        if ticket.ticket_state != "normal" || ticket.ets <= Local::now().naive_local() {
+            return Ok(None);
+        }
+        self.cache(&ticket).await?;
+        Ok(Some(Token::from(&ticket)))
+    }
+
+    /// 校验 API key: 摘要 -> 状态与有效期 -> 账户状态, 令牌携带 key 限定的权限码
+    async fn validate_api_key(&self, key_id: &str, secret: &str) -> service::Result<Option<Token>>{
+        let key = match self.api_key_repository.find(key_id).await? {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+        if !oauth_utils::secret_matches(secret, key.key_hash.as_str()) {
+            warn!("api key: {} secret mismatch", key.key_id);
+            return Ok(None);
+        }
+        self.check_account_state_db(key.account_id.as_str()).await?;
+        let now = Local::now().naive_local();
+        if !key.active(now) {
+            return Ok(None);
+        }
+        // 同一 key 每个间隔内只记录一次, 失败不影响本次请求
+        let touch = self
+            .redis
+            .set_nx_with_expire(format!("{}{}", API_KEY_USED_PREFIX, key.key_id).as_str(), "1", API_KEY_TOUCH_INTERVAL)
+            .await;
+        let touch = match touch {
+            Ok(true) => self.api_key_repository.touch(key.key_id.as_str(), now).await.map(|_| ()),
+            Ok(false) => Ok(()),
+            Err(err) => Err(err.into()),
+        };
+        if let Err(err) = touch {
+            warn!("touch api key: {} error: {}", key.key_id, err);
+        }
+        Ok(Some(Token {
+            scopes: key.scope_list(),
+            api_key: Some(key.key_id),
+            account_id: key.account_id,
+            ticket_id: String::new(),
+            impersonator: None,
+            client_id: None,
+        }))
     }
 }
 
 impl TokenHandler for TokenService {
     fn parse(&'_ self, token: &str) -> TokenResult<Option<Token>> {
-        let redis = self.redis.clone();
+        let service = self.clone();
         let t = String::from(token);
         Box::pin(async move {
-            let cache = redis.get::<Option<String>>(t.as_str()).await?;
-            let payload = match cache {
-                Some(c) => {
-                    if c.is_empty() {
-                        None
-                    } else {
-                        Some(serde_json::from_str::<Token>(c.as_str())?)
-                    }
-                }
-                None => None,
-            };
-            Ok(payload)
+            Ok(service.validate(t.as_str()).await?)
         })
     }
 
@@ -79,4 +359,8 @@ impl TokenHandler for TokenService {
             .any(|path| url.starts_with(path));
         Box::pin(async move { Ok(exclude) })
     }
+
+    fn permit(&self, url: &str, token: &Token) -> bool {
+        !token.is_oauth() || url == OAUTH_USERINFO_PATH
+    }
 }
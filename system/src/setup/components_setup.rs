@@ -8,8 +8,11 @@ use crate::service::menu::MenuService;
 use crate::service::token::TokenService;
 use actix_web::web;
 use laurel_actix::handler::TokenHandler;
+use laurel_actix::permission::{CachedPermissionHandler, PermissionHandler};
 use laurel_id_api::id::IdApi;
 use laurel_middleware::reqwest_middle::RequestLoggingMiddleware;
+use laurel_middleware::signature::SigningMiddleware;
+use laurel_actix::signature::SignatureVerifier;
 use laurel_pg::DbPool;
 use laurel_redis::Redis;
 use std::sync::Arc;
@@ -18,8 +21,26 @@ use laurel_logs_api::logs::LogApi;
 use crate::repository::account::AccountRepository;
 use crate::repository::passport::PassportRepository;
 use crate::repository::profile::ProfileRepository;
+use crate::repository::role::RoleRepository;
 use crate::service::account::AccountService;
+use crate::service::audit::AuditService;
+use crate::service::code_sender;
+use crate::service::login_code::LoginCodeService;
+use crate::service::device::DeviceService;
+use crate::service::impersonation::ImpersonationService;
+use crate::service::oauth::OAuthService;
+use crate::service::federation::FederationService;
+use crate::service::api_key::ApiKeyService;
+use crate::service::qr_login::QrLoginService;
+use crate::service::login_guard::LoginGuard;
+use crate::service::session_limit::SessionLimiter;
+use crate::service::mfa::MfaService;
+use crate::service::permission::PermissionService;
 use crate::service::profile::ProfileService;
+use crate::service::role::RoleService;
+use crate::service::ticket::TicketService;
+use crate::utils::jwt_utils::JwtKeys;
+use crate::utils::password_policy::PasswordPolicy;
 
 #[allow(unused)]
 pub fn load_components(
@@ -45,25 +66,18 @@ pub fn load_components(
         //.brotli(false)
         .build()
         .expect("failed to build reqwest client");
-    let client = Arc::new(reqwest_middleware::ClientBuilder::new(request_client)
-        .with(RequestLoggingMiddleware)
-        .build()
-    );
-
-    let token_service: Arc<TokenService> = Arc::new(
-        TokenService::new(
-            redis.clone(),
-            vec!["/api/system/account/login".to_string()],
-            vec![
-                "/interface".to_string(),
-                "/swagger-ui".to_string(),
-                "/api-docs".to_string(),
-            ],
-            service_config.uc_config.secret.clone(),
-        )
-    );
-    let dyn_token_service: Arc<dyn TokenHandler> = Arc::clone(&token_service) as Arc<dyn TokenHandler>;
-    cfg.app_data(web::Data::new(dyn_token_service));
+    let mut client_builder = reqwest_middleware::ClientBuilder::new(request_client)
+        .with(RequestLoggingMiddleware);
+    if let Some(signature_config) = &service_config.signature_config {
+        client_builder = client_builder.with(
+            SigningMiddleware::new(signature_config.caller.clone(), signature_config.key.clone())
+        );
+    }
+    let client = Arc::new(client_builder.build());
+    cfg.app_data(web::Data::new(match &service_config.signature_config {
+        Some(signature_config) => SignatureVerifier::new(signature_config, redis.clone()),
+        None => SignatureVerifier::disabled(),
+    }));
 
     let fe_micro_service_repository = Arc::new(FeMicroServiceRepository::new(pool.clone()));
     #[allow(deprecated)]
@@ -91,9 +105,11 @@ pub fn load_components(
     cfg.app_data(web::Data::new(micro_app_service));
 
     let menu_repository = Arc::new(MenuRepository::new(pool.clone()));
+    let role_repository = Arc::new(RoleRepository::new(pool.clone()));
     let menu_service = MenuService::builder()
         .id_api(id_api.clone())
         .menu_repository(menu_repository)
+        .role_repository(Arc::clone(&role_repository))
         .build();
     cfg.app_data(web::Data::new(menu_service));
 
@@ -107,20 +123,193 @@ pub fn load_components(
     let profile_repository = Arc::new(ProfileRepository::new(pool.clone()));
     let ticket_repository = Arc::new(repository::ticket::Repository::new(pool.clone()));
 
+    let jwt_keys = Arc::new(
+        JwtKeys::load(&service_config.uc_config.jwt, service_config.uc_config.secret.as_str())
+            .expect("failed to load jwt keys")
+    );
+    let token_service: Arc<TokenService> = Arc::new(
+        TokenService::new(
+            redis.clone(),
+            Arc::clone(&ticket_repository),
+            Arc::new(repository::api_key::Repository::new(pool.clone())),
+            Arc::clone(&account_repository),
+            vec![
+                "/api/system/account/login".to_string(),
+                "/api/system/account/login/mfa".to_string(),
+                "/api/system/account/login/code/send".to_string(),
+                "/api/system/account/login/code".to_string(),
+                "/api/system/account/login/qr/create".to_string(),
+                "/api/system/account/login/qr/status".to_string(),
+                "/api/system/account/login/qr".to_string(),
+                "/api/system/account/password/renew".to_string(),
+                "/api/system/account/token/refresh".to_string(),
+                "/.well-known/jwks.json".to_string(),
+                "/.well-known/openid-configuration".to_string(),
+                "/api/system/oauth2/authorize".to_string(),
+                "/api/system/oauth2/token".to_string(),
+                "/api/system/federation/providers".to_string(),
+                "/api/system/federation/authorize".to_string(),
+                "/api/system/federation/login".to_string(),
+            ],
+            vec![
+                "/interface".to_string(),
+                "/swagger-ui".to_string(),
+                "/api-docs".to_string(),
+            ],
+            Arc::clone(&jwt_keys),
+            Duration::from_secs(service_config.uc_config.ticket_expire.unwrap_or(604800)),
+            Duration::from_secs(service_config.uc_config.access_expire.unwrap_or(1800)),
+        )
+    );
+    cfg.app_data(web::Data::new(Arc::clone(&token_service)));
+    let dyn_token_service: Arc<dyn TokenHandler> = Arc::clone(&token_service) as Arc<dyn TokenHandler>;
+    cfg.app_data(web::Data::new(dyn_token_service));
+    cfg.app_data(web::Data::new(service_config.server_config.session.clone()));
+
     let log_api = Arc::new(
         LogApi::build(Arc::clone(&client), service_config.api_config.log_service.clone(), None)
     );
-    cfg.app_data(web::Data::new(AccountService::new(
-        account_repository,
-        passport_repository,
-        redis.clone(),
-        Arc::clone(&log_api),
-        Arc::clone(&ticket_repository),
-        Arc::clone(&token_service),
-        id_api.clone(),
-        ip_api,
-        ua_api,
-    )));
-    cfg.app_data(web::Data::new(ProfileService::new(profile_repository)));
+    let audit_service = Arc::new(AuditService::new(Arc::clone(&log_api)));
+    let ticket_service = TicketService::builder()
+        .token_service(Arc::clone(&token_service))
+        .ticket_repository(Arc::clone(&ticket_repository))
+        .log_api(Arc::clone(&log_api))
+        .audit_service(Arc::clone(&audit_service))
+        .build();
+    cfg.app_data(web::Data::new(ticket_service));
+
+    let argon2_config = &service_config.uc_config.argon2;
+    let password_params = argon2::Params::new(
+        argon2_config.memory_cost,
+        argon2_config.time_cost,
+        argon2_config.parallelism,
+        None,
+    )
+    .expect("invalid argon2 config");
+    let mfa_service = Arc::new(
+        MfaService::builder()
+            .mfa_repository(Arc::new(repository::mfa::Repository::new(pool.clone())))
+            .account_repository(Arc::clone(&account_repository))
+            .audit_service(Arc::clone(&audit_service))
+            .redis(redis.clone())
+            .issuer(service_config.uc_config.mfa_issuer.clone().unwrap_or("Laurel".to_string()))
+            .build()
+    );
+    cfg.app_data(web::Data::from(Arc::clone(&mfa_service)));
+
+    let device_service = Arc::new(
+        DeviceService::builder()
+            .device_repository(Arc::new(repository::device::Repository::new(pool.clone())))
+            .token_service(Arc::clone(&token_service))
+            .audit_service(Arc::clone(&audit_service))
+            .build()
+    );
+    cfg.app_data(web::Data::from(Arc::clone(&device_service)));
+
+    let account_service = Arc::new(AccountService::builder()
+        .account_repository(Arc::clone(&account_repository))
+        .passport_repository(passport_repository)
+        .redis(redis.clone())
+        .log_api(Arc::clone(&log_api))
+        .ticket_repository(Arc::clone(&ticket_repository))
+        .token_service(Arc::clone(&token_service))
+        .id_api(id_api.clone())
+        .ip_api(ip_api)
+        .ua_api(ua_api)
+        .password_params(password_params)
+        .password_policy(PasswordPolicy::new(service_config.uc_config.password_policy.clone()))
+        .audit_service(Arc::clone(&audit_service))
+        .login_guard(Arc::new(LoginGuard::new(redis.clone(), service_config.uc_config.login_guard.clone())))
+        .mfa_service(mfa_service)
+        .device_service(device_service)
+        .session_limiter(Arc::new(
+            SessionLimiter::builder()
+                .redis(redis.clone())
+                .ticket_repository(Arc::clone(&ticket_repository))
+                .token_service(Arc::clone(&token_service))
+                .audit_service(Arc::clone(&audit_service))
+                .config(service_config.uc_config.session_limit.clone())
+                .build()
+        ))
+        .login_code_service(Arc::new(LoginCodeService::new(
+            redis.clone(),
+            code_sender::from_config(&service_config.uc_config.login_code),
+            service_config.uc_config.login_code.clone(),
+        )))
+        .build());
+    cfg.app_data(web::Data::from(Arc::clone(&account_service)));
+    cfg.app_data(web::Data::new(ProfileService::new(Arc::clone(&profile_repository))));
+
+    let permission_cache = Arc::new(CachedPermissionHandler::new(
+        Arc::new(PermissionService::new(Arc::clone(&role_repository))),
+        Duration::from_secs(service_config.uc_config.permission_cache_ttl.unwrap_or(60)),
+    ));
+    let dyn_permission_handler: Arc<dyn PermissionHandler> = Arc::clone(&permission_cache) as Arc<dyn PermissionHandler>;
+    cfg.app_data(web::Data::new(dyn_permission_handler));
+
+    let qr_login_service = QrLoginService::builder()
+        .redis(redis.clone())
+        .account_repository(Arc::clone(&account_repository))
+        .account_service(Arc::clone(&account_service))
+        .audit_service(Arc::clone(&audit_service))
+        .config(service_config.uc_config.qr_login.clone())
+        .build();
+    cfg.app_data(web::Data::new(qr_login_service));
+
+    let federation_service = FederationService::builder()
+        .identity_repository(Arc::new(repository::federation::Repository::new(pool.clone())))
+        .account_repository(Arc::clone(&account_repository))
+        .profile_repository(Arc::clone(&profile_repository))
+        .account_service(account_service)
+        .redis(redis.clone())
+        .http(
+            reqwest::ClientBuilder::new()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("failed to build federation client")
+        )
+        .config(service_config.uc_config.federation.clone())
+        .build();
+    cfg.app_data(web::Data::new(federation_service));
+
+    let oauth_service = OAuthService::builder()
+        .client_repository(Arc::new(repository::oauth::Repository::new(pool.clone())))
+        .account_repository(Arc::clone(&account_repository))
+        .profile_repository(profile_repository)
+        .ticket_repository(Arc::clone(&ticket_repository))
+        .token_service(Arc::clone(&token_service))
+        .jwt_keys(Arc::clone(&jwt_keys))
+        .audit_service(Arc::clone(&audit_service))
+        .redis(redis.clone())
+        .id_api(id_api.clone())
+        .config(service_config.uc_config.oauth.clone())
+        .build();
+    cfg.app_data(web::Data::new(oauth_service));
+
+    let api_key_service = ApiKeyService::builder()
+        .api_key_repository(Arc::new(repository::api_key::Repository::new(pool.clone())))
+        .permission_cache(Arc::clone(&permission_cache))
+        .audit_service(Arc::clone(&audit_service))
+        .build();
+    cfg.app_data(web::Data::new(api_key_service));
+
+    let impersonation_service = ImpersonationService::builder()
+        .account_repository(account_repository)
+        .ticket_repository(Arc::clone(&ticket_repository))
+        .token_service(Arc::clone(&token_service))
+        .permission_cache(Arc::clone(&permission_cache))
+        .audit_service(Arc::clone(&audit_service))
+        .id_api(id_api.clone())
+        .config(service_config.uc_config.impersonation.clone())
+        .build();
+    cfg.app_data(web::Data::new(impersonation_service));
+
+    let role_service = RoleService::builder()
+        .role_repository(Arc::clone(&role_repository))
+        .audit_service(Arc::clone(&audit_service))
+        .permission_cache(permission_cache)
+        .id_api(id_api.clone())
+        .build();
+    cfg.app_data(web::Data::new(role_service));
 }
 
@@ -0,0 +1,22 @@
+//! 系统服务业务状态码
+
+/// 账户登录失败次数过多, 暂时锁定
+pub const ACCOUNT_LOCKED: u16 = 10001;
+/// 来源ip登录失败次数过多, 暂时锁定
+pub const IP_LOCKED: u16 = 10002;
+/// 账户已被禁用
+pub const ACCOUNT_DISABLED: u16 = 10003;
+/// 账户已被管理员锁定
+pub const ACCOUNT_FROZEN: u16 = 10004;
+/// 账户待激活
+pub const ACCOUNT_PENDING: u16 = 10005;
+/// 验证码发送过于频繁
+pub const LOGIN_CODE_TOO_FREQUENT: u16 = 10006;
+/// 验证码发送次数超过每小时上限
+pub const LOGIN_CODE_LIMITED: u16 = 10007;
+/// 密码不符合安全策略, data 为逐条的失败规则
+pub const PASSWORD_POLICY: u16 = 10008;
+/// 在线会话数已达上限, 拒绝本次登录
+pub const SESSION_LIMIT: u16 = 10009;
+/// 会话因同一账户在别处登录超出上限而被下线
+pub const SESSION_REPLACED: u16 = 10010;
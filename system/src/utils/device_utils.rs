@@ -0,0 +1,55 @@
+use data_encoding::HEXLOWER;
+use laurel_tool_api::ua::Ua;
+use sha2::{Digest, Sha256};
+
+/// 设备指纹: 客户端设备id + 浏览器、系统与设备型号, 不含版本号以免升级后变化
+pub fn fingerprint(device_id: &str, ua: Option<&Ua>) -> String {
+    let browser = ua
+        .and_then(|u| u.browser.as_ref())
+        .and_then(|b| b.family.as_deref())
+        .unwrap_or_default();
+    let os = ua
+        .and_then(|u| u.os.as_ref())
+        .and_then(|o| o.os.as_deref())
+        .unwrap_or_default();
+    let (brand, model) = ua
+        .and_then(|u| u.device.as_ref())
+        .map(|d| (d.brand.as_deref().unwrap_or_default(), d.model.as_deref().unwrap_or_default()))
+        .unwrap_or_default();
+    let source = format!("{}|{}|{}|{}|{}", device_id.trim(), browser, os, brand, model);
+    HEXLOWER.encode(&Sha256::digest(source.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use laurel_tool_api::ua::{UaBrowser, UaOs};
+
+    fn ua(major: &str) -> Ua {
+        Ua {
+            browser: Some(UaBrowser {
+                family: Some("Chrome".to_string()),
+                major: Some(major.to_string()),
+                minor: None,
+                patch: None,
+            }),
+            os: Some(UaOs {
+                os: Some("Mac OS X".to_string()),
+                major: None,
+                minor: None,
+                patch: None,
+                patch_minor: None,
+            }),
+            device: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let fp = fingerprint("d1", Some(&ua("120")));
+        assert_eq!(fp.len(), 64);
+        assert_eq!(fp, fingerprint(" d1 ", Some(&ua("121"))));
+        assert_ne!(fp, fingerprint("d2", Some(&ua("120"))));
+        assert_ne!(fp, fingerprint("d1", None));
+    }
+}
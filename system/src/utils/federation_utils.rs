@@ -0,0 +1,131 @@
+use anyhow::Error;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use crate::model::federation::FederatedClaims;
+use crate::utils::oauth_utils;
+
+/// 身份源与本服务的时钟偏差容忍(秒)
+const LEEWAY: u64 = 60;
+
+/// 身份源必须使用非对称算法签名 id_token
+fn asymmetric(algorithm: Algorithm) -> bool {
+    !matches!(algorithm, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+/// 按身份源公钥校验 id_token 的签名、iss、aud、exp 与 nonce
+pub fn validate_id_token(id_token: &str, jwks: &JwkSet, issuer: &str, client_id: &str, nonce: &str) -> anyhow::Result<FederatedClaims> {
+    let header = jsonwebtoken::decode_header(id_token)?;
+    if !asymmetric(header.alg) {
+        return Err(Error::msg(format!("id token algorithm: {:?} not allowed", header.alg)));
+    }
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        // 未指定 kid 时身份源只能有一个密钥
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| Error::msg(format!("id token key: {:?} not found", header.kid)))?;
+    let key = DecodingKey::from_jwk(jwk)?;
+    let mut validation = Validation::new(header.alg);
+    validation.set_required_spec_claims(&["exp", "iat", "iss", "aud", "sub"]);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.leeway = LEEWAY;
+    let claims = jsonwebtoken::decode::<FederatedClaims>(id_token, &key, &validation)?.claims;
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(Error::msg("id token nonce mismatch"));
+    }
+    Ok(claims)
+}
+
+/// 自动创建账户的账户名: 身份源标识 + sub, 超长时以 sub 摘要代替
+pub fn account_name(provider: &str, subject: &str) -> String {
+    let name = format!("{}:{}", provider, subject);
+    if name.len() <= 64 {
+        return name;
+    }
+    format!("{}:{}", provider, &oauth_utils::secret_hash(subject)[..40])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use serde::Serialize;
+    use crate::{JwtConfig, JwtKeyConfig};
+    use crate::utils::jwt_utils::JwtKeys;
+
+    #[derive(Serialize)]
+    struct IdToken<'a> {
+        iss: &'a str,
+        sub: &'a str,
+        aud: &'a str,
+        iat: i64,
+        exp: i64,
+        nonce: &'a str,
+        email: &'a str,
+        email_verified: &'a str,
+    }
+
+    /// 以本地生成的密钥充当身份源
+    fn stub_provider() -> JwtKeys {
+        let signing = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        let dir = std::env::temp_dir();
+        let private_key = dir.join("laurel_federation_test_idp.pem");
+        let public_key = dir.join("laurel_federation_test_idp.pub.pem");
+        std::fs::write(&private_key, signing.to_pkcs8_pem(LineEnding::LF).unwrap().as_bytes()).unwrap();
+        std::fs::write(&public_key, signing.verifying_key().to_public_key_pem(LineEnding::LF).unwrap()).unwrap();
+        let config = JwtConfig {
+            signing_kid: Some("idp".to_string()),
+            keys: vec![JwtKeyConfig {
+                kid: "idp".to_string(),
+                algorithm: "EdDSA".to_string(),
+                private_key: Some(private_key.to_string_lossy().to_string()),
+                public_key: public_key.to_string_lossy().to_string(),
+            }],
+            ..Default::default()
+        };
+        JwtKeys::load(&config, "").unwrap()
+    }
+
+    fn id_token(keys: &JwtKeys, aud: &str, nonce: &str) -> String {
+        let now = chrono::Local::now().timestamp();
+        keys.encode(&IdToken {
+            iss: "https://idp.example.com",
+            sub: "u-1",
+            aud,
+            iat: now,
+            exp: now + 300,
+            nonce,
+            email: "alice@example.com",
+            email_verified: "true",
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_id_token() {
+        let provider = stub_provider();
+        let issuer = "https://idp.example.com";
+        let claims = validate_id_token(&id_token(&provider, "laurel", "n1"), provider.jwks(), issuer, "laurel", "n1").unwrap();
+        assert_eq!(claims.sub, "u-1");
+        assert_eq!(claims.email.as_deref(), Some("alice@example.com"));
+        assert!(claims.email_verified());
+
+        assert!(validate_id_token(&id_token(&provider, "other", "n1"), provider.jwks(), issuer, "laurel", "n1").is_err());
+        assert!(validate_id_token(&id_token(&provider, "laurel", "n2"), provider.jwks(), issuer, "laurel", "n1").is_err());
+        assert!(validate_id_token(&id_token(&provider, "laurel", "n1"), provider.jwks(), "https://evil.example.com", "laurel", "n1").is_err());
+
+        // 共享密钥签名的令牌不予接受
+        let hmac = JwtKeys::load(&JwtConfig::default(), "secret").unwrap();
+        assert!(validate_id_token(&id_token(&hmac, "laurel", "n1"), provider.jwks(), issuer, "laurel", "n1").is_err());
+    }
+
+    #[test]
+    fn test_account_name() {
+        assert_eq!(account_name("corp", "u-1"), "corp:u-1");
+        let name = account_name("corp", "x".repeat(100).as_str());
+        assert!(name.starts_with("corp:") && name.len() == 45);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+use anyhow::Error;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use data_encoding::BASE64URL_NOPAD;
+use ed25519_dalek::pkcs8::DecodePublicKey;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rsa::traits::PublicKeyParts;
+use crate::{JwtConfig, JwtKeyConfig};
+
+/// 签名与验签密钥, 按 kid 选择验签密钥
+pub struct JwtKeys {
+    issuer: String,
+    audience: Vec<String>,
+    /// (kid, 算法, 私钥); 未配置密钥时 kid 为空, 使用共享密钥 HS256
+    signing: (Option<String>, Algorithm, EncodingKey),
+    verifying: HashMap<String, (Algorithm, DecodingKey)>,
+    /// 共享密钥模式下的验签密钥
+    secret: Option<DecodingKey>,
+    jwks: JwkSet,
+}
+
+impl Debug for JwtKeys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKeys")
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("signing_kid", &self.signing.0)
+            .field("kids", &self.verifying.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl JwtKeys {
+    /// 按配置加载密钥, keys 为空时退回 secret + HS256
+    pub fn load(config: &JwtConfig, secret: &str) -> anyhow::Result<Self> {
+        if config.keys.is_empty() {
+            return Ok(Self {
+                issuer: config.issuer.clone(),
+                audience: config.audience.clone(),
+                signing: (None, Algorithm::HS256, EncodingKey::from_secret(secret.as_bytes())),
+                verifying: HashMap::new(),
+                secret: Some(DecodingKey::from_secret(secret.as_bytes())),
+                jwks: JwkSet { keys: vec![] },
+            });
+        }
+        let signing_kid = config
+            .signing_kid
+            .clone()
+            .ok_or_else(|| Error::msg("jwt signing_kid is required when keys are configured"))?;
+        let mut signing = None;
+        let mut verifying = HashMap::new();
+        let mut jwks = vec![];
+        for key in &config.keys {
+            let (private_pem, public_pem) = read_pem(key)?;
+            let algorithm = algorithm(key.algorithm.as_str())?;
+            if key.kid == signing_kid {
+                let private_pem = private_pem
+                    .ok_or_else(|| Error::msg(format!("jwt key: {} has no private key", key.kid)))?;
+                signing = Some((Some(key.kid.clone()), algorithm, encoding_key(algorithm, private_pem.as_str())?));
+            }
+            verifying.insert(key.kid.clone(), (algorithm, decoding_key(algorithm, public_pem.as_str())?));
+            jwks.push(jwk(key.kid.as_str(), algorithm, public_pem.as_str())?);
+        }
+        Ok(Self {
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            signing: signing.ok_or_else(|| Error::msg(format!("jwt signing key: {} not found", signing_kid)))?,
+            verifying,
+            secret: None,
+            jwks: JwkSet { keys: jwks },
+        })
+    }
+
+    pub fn issuer(&self) -> &str {
+        self.issuer.as_str()
+    }
+
+    pub fn audience(&self) -> &Vec<String> {
+        &self.audience
+    }
+
+    /// 签名算法名称, 如 RS256
+    pub fn algorithm(&self) -> String {
+        format!("{:?}", self.signing.1)
+    }
+
+    pub fn encode<T: serde::Serialize>(&self, claims: &T) -> anyhow::Result<String> {
+        let (kid, algorithm, key) = &self.signing;
+        let mut header = Header::new(*algorithm);
+        header.kid = kid.clone();
+        Ok(jsonwebtoken::encode(&header, claims, key)?)
+    }
+
+    /// 按 header.kid 选择密钥并校验 exp/iat/iss/aud
+    pub fn decode<T: serde::de::DeserializeOwned>(&self, token: &str) -> anyhow::Result<T> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let (algorithm, key) = match (&header.kid, &self.secret) {
+            (None, Some(secret)) => (Algorithm::HS256, secret),
+            (Some(kid), None) => match self.verifying.get(kid) {
+                Some((algorithm, key)) => (*algorithm, key),
+                None => return Err(Error::msg(format!("unknown jwt kid: {}", kid))),
+            },
+            _ => return Err(Error::msg("jwt kid mismatch")),
+        };
+        let mut validation = Validation::new(algorithm);
+        validation.set_required_spec_claims(&["exp", "iat", "iss", "aud"]);
+        validation.set_issuer(&[self.issuer.as_str()]);
+        validation.set_audience(&self.audience);
+        validation.leeway = 0;
+        Ok(jsonwebtoken::decode::<T>(token, key, &validation)?.claims)
+    }
+
+    /// 对外公开的验签公钥
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+fn read_pem(key: &JwtKeyConfig) -> anyhow::Result<(Option<String>, String)> {
+    let private_pem = match &key.private_key {
+        Some(path) => Some(std::fs::read_to_string(path)?),
+        None => None,
+    };
+    Ok((private_pem, std::fs::read_to_string(key.public_key.as_str())?))
+}
+
+fn algorithm(name: &str) -> anyhow::Result<Algorithm> {
+    match Algorithm::from_str(name)? {
+        a @ (Algorithm::RS256 | Algorithm::EdDSA) => Ok(a),
+        a => Err(Error::msg(format!("unsupported jwt algorithm: {:?}", a))),
+    }
+}
+
+fn encoding_key(algorithm: Algorithm, pem: &str) -> anyhow::Result<EncodingKey> {
+    Ok(match algorithm {
+        Algorithm::EdDSA => EncodingKey::from_ed_pem(pem.as_bytes())?,
+        _ => EncodingKey::from_rsa_pem(pem.as_bytes())?,
+    })
+}
+
+fn decoding_key(algorithm: Algorithm, pem: &str) -> anyhow::Result<DecodingKey> {
+    Ok(match algorithm {
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem.as_bytes())?,
+        _ => DecodingKey::from_rsa_pem(pem.as_bytes())?,
+    })
+}
+
+/// 由公钥 PEM 生成 JWK
+fn jwk(kid: &str, algorithm: Algorithm, pem: &str) -> anyhow::Result<Jwk> {
+    let (key_algorithm, parameters) = match algorithm {
+        Algorithm::EdDSA => {
+            let key = ed25519_dalek::VerifyingKey::from_public_key_pem(pem)?;
+            (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: BASE64URL_NOPAD.encode(key.as_bytes()),
+                }),
+            )
+        }
+        _ => {
+            let key = rsa::RsaPublicKey::from_public_key_pem(pem)?;
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: BASE64URL_NOPAD.encode(&key.n().to_bytes_be()),
+                    e: BASE64URL_NOPAD.encode(&key.e().to_bytes_be()),
+                }),
+            )
+        }
+    };
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+/// 数据库中的本地时间转为 JWT 使用的 Unix 时间戳(秒)
+pub fn timestamp(local: NaiveDateTime) -> i64 {
+    match Local.from_local_datetime(&local).earliest() {
+        Some(t) => t.timestamp(),
+        // 夏令时跳过的时刻, 按当前时区偏移换算
+        None => local.and_utc().timestamp() - Local::now().offset().local_minus_utc() as i64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        iss: String,
+        aud: Vec<String>,
+        iat: u64,
+        exp: u64,
+    }
+
+    fn claims(aud: &str) -> Claims {
+        let now = chrono::Local::now().timestamp() as u64;
+        Claims {
+            sub: "1".to_string(),
+            iss: "laurel-system".to_string(),
+            aud: vec![aud.to_string()],
+            iat: now,
+            exp: now + 60,
+        }
+    }
+
+    #[test]
+    fn test_ed25519_keys() {
+        let signing = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let dir = std::env::temp_dir();
+        let private_key = dir.join("laurel_jwt_test_k1.pem");
+        let public_key = dir.join("laurel_jwt_test_k1.pub.pem");
+        std::fs::write(&private_key, signing.to_pkcs8_pem(LineEnding::LF).unwrap().as_bytes()).unwrap();
+        std::fs::write(&public_key, signing.verifying_key().to_public_key_pem(LineEnding::LF).unwrap()).unwrap();
+        let config = JwtConfig {
+            signing_kid: Some("k1".to_string()),
+            keys: vec![JwtKeyConfig {
+                kid: "k1".to_string(),
+                algorithm: "EdDSA".to_string(),
+                private_key: Some(private_key.to_string_lossy().to_string()),
+                public_key: public_key.to_string_lossy().to_string(),
+            }],
+            ..Default::default()
+        };
+        let keys = JwtKeys::load(&config, "").unwrap();
+        let token = keys.encode(&claims("laurel")).unwrap();
+        assert_eq!(jsonwebtoken::decode_header(&token).unwrap().kid.as_deref(), Some("k1"));
+        assert_eq!(keys.decode::<Claims>(&token).unwrap().sub, "1");
+        assert!(keys.decode::<Claims>(&keys.encode(&claims("other")).unwrap()).is_err());
+        assert_eq!(keys.jwks().find("k1").map(|k| k.is_supported()), Some(true));
+
+        // 共享密钥签发的令牌不能通过非对称密钥验签
+        let hmac = JwtKeys::load(&JwtConfig::default(), "secret").unwrap();
+        assert!(keys.decode::<Claims>(&hmac.encode(&claims("laurel")).unwrap()).is_err());
+        assert!(hmac.jwks().keys.is_empty());
+    }
+
+    #[test]
+    fn test_timestamp_non_utc() {
+        // 东八区, 不依赖系统时区数据
+        unsafe { std::env::set_var("TZ", "CST-8") };
+        assert_eq!(Local::now().offset().local_minus_utc(), 8 * 3600);
+        let keys = JwtKeys::load(&JwtConfig::default(), "secret").unwrap();
+        let mut c = claims("laurel");
+        c.exp = timestamp(Local::now().naive_local() + chrono::Duration::seconds(60)) as u64;
+        let exp = keys.decode::<Claims>(&keys.encode(&c).unwrap()).unwrap().exp as i64;
+        let remain = exp - chrono::Utc::now().timestamp();
+        assert!((59..=60).contains(&remain), "exp - now = {}", remain);
+    }
+}
@@ -1,2 +1,9 @@
 pub mod passport_utils;
 pub mod token_utils;
+pub mod codes;
+pub mod totp_utils;
+pub mod jwt_utils;
+pub mod password_policy;
+pub mod device_utils;
+pub mod oauth_utils;
+pub mod federation_utils;
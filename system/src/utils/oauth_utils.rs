@@ -0,0 +1,115 @@
+use data_encoding::BASE64URL_NOPAD;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+
+/// 查询参数编码, 保留 RFC 3986 非保留字符
+const QUERY: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// PKCE S256: BASE64URL(SHA256(code_verifier))
+pub fn code_challenge(code_verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// 校验 code_verifier 格式(RFC 7636: 43-128 位非保留字符)及与 code_challenge 是否匹配
+pub fn verify_pkce(code_verifier: &str, code_challenge: &str) -> bool {
+    let valid = (43..=128).contains(&code_verifier.len())
+        && code_verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+    valid && self::code_challenge(code_verifier) == code_challenge
+}
+
+/// id_token 的 at_hash: access token 摘要的左半部分
+pub fn at_hash(access_token: &str) -> String {
+    let digest = Sha256::digest(access_token.as_bytes());
+    BASE64URL_NOPAD.encode(&digest[..digest.len() / 2])
+}
+
+/// 客户端密钥摘要
+pub fn secret_hash(secret: &str) -> String {
+    data_encoding::HEXLOWER.encode(&Sha256::digest(secret.as_bytes()))
+}
+
+/// 按摘要校验密钥, 逐字节比较耗时固定
+pub fn secret_matches(secret: &str, hash: &str) -> bool {
+    let expected = secret_hash(secret);
+    expected.len() == hash.len()
+        && expected
+            .bytes()
+            .zip(hash.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// 申请的 scope 与允许的 scope 取交集, 保持申请顺序并去重
+pub fn grant_scope(requested: &str, allowed: &[&str]) -> String {
+    let mut granted: Vec<&str> = vec![];
+    for scope in requested.split_whitespace() {
+        if allowed.contains(&scope) && !granted.contains(&scope) {
+            granted.push(scope);
+        }
+    }
+    granted.join(" ")
+}
+
+/// OIDC 标准 scope
+pub static OIDC_SCOPES: [&str; 4] = ["openid", "profile", "email", "phone"];
+
+/// 仅保留 OIDC 标准 scope
+pub fn oidc_scopes(scope: &str) -> Vec<String> {
+    scope
+        .split_whitespace()
+        .filter(|s| OIDC_SCOPES.contains(s))
+        .map(|s| s.to_string())
+        .collect()
+}
+
+pub fn has_scope(scope: &str, name: &str) -> bool {
+    scope.split_whitespace().any(|s| s == name)
+}
+
+/// 在回调地址上追加查询参数
+pub fn redirect_with(redirect_uri: &str, params: &[(&str, &str)]) -> String {
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, percent_encoding::utf8_percent_encode(v, QUERY)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", redirect_uri, separator, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce() {
+        // RFC 7636 附录 B 示例
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert_eq!(code_challenge(verifier), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert!(verify_pkce(verifier, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"));
+        assert!(!verify_pkce(verifier, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cN"));
+        assert!(!verify_pkce("short", code_challenge("short").as_str()));
+    }
+
+    #[test]
+    fn test_grant_scope() {
+        let allowed = ["openid", "profile", "email"];
+        assert_eq!(grant_scope("openid email phone openid", &allowed), "openid email");
+        assert_eq!(grant_scope("", &allowed), "");
+        assert!(has_scope("openid email", "email"));
+        assert!(!has_scope("openid emails", "email"));
+    }
+
+    #[test]
+    fn test_redirect_with() {
+        assert_eq!(
+            redirect_with("https://app.example.com/cb", &[("code", "abc"), ("state", "x y")]),
+            "https://app.example.com/cb?code=abc&state=x%20y"
+        );
+        assert_eq!(
+            redirect_with("https://app.example.com/cb?from=1", &[("error", "access_denied")]),
+            "https://app.example.com/cb?from=1&error=access_denied"
+        );
+    }
+}
@@ -1,7 +1,14 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{Engine, engine::general_purpose::STANDARD};
 use rand::RngCore;
 use sha1::{Digest, Sha1};
 
+/// 旧版: sha1(utf16(account_id + password) + salt)
+pub static HASH_VERSION_SHA1: &str = "sha1";
+/// PHC 格式的 argon2id 字符串, 盐与参数均包含在内
+pub static HASH_VERSION_ARGON2ID: &str = "argon2id";
+
 //pub struct Passports;
 
 //impl Passports {
@@ -22,6 +29,35 @@ use sha1::{Digest, Sha1};
         Ok(STANDARD.encode(result))
     }
 
+    /// argon2id 生成秘钥, salt 为 [`salt`] 生成的 base64 字符串
+    pub fn argon2id(password: &str, salt: &str, params: &Params) -> anyhow::Result<String> {
+        let salt = SaltString::encode_b64(&STANDARD.decode(salt)?)?;
+        let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+            .hash_password(password.as_bytes(), &salt)?;
+        Ok(hash.to_string())
+    }
+
+    /// 按版本校验密码
+    pub fn verify(
+        account_id: &str,
+        password: &str,
+        salt: &str,
+        hash: &str,
+        hash_version: &str,
+    ) -> anyhow::Result<bool> {
+        if hash_version == HASH_VERSION_ARGON2ID {
+            let parsed = PasswordHash::new(hash)?;
+            // 参数取自 hash 本身, 修改配置不影响已有密码
+            return Ok(Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok());
+        }
+        if hash_version == HASH_VERSION_SHA1 {
+            return Ok(self::password(account_id, password, salt)? == hash);
+        }
+        Err(anyhow::Error::msg(format!("unknown hash version: {}", hash_version)))
+    }
+
     /// 账户信息组合成字节数组
     fn strings_to_bytes(account_id: &str, password: &str, salt: &str) -> anyhow::Result<Vec<u8>> {
         let b1 = account_id
@@ -65,4 +101,23 @@ mod tests {
         assert_eq!(result1, result2);
         Ok(())
     }
+
+    #[test]
+    fn test_verify_sha1() -> Result<(), Box<dyn std::error::Error>> {
+        let salt = "knVrM0F8Pj1nqqPLj9gh4g==";
+        let hash = "+ugCaoPg8wHg8iM9caIKGJ4QjDE=";
+        assert!(verify("1958358443601567744", "110120", salt, hash, HASH_VERSION_SHA1)?);
+        assert!(!verify("1958358443601567744", "110121", salt, hash, HASH_VERSION_SHA1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_argon2id() -> Result<(), Box<dyn std::error::Error>> {
+        let params = Params::new(Params::MIN_M_COST, 1, 1, None)?;
+        let hash = argon2id("110120", salt().as_str(), &params)?;
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify("", "110120", "", hash.as_str(), HASH_VERSION_ARGON2ID)?);
+        assert!(!verify("", "110121", "", hash.as_str(), HASH_VERSION_ARGON2ID)?);
+        Ok(())
+    }
 }
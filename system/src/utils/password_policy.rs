@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use chrono::{Duration, NaiveDateTime};
+use crate::PasswordPolicyConfig;
+use crate::model::passport::PolicyViolation;
+
+/// 内置的常见弱密码, 比较时忽略大小写
+static COMMON_PASSWORDS: [&str; 20] = [
+    "password", "password1", "password123", "passw0rd", "12345678",
+    "123456789", "1234567890", "11111111", "88888888", "00000000",
+    "qwerty123", "qwertyuiop", "1q2w3e4r", "1qaz2wsx", "abc12345",
+    "abcd1234", "iloveyou", "admin123", "admin@123", "welcome1",
+];
+
+/// 密码策略: 长度、字符类别、弱密码与最长有效期
+#[derive(Debug)]
+pub struct PasswordPolicy {
+    config: PasswordPolicyConfig,
+    banned: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    pub fn new(config: PasswordPolicyConfig) -> Self {
+        let banned = COMMON_PASSWORDS
+            .iter()
+            .map(|p| p.to_string())
+            .chain(config.banned_passwords.iter().map(|p| p.to_lowercase()))
+            .collect();
+        Self { config, banned }
+    }
+
+    /// 逐条校验, 返回全部不满足的规则
+    pub fn check(&self, password: &str, account_name: &str) -> Vec<PolicyViolation> {
+        let config = &self.config;
+        let mut violations = vec![];
+        let length = password.chars().count();
+        if length < config.min_length {
+            violations.push(PolicyViolation::new("min_length", format!("密码长度不能少于{}位", config.min_length)));
+        }
+        if length > config.max_length {
+            violations.push(PolicyViolation::new("max_length", format!("密码长度不能超过{}位", config.max_length)));
+        }
+        if config.require_lowercase && !password.chars().any(|c| c.is_ascii_lowercase()) {
+            violations.push(PolicyViolation::new("lowercase", "密码需包含小写字母"));
+        }
+        if config.require_uppercase && !password.chars().any(|c| c.is_ascii_uppercase()) {
+            violations.push(PolicyViolation::new("uppercase", "密码需包含大写字母"));
+        }
+        if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PolicyViolation::new("digit", "密码需包含数字"));
+        }
+        if config.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PolicyViolation::new("symbol", "密码需包含特殊字符"));
+        }
+        let lower = password.to_lowercase();
+        if self.banned.contains(&lower) {
+            violations.push(PolicyViolation::new("banned", "密码过于常见"));
+        }
+        if account_name.chars().count() >= 3 && lower.contains(&account_name.to_lowercase()) {
+            violations.push(PolicyViolation::new("account", "密码不能包含账户名"));
+        }
+        violations
+    }
+
+    /// 不允许重复使用的最近密码个数, 0 不限制
+    pub fn history_size(&self) -> i64 {
+        self.config.history_size
+    }
+
+    /// 密码是否超过最长有效期
+    pub fn expired(&self, pts: NaiveDateTime, now: NaiveDateTime) -> bool {
+        self.config.max_age_days > 0 && pts + Duration::days(self.config.max_age_days) < now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(violations: Vec<PolicyViolation>) -> Vec<String> {
+        violations.into_iter().map(|v| v.rule).collect()
+    }
+
+    #[test]
+    fn test_check() {
+        let policy = PasswordPolicy::new(PasswordPolicyConfig {
+            banned_passwords: vec!["Laurel@2024".to_string()],
+            ..Default::default()
+        });
+        assert!(policy.check("Tr0ub4dor&3", "admin").is_empty());
+        assert_eq!(rules(policy.check("abc", "admin")), vec!["min_length", "uppercase", "digit"]);
+        assert_eq!(rules(policy.check("Password123", "admin")), vec!["banned"]);
+        assert_eq!(rules(policy.check("laurel@2024A", "admin")), Vec::<String>::new());
+        assert_eq!(rules(policy.check("LAUREL@2024", "admin")), vec!["lowercase", "banned"]);
+        assert_eq!(rules(policy.check("Admin12345", "admin")), vec!["account"]);
+    }
+
+    #[test]
+    fn test_expired() {
+        let now = chrono::Local::now().naive_local();
+        let policy = PasswordPolicy::new(PasswordPolicyConfig::default());
+        assert!(!policy.expired(now - Duration::days(1000), now));
+        let policy = PasswordPolicy::new(PasswordPolicyConfig { max_age_days: 90, ..Default::default() });
+        assert!(!policy.expired(now - Duration::days(89), now));
+        assert!(policy.expired(now - Duration::days(91), now));
+    }
+}
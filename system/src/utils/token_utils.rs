@@ -1,3 +1,32 @@
+/// API key 前缀, 用于与 JWT 区分
+pub static API_KEY_PREFIX: &str = "lk_";
+
 pub fn token() -> String {
     uuid::Uuid::new_v4().simple().to_string()
 }
+
+/// API key: lk_{key_id}_{secret}
+pub fn api_key(key_id: &str, secret: &str) -> String {
+    format!("{}{}_{}", API_KEY_PREFIX, key_id, secret)
+}
+
+/// 拆分 API key 为 (key_id, secret), 非 API key 格式时返回 None
+pub fn parse_api_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix(API_KEY_PREFIX)?
+        .split_once('_')
+        .filter(|(key_id, secret)| !key_id.is_empty() && !secret.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_key() {
+        let key = api_key("abc", "s3cret");
+        assert_eq!(parse_api_key(key.as_str()), Some(("abc", "s3cret")));
+        assert_eq!(parse_api_key("lk_abc"), None);
+        assert_eq!(parse_api_key("lk__s3cret"), None);
+        assert_eq!(parse_api_key("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+    }
+}
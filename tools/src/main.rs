@@ -6,7 +6,7 @@ mod route;
 use clap::Parser;
 
 use serde::Deserialize;
-use laurel_actix::config::{load_config, AppArgs, ServerConfig};
+use laurel_actix::config::{load_config, AppArgs, RemoteTokenConfig, ServerConfig, SignatureConfig};
 use laurel_logging::types::LogConfig;
 use laurel_redis::{Redis, RedisConfig};
 use laurel_actix::{ActixApp};
@@ -23,6 +23,10 @@ pub struct AppConfig {
     pub redis_config: RedisConfig,
     pub ip_config: IpConfig,
     pub ua_config: UaConfig,
+    /// 配置后通过 uc 服务校验令牌
+    pub token_config: Option<RemoteTokenConfig>,
+    /// 服务间调用签名, 未配置时拒绝全部 /interface 请求
+    pub signature_config: Option<SignatureConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -2,11 +2,12 @@ pub mod ip_api {
     use crate::service::ip::IpSearchService;
     use actix_web::{post, web};
     use laurel_actix::Data;
+    use laurel_actix::signature::Signed;
     use laurel_actix::types::{Autowired, RequestBody, route};
     use laurel_tool_api::ip::{IpLocationBo, IpReqBo};
 
     pub fn config(cfg: &mut web::ServiceConfig) {
-        cfg.service(web::scope("/interface/tools/ip").service(ip_location));
+        cfg.service(web::scope("/interface/tools/ip").wrap(Signed).service(ip_location));
     }
 
     #[post("/location")]
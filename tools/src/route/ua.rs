@@ -2,11 +2,13 @@
 pub mod ua_api{
     use actix_web::{post, web};
     use laurel_actix::Data;
+    use laurel_actix::signature::Signed;
     use laurel_actix::types::{route, Autowired, RequestBody};
     use laurel_tool_api::ua::{UaBrowser, UaDevice, UaOs};
 
     pub fn config(cfg: &mut web::ServiceConfig) {
         cfg.service(web::scope("/interface/tools/ua")
+            .wrap(Signed)
             .service(parse_ua)
         );
     }
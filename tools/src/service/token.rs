@@ -37,7 +37,12 @@ impl TokenHandler for TokenService {
             Ok(
                 Some(
                     Token{
-                        account_id: "123".to_string()
+                        account_id: "123".to_string(),
+                        ticket_id: String::new(),
+                        impersonator: None,
+                        api_key: None,
+                        scopes: None,
+                        client_id: None,
                     }
                 )
             )
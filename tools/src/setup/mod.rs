@@ -1,6 +1,11 @@
 use std::sync::Arc;
 use actix_web::web;
 use laurel_actix::handler::TokenHandler;
+use laurel_actix::remote::RemoteTokenHandler;
+use laurel_actix::signature::SignatureVerifier;
+use laurel_middleware::signature::SigningMiddleware;
+use laurel_middleware::reqwest_middle::RequestLoggingMiddleware;
+use laurel_uc_api::account::AccountApi;
 use laurel_redis::Redis;
 use crate::{service, AppConfig};
 
@@ -16,19 +21,40 @@ pub fn setup(
     let ip_service = service::ip::IpSearchService::new(Arc::new(v4), Arc::new(v6), redis.clone());
     cfg.app_data(web::Data::new(ip_service));
 
-    let token_service: Arc<dyn TokenHandler> = Arc::new(
-        service::token::TokenService::new(
-            match &config.server_config.excludes {
-                Some(p) => p.clone(),
-                None => vec![]
-            },
-            match &config.server_config.exclude_starts {
-                Some(p) => p.clone(),
-                None => vec![]
+    let excludes = match &config.server_config.excludes {
+        Some(p) => p.clone(),
+        None => vec![]
+    };
+    let exclude_starts = match &config.server_config.exclude_starts {
+        Some(p) => p.clone(),
+        None => vec![]
+    };
+    let token_service: Arc<dyn TokenHandler> = match &config.token_config {
+        Some(token_config) => {
+            let mut client_builder = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+                .with(RequestLoggingMiddleware);
+            if let Some(signature_config) = &config.signature_config {
+                client_builder = client_builder.with(
+                    SigningMiddleware::new(signature_config.caller.clone(), signature_config.key.clone())
+                );
             }
-        )
-    );
+            let client = Arc::new(client_builder.build());
+            Arc::new(RemoteTokenHandler::new(
+                AccountApi::build(client, token_config.uc_service.clone(), None),
+                excludes,
+                exclude_starts,
+                token_config,
+            ))
+        },
+        None => Arc::new(service::token::TokenService::new(excludes, exclude_starts)),
+    };
     cfg.app_data(web::Data::new(token_service));
+    cfg.app_data(web::Data::new(config.server_config.session.clone()));
+
+    cfg.app_data(web::Data::new(match &config.signature_config {
+        Some(signature_config) => SignatureVerifier::new(signature_config, redis.clone()),
+        None => SignatureVerifier::disabled(),
+    }));
 
     let f = std::fs::File::open(&config.ua_config.path)
         .expect(